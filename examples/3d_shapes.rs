@@ -4,7 +4,7 @@ use std::f32::consts::PI;
 
 use bevy::{
     color::palettes::basic::SILVER,
-    core_pipeline::{core_3d::graph::Node3d, smaa::Smaa},
+    core_pipeline::smaa::Smaa,
     input::common_conditions::input_toggle_active,
     prelude::*,
     render::{
@@ -12,7 +12,7 @@ use bevy::{
         render_resource::{Extent3d, TextureDimension, TextureFormat},
     },
 };
-use bevy_edge_detection::{EdgeDetection, EdgeDetectionPlugin};
+use bevy_edge_detection::{EdgeDetection, EdgeDetectionPlugin, EdgePlacement};
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 
@@ -20,9 +20,8 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
         .add_plugins(EdgeDetectionPlugin {
-            // If you wish to apply Smaa anti-aliasing after edge detection,
-            // please ensure that the rendering order of [`EdgeDetectionNode`] is set before [`SmaaNode`].
-            before: Node3d::Smaa,
+            // Edges are drawn before Smaa, so they are anti-aliased with the scene.
+            placement: EdgePlacement::AfterTonemappingBeforeAA,
         })
         .add_plugins(EguiPlugin)
         .add_plugins(PanOrbitCameraPlugin)
@@ -276,6 +275,14 @@ fn edge_detection_ui(mut ctx: EguiContexts, mut edge_detection: Single<&mut Edge
                 ui.label("uv_distortion_strength");
             });
 
+            ui.add(
+                egui::Slider::new(&mut edge_detection.non_edge_desaturation, 0.0..=1.0)
+                    .text("non_edge_desaturation"),
+            );
+            ui.add(
+                egui::Slider::new(&mut edge_detection.strength_steps, 0..=8).text("strength_steps"),
+            );
+
             let mut color = edge_detection.edge_color.to_srgba().to_f32_array_no_alpha();
             ui.horizontal(|ui| {
                 egui::color_picker::color_edit_button_rgb(ui, &mut color);
@@ -0,0 +1,151 @@
+//! Stacks the edges with Bevy's bloom.
+//!
+//! By default the edges are drawn before bloom and pushed above the LDR range with
+//! `edge_emissive_strength`, so they glow like the emissive spheres. Run with `--no-glow` to draw
+//! them after bloom instead, where they stay crisp lines on top of the glowing scene:
+//!
+//! ```sh
+//! cargo run --example bloom -- --no-glow
+//! ```
+
+use bevy::{
+    core_pipeline::{bloom::Bloom, core_3d::graph::Core3d, core_3d::graph::Node3d},
+    prelude::*,
+    render::{
+        render_graph::{RenderGraph, RenderLabel},
+        RenderApp,
+    },
+};
+use bevy_edge_detection::{
+    EdgeDetection, EdgeDetectionComputeLabel, EdgeDetectionLabel, EdgeDetectionPlugin,
+    EdgePlacement,
+};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+
+fn main() {
+    let glow = !std::env::args().any(|arg| arg == "--no-glow");
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins)
+        .add_plugins(EdgeDetectionPlugin {
+            placement: if glow {
+                EdgePlacement::BeforeBloom
+            } else {
+                EdgePlacement::BeforeTonemapping
+            },
+        })
+        .add_plugins(PanOrbitCameraPlugin)
+        .add_systems(Startup, setup);
+
+    // The known-good orderings: the edges end before bloom starts when they should glow, and
+    // start after it ended otherwise.
+    if glow {
+        assert!(runs_before(&app, EdgeDetectionComputeLabel, Node3d::Bloom));
+    } else {
+        assert!(runs_before(&app, Node3d::Bloom, EdgeDetectionLabel));
+    }
+
+    app.insert_resource(EdgeEmissive(glow)).run();
+}
+
+/// Whether the Core3d graph orders `before` ahead of `after`, directly or through other nodes.
+fn runs_before(app: &App, before: impl RenderLabel, after: impl RenderLabel) -> bool {
+    let graph = app.sub_app(RenderApp).world().resource::<RenderGraph>();
+    let Some(core_3d) = graph.get_sub_graph(Core3d) else {
+        return false;
+    };
+
+    let after = after.intern();
+    let mut pending = vec![before.intern()];
+    let mut visited = Vec::new();
+
+    while let Some(label) = pending.pop() {
+        if label == after {
+            return true;
+        }
+        if visited.contains(&label) {
+            continue;
+        }
+        visited.push(label);
+
+        if let Ok(node) = core_3d.get_node_state(label) {
+            pending.extend(
+                node.edges
+                    .output_edges()
+                    .iter()
+                    .map(|edge| edge.get_input_node()),
+            );
+        }
+    }
+
+    false
+}
+
+#[derive(Resource)]
+struct EdgeEmissive(bool);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    edge_emissive: Res<EdgeEmissive>,
+) {
+    let sphere = meshes.add(Sphere::new(0.5).mesh().uv(32, 18));
+
+    for (i, color) in [
+        LinearRgba::rgb(8.0, 1.0, 0.5),
+        LinearRgba::rgb(0.5, 8.0, 1.0),
+        LinearRgba::rgb(1.0, 0.5, 8.0),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        commands.spawn((
+            Mesh3d(sphere.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::BLACK,
+                emissive: color,
+                ..default()
+            })),
+            Transform::from_xyz(i as f32 * 1.5 - 1.5, 0.5, 0.0),
+        ));
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.3, 0.35))),
+        Transform::from_xyz(0.0, 0.5, -2.0),
+    ));
+
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(12.0, 12.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.1, 0.1, 0.1))),
+    ));
+
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2_000.0,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            // Bloom and emissive edges need an HDR target.
+            hdr: true,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 3.0, 6.0).looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
+        Msaa::Off,
+        Bloom::NATURAL,
+        EdgeDetection {
+            edge_color: Color::srgb(0.2, 0.9, 1.0),
+            // Pushes the edges above the LDR range, so bloom picks them up.
+            edge_emissive_strength: if edge_emissive.0 { 6.0 } else { 1.0 },
+            ..default()
+        },
+        PanOrbitCamera::default(),
+    ));
+}
@@ -0,0 +1,184 @@
+//! A tree with alpha-masked leaves and a glass window, see `EdgeDetection::foliage_suppression`
+//! and `EdgeDetection::transparent_outlines`.
+//!
+//! Press F to toggle the foliage suppression and T to toggle the outlines of the window.
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_edge_detection::{EdgeDetection, EdgeDetectionPlugin};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EdgeDetectionPlugin::default())
+        .add_plugins(PanOrbitCameraPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, toggle_settings)
+        .run();
+}
+
+/// A cheap deterministic hash in [0, 1), so the example needs no random number generator.
+fn hash(n: u32) -> f32 {
+    let n = n.wrapping_mul(0x27d4_eb2d) ^ (n >> 15);
+    let n = n.wrapping_mul(0x1656_67b1) ^ (n >> 13);
+    (n % 10_000) as f32 / 10_000.0
+}
+
+/// A leaf cluster texture, opaque where a few scattered discs cover it.
+fn leaves_image() -> Image {
+    const SIZE: u32 = 64;
+    let discs: Vec<(Vec2, f32)> = (0..12)
+        .map(|i| {
+            let center = Vec2::new(hash(i * 3), hash(i * 3 + 1)) * SIZE as f32;
+            (center, 4.0 + 6.0 * hash(i * 3 + 2))
+        })
+        .collect();
+
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let position = Vec2::new(x as f32, y as f32);
+            let covered = discs
+                .iter()
+                .any(|(center, radius)| position.distance(*center) < *radius);
+            let shade = (160.0 + 60.0 * hash(x * SIZE + y)) as u8;
+            data.extend_from_slice(&[40, shade, 50, if covered { 255 } else { 0 }]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(12.0, 12.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.4, 0.5, 0.3))),
+    ));
+
+    // The trunk.
+    commands.spawn((
+        Mesh3d(meshes.add(Cylinder::new(0.15, 2.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.4, 0.25, 0.1))),
+        Transform::from_xyz(-1.5, 1.0, 0.0),
+    ));
+
+    // The crown, made of alpha-masked quads facing in all directions.
+    let leaves = materials.add(StandardMaterial {
+        base_color_texture: Some(images.add(leaves_image())),
+        alpha_mode: AlphaMode::Mask(0.5),
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    let quad = meshes.add(Rectangle::new(0.8, 0.8));
+    for i in 0..120 {
+        let direction = Vec3::new(
+            hash(i * 5) - 0.5,
+            hash(i * 5 + 1) - 0.5,
+            hash(i * 5 + 2) - 0.5,
+        );
+        let position = Vec3::new(-1.5, 2.4, 0.0) + direction.normalize_or_zero() * 0.9;
+        let rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            hash(i * 5 + 3) * std::f32::consts::TAU,
+            hash(i * 5 + 4) * std::f32::consts::TAU,
+            0.0,
+        );
+        commands.spawn((
+            Mesh3d(quad.clone()),
+            MeshMaterial3d(leaves.clone()),
+            Transform::from_translation(position).with_rotation(rotation),
+        ));
+    }
+
+    // The window, a frame with a pane of alpha-blended glass.
+    let frame = materials.add(Color::srgb(0.9, 0.9, 0.85));
+    for (size, position) in [
+        (Vec3::new(2.2, 0.1, 0.1), Vec3::new(1.5, 0.55, 0.0)),
+        (Vec3::new(2.2, 0.1, 0.1), Vec3::new(1.5, 2.45, 0.0)),
+        (Vec3::new(0.1, 2.0, 0.1), Vec3::new(0.45, 1.5, 0.0)),
+        (Vec3::new(0.1, 2.0, 0.1), Vec3::new(2.55, 1.5, 0.0)),
+    ] {
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(frame.clone()),
+            Transform::from_translation(position),
+        ));
+    }
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(2.0, 1.8, 0.02))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgba(0.6, 0.8, 1.0, 0.2),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        })),
+        Transform::from_xyz(1.5, 1.5, 0.0),
+    ));
+
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 4_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 2.5, 7.0).looking_at(Vec3::new(0.0, 1.5, 0.0), Vec3::Y),
+        Msaa::Off,
+        EdgeDetection {
+            foliage_suppression: 4.0,
+            transparent_outlines: true,
+            ..default()
+        },
+        PanOrbitCamera::default(),
+    ));
+}
+
+fn toggle_settings(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut edge_detection: Single<&mut EdgeDetection>,
+) {
+    if keys.just_pressed(KeyCode::KeyF) {
+        edge_detection.foliage_suppression = if edge_detection.foliage_suppression > 0.0 {
+            0.0
+        } else {
+            4.0
+        };
+        info!(
+            "foliage_suppression: {}",
+            edge_detection.foliage_suppression
+        );
+    }
+
+    if keys.just_pressed(KeyCode::KeyT) {
+        edge_detection.transparent_outlines = !edge_detection.transparent_outlines;
+        info!(
+            "transparent_outlines: {}",
+            edge_detection.transparent_outlines
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Outlines the shape under the cursor and the selected one, see
+//! `bevy_edge_detection::picking::EdgeDetectionPickingPlugin`.
+//!
+//! Hover a shape to outline it, click it to select it, and press M to toggle multiple selection.
+
+use bevy::prelude::*;
+use bevy_edge_detection::{
+    picking::{EdgeDetectionPickable, EdgeDetectionPickingPlugin, EdgeDetectionPickingSettings},
+    EdgeDetection, EdgeDetectionPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(MeshPickingPlugin)
+        .add_plugins(EdgeDetectionPlugin::default())
+        .add_plugins(EdgeDetectionPickingPlugin)
+        .insert_resource(EdgeDetectionPickingSettings {
+            hover_color: Color::srgb(0.3, 0.8, 1.0),
+            selection_color: Color::srgb(1.0, 0.6, 0.0),
+            ..default()
+        })
+        .add_systems(Startup, setup)
+        .add_systems(Update, toggle_multi_select)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = materials.add(Color::srgb(0.8, 0.7, 0.6));
+    for (i, shape) in [
+        meshes.add(Cuboid::default()),
+        meshes.add(Sphere::default().mesh().uv(32, 18)),
+        meshes.add(Torus::default()),
+        meshes.add(Cylinder::default()),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        commands.spawn((
+            Mesh3d(shape),
+            MeshMaterial3d(material.clone()),
+            Transform::from_xyz(i as f32 * 2.0 - 3.0, 0.5, 0.0),
+            EdgeDetectionPickable,
+        ));
+    }
+
+    // The ground can be hovered too, but isn't outlined.
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(12.0, 12.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
+    ));
+
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 4_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 4.0, 8.0).looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
+        Msaa::Off,
+        EdgeDetection {
+            targets_only: true,
+            mesh_params: true,
+            depth_thickness: 2.0,
+            normal_thickness: 2.0,
+            uv_distortion_strength: Vec2::ZERO,
+            ..default()
+        },
+    ));
+}
+
+fn toggle_multi_select(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<EdgeDetectionPickingSettings>,
+) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        settings.multi_select = !settings.multi_select;
+        info!("multi_select: {}", settings.multi_select);
+    }
+}
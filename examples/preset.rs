@@ -0,0 +1,60 @@
+//! Loads the edge detection settings of a camera from a `.ron` preset at startup.
+//!
+//! Press `S` to print the current settings as a preset, which can be saved to a file and loaded
+//! back the same way.
+
+use bevy::prelude::*;
+use bevy_edge_detection::{EdgeDetection, EdgeDetectionPlugin};
+
+const PRESET_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/presets/ink.ron");
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EdgeDetectionPlugin::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, print_preset)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let preset = std::fs::read_to_string(PRESET_PATH).expect("failed to read the preset");
+    let edge_detection: EdgeDetection = ron::from_str(&preset).expect("failed to parse the preset");
+
+    commands.spawn((
+        Mesh3d(meshes.add(Torus::default())),
+        MeshMaterial3d(materials.add(Color::srgb(0.9, 0.85, 0.7))),
+        Transform::from_xyz(0.0, 1.0, 0.0).with_rotation(Quat::from_rotation_x(0.8)),
+    ));
+
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(20.0, 20.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.8, 0.8, 0.8))),
+    ));
+
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 4.0, 6.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+        Msaa::Off,
+        edge_detection,
+    ));
+}
+
+fn print_preset(keyboard: Res<ButtonInput<KeyCode>>, edge_detection: Single<&EdgeDetection>) {
+    if !keyboard.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+
+    let preset = ron::ser::to_string_pretty(*edge_detection, default())
+        .expect("failed to serialize the preset");
+    info!("\n{preset}");
+}
@@ -0,0 +1,124 @@
+//! Outlines only the selected shape, see `EdgeDetection::targets_only`.
+//!
+//! Press space to select the next shape, and X to toggle the outline of its hidden part behind the
+//! wall, see `EdgeDetection::occluded_edge_color`.
+
+use bevy::prelude::*;
+use bevy_edge_detection::{EdgeDetection, EdgeDetectionPlugin, EdgeDetectionTarget};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EdgeDetectionPlugin::default())
+        .add_plugins(PanOrbitCameraPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (select_next, toggle_xray))
+        .run();
+}
+
+const OCCLUDED_EDGE_COLOR: Color = Color::srgb(0.2, 0.6, 1.0);
+
+/// The shapes which can be selected, in order.
+#[derive(Component)]
+struct Selectable(usize);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let shapes = [
+        meshes.add(Cuboid::default()),
+        meshes.add(Sphere::default().mesh().uv(32, 18)),
+        meshes.add(Torus::default()),
+        meshes.add(Cylinder::default()),
+    ];
+    let material = materials.add(Color::srgb(0.8, 0.7, 0.6));
+
+    for (i, shape) in shapes.into_iter().enumerate() {
+        let mut entity = commands.spawn((
+            Mesh3d(shape),
+            MeshMaterial3d(material.clone()),
+            Transform::from_xyz(i as f32 * 2.0 - 3.0, 0.5, 0.0),
+            Selectable(i),
+        ));
+
+        if i == 0 {
+            entity.insert(EdgeDetectionTarget);
+        }
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(12.0, 12.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
+    ));
+
+    // A low wall hiding the lower half of the shapes.
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(8.0, 0.6, 0.2))),
+        MeshMaterial3d(materials.add(Color::srgb(0.6, 0.6, 0.65))),
+        Transform::from_xyz(0.0, 0.3, 1.5),
+    ));
+
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 4_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 4.0, 8.0).looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
+        Msaa::Off,
+        EdgeDetection {
+            targets_only: true,
+            edge_color: Color::srgb(1.0, 0.6, 0.0),
+            occluded_edge_color: Some(OCCLUDED_EDGE_COLOR),
+            ..default()
+        },
+        PanOrbitCamera::default(),
+    ));
+}
+
+fn select_next(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    shapes: Query<(Entity, &Selectable, Has<EdgeDetectionTarget>)>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    let count = shapes.iter().len();
+    let Some(selected) = shapes
+        .iter()
+        .find_map(|(_, selectable, target)| target.then_some(selectable.0))
+    else {
+        return;
+    };
+
+    for (entity, selectable, target) in &shapes {
+        if target {
+            commands.entity(entity).remove::<EdgeDetectionTarget>();
+        } else if selectable.0 == (selected + 1) % count {
+            commands.entity(entity).insert(EdgeDetectionTarget);
+        }
+    }
+}
+
+fn toggle_xray(keys: Res<ButtonInput<KeyCode>>, mut edge_detection: Single<&mut EdgeDetection>) {
+    if keys.just_pressed(KeyCode::KeyX) {
+        edge_detection.occluded_edge_color = match edge_detection.occluded_edge_color {
+            Some(_) => None,
+            None => Some(OCCLUDED_EDGE_COLOR),
+        };
+        info!(
+            "occluded_edge_color: {:?}",
+            edge_detection.occluded_edge_color
+        );
+    }
+}
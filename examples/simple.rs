@@ -1,10 +1,11 @@
 //! This example comes from [3d_shapes](https://github.com/bevyengine/bevy/blob/main/examples/3d/3d_shapes.rs)
 
-use bevy::{
-    core_pipeline::{core_3d::graph::Node3d, smaa::Smaa},
-    prelude::*,
+use std::f32::consts::PI;
+
+use bevy::{core_pipeline::smaa::Smaa, prelude::*};
+use bevy_edge_detection::{
+    EdgeDetection, EdgeDetectionPlugin, EdgePlacement, SamplerFilter, MAX_HALO_RADIUS,
 };
-use bevy_edge_detection::{EdgeDetection, EdgeDetectionPlugin};
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 
@@ -12,9 +13,8 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
         .add_plugins(EdgeDetectionPlugin {
-            // If you wish to apply Smaa anti-aliasing after edge detection,
-            // please ensure that the rendering order of [`EdgeDetectionNode`] is set before [`SmaaNode`].
-            before: Node3d::Smaa,
+            // Edges are drawn before Smaa, so they are anti-aliased with the scene.
+            placement: EdgePlacement::AfterTonemappingBeforeAA,
         })
         .add_plugins(EguiPlugin)
         .add_plugins(PanOrbitCameraPlugin)
@@ -115,6 +115,47 @@ fn edge_detection_ui(mut ctx: EguiContexts, mut edge_detection: Single<&mut Edge
                     .text("color_thickness"),
             );
 
+            ui.add(egui::Checkbox::new(
+                &mut edge_detection.thin_edges,
+                "thin_edges",
+            ));
+
+            // Compare with a UV distortion: nearest keeps the distorted silhouettes crisp.
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut edge_detection.sampler_filter,
+                    SamplerFilter::Linear,
+                    "Linear",
+                );
+                ui.radio_value(
+                    &mut edge_detection.sampler_filter,
+                    SamplerFilter::Nearest,
+                    "Nearest",
+                );
+                ui.label("sampler_filter");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Slider::new(&mut edge_detection.radial_falloff, 0.0..=4.0)
+                        .text("radial_falloff"),
+                );
+                ui.add(egui::Checkbox::new(
+                    &mut edge_detection.radial_invert,
+                    "radial_invert",
+                ));
+            });
+
+            // Move the camera into the box to see edges fade out up close.
+            ui.add(
+                egui::Slider::new(&mut edge_detection.near_fade_start, 0.0..=8.0)
+                    .text("near_fade_start"),
+            );
+            ui.add(
+                egui::Slider::new(&mut edge_detection.near_fade_end, 0.0..=8.0)
+                    .text("near_fade_end"),
+            );
+
             ui.add(
                 egui::Slider::new(&mut edge_detection.steep_angle_threshold, 0.0..=1.0)
                     .text("steep_angle_threshold"),
@@ -150,12 +191,80 @@ fn edge_detection_ui(mut ctx: EguiContexts, mut edge_detection: Single<&mut Edge
                 ui.label("uv_distortion_strength");
             });
 
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut edge_detection.uv_distortion_frequency2.x)
+                        .range(0.0..=64.0),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut edge_detection.uv_distortion_frequency2.y)
+                        .range(0.0..=64.0),
+                );
+                ui.label("uv_distortion_frequency2");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut edge_detection.uv_distortion_strength2.x)
+                        .range(0.0..=1.0)
+                        .fixed_decimals(4),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut edge_detection.uv_distortion_strength2.y)
+                        .range(0.0..=1.0)
+                        .fixed_decimals(4),
+                );
+                ui.label("uv_distortion_strength2");
+            });
+
+            ui.add(
+                egui::Slider::new(&mut edge_detection.non_edge_desaturation, 0.0..=1.0)
+                    .text("non_edge_desaturation"),
+            );
+            ui.add(
+                egui::Slider::new(&mut edge_detection.strength_steps, 0..=8).text("strength_steps"),
+            );
+
             let mut color = edge_detection.edge_color.to_srgba().to_f32_array_no_alpha();
             ui.horizontal(|ui| {
                 egui::color_picker::color_edit_button_rgb(ui, &mut color);
                 ui.label("edge_color");
             });
             edge_detection.edge_color = Color::srgb_from_array(color);
+
+            ui.add(
+                egui::Slider::new(&mut edge_detection.halo_radius, 0..=MAX_HALO_RADIUS)
+                    .text("halo_radius"),
+            );
+            ui.add(
+                egui::Slider::new(&mut edge_detection.halo_strength, 0.0..=4.0)
+                    .text("halo_strength"),
+            );
+
+            ui.horizontal(|ui| {
+                let mut enabled = edge_detection.debug_split.is_some();
+                let mut split = edge_detection.debug_split.unwrap_or(0.5);
+                ui.add(egui::Checkbox::new(&mut enabled, "debug_split"));
+                ui.add(egui::Slider::new(&mut split, 0.0..=1.0));
+
+                edge_detection.debug_split = enabled.then_some(split);
+            });
+
+            ui.horizontal(|ui| {
+                let (start, end, direction) = edge_detection.edge_color_gradient.unwrap_or((
+                    Color::srgb(0.5, 0.0, 0.8),
+                    Color::srgb(0.0, 0.8, 0.8),
+                    Vec2::NEG_Y,
+                ));
+
+                let mut enabled = edge_detection.edge_color_gradient.is_some();
+                let mut angle = direction.to_angle();
+                ui.add(egui::Checkbox::new(&mut enabled, "edge_color_gradient"));
+                ui.add(egui::Slider::new(&mut angle, -PI..=PI).text("angle"));
+
+                edge_detection.edge_color_gradient =
+                    enabled.then(|| (start, end, Vec2::from_angle(angle)));
+            });
         });
     });
 }
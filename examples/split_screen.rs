@@ -0,0 +1,112 @@
+//! Two cameras side by side on one window, each with its own edge detection settings.
+//!
+//! Both halves share the render target, so edges must stay within the viewport of their camera:
+//! the left one outlines in red, the right one in blue, and no line runs along the split.
+
+use bevy::{
+    prelude::*,
+    render::camera::{ClearColorConfig, Viewport},
+    window::WindowResized,
+};
+use bevy_edge_detection::{EdgeDetection, EdgeDetectionPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EdgeDetectionPlugin::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, set_camera_viewports)
+        .run();
+}
+
+/// Which half of the window a camera renders to, 0 for the left one.
+#[derive(Component)]
+struct SplitCamera(u32);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = materials.add(Color::srgb(0.8, 0.7, 0.6));
+    for (i, shape) in [
+        meshes.add(Cuboid::default()),
+        meshes.add(Sphere::default().mesh().uv(32, 18)),
+        meshes.add(Torus::default()),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        commands.spawn((
+            Mesh3d(shape),
+            MeshMaterial3d(material.clone()),
+            Transform::from_xyz(i as f32 * 2.0 - 2.0, 0.5, 0.0),
+        ));
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(10.0, 10.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
+    ));
+
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 4_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    let cameras = [
+        (Vec3::new(-4.0, 4.0, 6.0), Color::srgb(0.8, 0.1, 0.1)),
+        (Vec3::new(4.0, 3.0, 6.0), Color::srgb(0.1, 0.2, 0.9)),
+    ];
+    for (i, (position, edge_color)) in cameras.into_iter().enumerate() {
+        commands.spawn((
+            Camera3d::default(),
+            Camera {
+                order: i as isize,
+                // The right camera draws over the left one instead of clearing the window.
+                clear_color: if i == 0 {
+                    ClearColorConfig::Default
+                } else {
+                    ClearColorConfig::None
+                },
+                ..default()
+            },
+            Transform::from_translation(position).looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
+            Msaa::Off,
+            EdgeDetection {
+                edge_color,
+                ..default()
+            },
+            SplitCamera(i as u32),
+        ));
+    }
+}
+
+fn set_camera_viewports(
+    windows: Query<&Window>,
+    mut resize_events: EventReader<WindowResized>,
+    mut cameras: Query<(&mut Camera, &SplitCamera)>,
+) {
+    // Also runs once at startup, when the cameras don't have a viewport yet.
+    let needs_viewports = cameras.iter().any(|(camera, _)| camera.viewport.is_none());
+    if resize_events.read().last().is_none() && !needs_viewports {
+        return;
+    }
+
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let size = window.physical_size() / UVec2::new(2, 1);
+
+    for (mut camera, split) in &mut cameras {
+        camera.viewport = Some(Viewport {
+            physical_position: UVec2::new(split.0 * size.x, 0),
+            physical_size: size,
+            ..default()
+        });
+    }
+}
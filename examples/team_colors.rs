@@ -0,0 +1,99 @@
+//! Outlines in a different color per mesh, see `EdgeDetectionColor`.
+//!
+//! Enemies are outlined in red, allies in green and loot in yellow, the rest of the scene keeps
+//! the edge color of the camera. Press C to toggle the colors.
+
+use bevy::prelude::*;
+use bevy_edge_detection::{EdgeDetection, EdgeDetectionColor, EdgeDetectionPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EdgeDetectionPlugin::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, toggle_colors)
+        .run();
+}
+
+/// The outline color of a mesh while the colors are on.
+#[derive(Component)]
+struct Team(Color);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = materials.add(Color::srgb(0.8, 0.7, 0.6));
+    let capsule = meshes.add(Capsule3d::new(0.3, 1.0));
+    let chest = meshes.add(Cuboid::new(0.6, 0.4, 0.4));
+
+    let enemy = Color::srgb(0.9, 0.1, 0.1);
+    let ally = Color::srgb(0.1, 0.8, 0.2);
+    let loot = Color::srgb(1.0, 0.85, 0.1);
+    for (mesh, position, color) in [
+        (capsule.clone(), Vec3::new(-2.0, 0.8, -1.0), enemy),
+        (capsule.clone(), Vec3::new(-0.8, 0.8, -1.5), enemy),
+        (capsule.clone(), Vec3::new(1.0, 0.8, 0.5), ally),
+        (capsule, Vec3::new(2.2, 0.8, 0.0), ally),
+        (chest, Vec3::new(0.0, 0.2, 1.5), loot),
+    ] {
+        commands.spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(position),
+            Team(color),
+            EdgeDetectionColor(color),
+        ));
+    }
+
+    // Uncolored geometry, outlined in the edge color of the camera.
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+        MeshMaterial3d(material),
+        Transform::from_xyz(2.5, 0.5, -2.0),
+    ));
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(10.0, 10.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
+    ));
+
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 4_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 4.0, 7.0).looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::Y),
+        Msaa::Off,
+        EdgeDetection {
+            depth_thickness: 2.0,
+            normal_thickness: 2.0,
+            mesh_params: true,
+            ..default()
+        },
+    ));
+}
+
+fn toggle_colors(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    meshes: Query<(Entity, &Team, Has<EdgeDetectionColor>)>,
+) {
+    if !keys.just_pressed(KeyCode::KeyC) {
+        return;
+    }
+
+    for (entity, team, colored) in &meshes {
+        if colored {
+            commands.entity(entity).remove::<EdgeDetectionColor>();
+        } else {
+            commands.entity(entity).insert(EdgeDetectionColor(team.0));
+        }
+    }
+}
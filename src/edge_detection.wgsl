@@ -29,6 +29,70 @@
 @group(0) @binding(6) var<uniform> view: View;
 @group(0) @binding(7) var<uniform> ed_uniform: EdgeDetectionUniform;
 
+#ifdef SECONDARY_DEPTH
+@group(0) @binding(8) var secondary_depth_texture: texture_2d<f32>;
+#endif
+
+#ifdef OVERLAY
+@group(0) @binding(9) var overlay_texture: texture_2d<f32>;
+#endif
+
+#ifdef MOTION_VECTORS
+#ifdef MULTISAMPLED
+@group(0) @binding(10) var motion_vector_prepass_texture: texture_multisampled_2d<f32>;
+#else
+@group(0) @binding(10) var motion_vector_prepass_texture: texture_2d<f32>;
+#endif
+#endif
+
+#ifdef TEMPORAL
+@group(0) @binding(11) var history_texture: texture_2d<f32>;
+#endif
+
+@group(0) @binding(12) var edge_mask_texture: texture_2d<f32>;
+
+#ifdef THRESHOLD_MAP
+@group(0) @binding(13) var threshold_map_texture: texture_2d<f32>;
+#endif
+
+#ifdef STRENGTH_MASK
+@group(0) @binding(14) var strength_mask_texture: texture_2d<f32>;
+#endif
+
+#ifdef ENABLE_OBJECT_ID
+@group(0) @binding(15) var object_id_texture: texture_2d<u32>;
+#endif
+
+#ifdef ENABLE_MATERIAL_ID
+@group(0) @binding(16) var deferred_prepass_texture: texture_2d<u32>;
+@group(0) @binding(17) var deferred_lighting_pass_id_texture: texture_2d<u32>;
+#endif
+
+#ifdef COMPUTE
+@group(0) @binding(18) var output_texture: texture_storage_2d<rgba16float, write>;
+#endif
+
+// The textures of the marker pass and the curvature texture are a second bind group, which only
+// the pipelines reading them have.
+
+#ifdef MARKED_ENTITIES
+@group(1) @binding(0) var marker_depth_texture: texture_depth_2d;
+@group(1) @binding(1) var marker_texture: texture_2d<f32>;
+#endif
+
+#ifdef XRAY_OUTLINES
+@group(1) @binding(2) var xray_depth_texture: texture_depth_2d;
+#endif
+
+#ifdef CURVATURE_EDGES
+@group(1) @binding(3) var curvature_texture: texture_2d<f32>;
+#endif
+
+#ifdef MESH_PARAMS
+@group(1) @binding(4) var marker_color_texture: texture_2d<f32>;
+@group(1) @binding(5) var marker_thresholds_texture: texture_2d<f32>;
+#endif
+
 struct EdgeDetectionUniform {
     depth_threshold: f32,
     normal_threshold: f32,
@@ -45,6 +109,146 @@ struct EdgeDetectionUniform {
     uv_distortion: vec4f,
 
     edge_color: vec4f,
+    edge_emissive_strength: f32,
+    camera_exposure: f32,
+
+    background_color: vec4f,
+
+    non_edge_desaturation: f32,
+    posterize_levels: u32,
+
+    overlay_opacity: f32,
+    overlay_scale: f32,
+
+    scanline_strength: f32,
+    scanline_frequency: f32,
+
+    temporal_blend: f32,
+
+    hatch_angle: f32,
+    hatch_spacing: f32,
+    hatch_line_width: f32,
+    hatch_mid_threshold: f32,
+    hatch_dark_threshold: f32,
+
+    edge_light_direction: vec3f,
+    edge_color_lit: vec4f,
+    edge_color_shadow: vec4f,
+
+    dilate_radius: u32,
+
+    strength_steps: u32,
+    edge_response_gamma: f32,
+
+    edge_shadow_offset: vec2f,
+    edge_shadow_color: vec4f,
+
+    pixelate_grid: vec2f,
+
+    normal_edge_min_spacing: u32,
+
+    rim_modulation: f32,
+
+    nearer_object_darkening: f32,
+
+    thickness_falloff: f32,
+
+    contour_light_dir: vec2f,
+    contour_strength: f32,
+
+    contrast_guard: f32,
+    contrast_guard_color: vec4f,
+
+    background_tint: vec4f,
+    background_tint_strength: f32,
+
+    noise_seed: u32,
+
+    halftone_spacing: f32,
+    halftone_angle: f32,
+    halftone_exponent: f32,
+
+    stipple_spacing: f32,
+    stipple_size: f32,
+
+    duotone_light_color: vec4f,
+    duotone_shadow_color: vec4f,
+    duotone_threshold: f32,
+    duotone_softness: f32,
+
+    min_neighborhood_variance: f32,
+
+    pooling_radius: f32,
+    pooling_strength: f32,
+    pooling_color: vec4f,
+
+    depth_band_starts: array<vec4f, 2>,
+    depth_band_colors: array<vec4f, 8>,
+    depth_band_count: u32,
+
+    edge_gradient_start: vec4f,
+    edge_gradient_end: vec4f,
+    edge_gradient_direction: vec2f,
+
+    distance_fade_start: f32,
+    distance_fade_end: f32,
+    near_fade_start: f32,
+    near_fade_end: f32,
+    effect_depth_min: f32,
+    effect_depth_max: f32,
+    volume_center: vec3f,
+    volume_extents: vec3f,
+    volume_falloff: f32,
+    height_min: f32,
+    height_max: f32,
+    height_falloff: f32,
+
+    fog_mode: u32,
+    fog_color: vec4f,
+    fog_params: vec4f,
+    fog_inscattering: vec4f,
+
+    exclusion_rect: vec4f,
+    screen_rect: vec4f,
+    debug_split: f32,
+    radial_falloff: f32,
+    distance_field_radius: u32,
+    motion_threshold: f32,
+    background_depth_epsilon: f32,
+    viewmodel_depth_cutoff: f32,
+    foliage_suppression: f32,
+    uv_distortion2: vec4f,
+    occluded_edge_color: vec4f,
+    curvature_threshold: f32,
+    halo_radius: u32,
+    halo_strength: f32,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4f,
+#ifdef TEMPORAL
+    // The stabilized edge mask, read back as the history of the next frame.
+    @location(1) edge_mask: f32,
+#endif
+#ifdef GRADIENT_OUTPUT
+    // Always at location 2, so the attachment doesn't move with temporal stabilization.
+    @location(2) gradient: vec2f,
+#endif
+}
+
+struct MaskOutput {
+    @location(0) edge_mask: f32,
+#ifdef GRADIENT_OUTPUT
+    @location(2) gradient: vec2f,
+#endif
+}
+
+struct DilateOutput {
+    @location(0) color: vec4f,
+#ifdef DISTANCE_FIELD
+    // After the unused locations of the gradient and history, like in `FragmentOutput`.
+    @location(3) distance: f32,
+#endif
 }
 
 // -----------------------
@@ -85,49 +289,274 @@ fn calculate_view(world_position: vec3f) -> vec3f {
 #endif
 }
 
+// -----------------------
+// Edge Response ---------
+// -----------------------
+
+/// The ratio of a gradient to its threshold. Values above 1.0 are edges, while values below 1.0
+/// fall off smoothly around them.
+fn edge_response(grad: f32, threshold: f32) -> f32 {
+    return grad / max(threshold, 1e-6);
+}
+
+// The weights of the outer and the center rows of the 3x3 derivative kernels, see `EdgeOperator`.
+// Scharr is scaled down to the total weight of Sobel, so a step gives the same response and the
+// thresholds carry over between operators. The Roberts cross is scaled the same way where the
+// detectors compute it.
+const SOBEL_WEIGHTS = vec2f(1.0, 2.0);
+const SCHARR_WEIGHTS = vec2f(0.75, 2.5);
+
 // -----------------------
 // Depth Detection -------
 // -----------------------
 
+/// `uv` of the render target relative to the camera viewport, from `(0, 0)` at its top-left to
+/// `(1, 1)` at its bottom-right corner. The same as `uv` without a viewport.
+fn view_uv(uv: vec2f) -> vec2f {
+    return (uv * texture_size - view.viewport.xy) / view.viewport.zw;
+}
+
+/// The NDC position of `uv` of the render target, which the view projects into its viewport.
+fn view_uv_to_ndc(uv: vec2f) -> vec2f {
+    return uv_to_ndc(view_uv(uv));
+}
+
+#ifdef SCREEN_RECT
+/// Whether `uv` lies within the screen rect the effect is limited to.
+fn in_screen_rect(uv: vec2f) -> bool {
+    let rect_uv = view_uv(uv);
+    return all(rect_uv >= ed_uniform.screen_rect.xy) && all(rect_uv <= ed_uniform.screen_rect.zw);
+}
+#endif
+
+/// Clamps `uv` to the part of the view edges are detected in, so kernels reaching past the screen
+/// rect repeat its border instead of detecting the content outside of it. The rect is relative to
+/// the camera viewport, so neighboring viewports of the render target aren't read either.
+fn clamp_to_screen_rect(uv: vec2f) -> vec2f {
+#ifdef SCREEN_RECT
+    let rect = ed_uniform.screen_rect;
+    // Clamped to the centers of the border texels, whose far sides touch the next viewport.
+    let half_texel = 0.5 / texture_size;
+    let rect_min = (view.viewport.xy + rect.xy * view.viewport.zw) / texture_size + half_texel;
+    let rect_max = (view.viewport.xy + rect.zw * view.viewport.zw) / texture_size - half_texel;
+    return clamp(uv, rect_min, max(rect_min, rect_max));
+#else
+    return uv;
+#endif
+}
+
+/// The texel of the view containing `uv`, clamped to the edge.
+/// Prepass textures are loaded at whole texels, so the taps of the kernels stay aligned with the
+/// texel centers instead of being interpolated across the edges they detect.
+fn pixel_coord(uv: vec2f) -> vec2i {
+    let coord = vec2i(floor(clamp_to_screen_rect(uv) * texture_size));
+    return clamp(coord, vec2i(0), vec2i(texture_size) - 1);
+}
+
 fn prepass_depth(uv: vec2f) -> f32 {
 #ifdef MULTISAMPLED
-    let pixel_coord = vec2i(uv * texture_size);
-    let depth = textureLoad(depth_prepass_texture, pixel_coord, sample_index_i);
+    let depth = textureLoad(depth_prepass_texture, pixel_coord(uv), sample_index_i);
 #else
-    let depth = textureSample(depth_prepass_texture, texture_sampler, uv);
+    let depth = textureLoad(depth_prepass_texture, pixel_coord(uv), 0);
 #endif
     return depth;
 }
 
+/// Whether the prepass depth `depth` belongs to the background, i.e. the clear color or a skybox.
+/// Reversed-Z puts it at a depth of 0, also with an infinite far plane, and
+/// `background_depth_epsilon` absorbs the geometry at the far plane.
+fn is_background(depth: f32) -> bool {
+    return depth <= ed_uniform.background_depth_epsilon;
+}
+
 fn prepass_view_z(uv: vec2f) -> f32 {
     let depth = prepass_depth(uv);
     return depth_ndc_to_view_z(depth);
 }
 
-fn view_z_gradient_x(uv: vec2f, y: f32, thickness: f32) -> f32 {
+#ifdef SECONDARY_DEPTH
+/// The secondary depth may have a different resolution than the view, so it's loaded by its own size.
+fn secondary_view_z(uv: vec2f) -> f32 {
+    let size = vec2i(textureDimensions(secondary_depth_texture));
+    let pixel_coord = clamp(vec2i(clamp_to_screen_rect(uv) * vec2f(size)), vec2i(0), size - 1);
+    let depth = textureLoad(secondary_depth_texture, pixel_coord, 0).r;
+    return depth_ndc_to_view_z(depth);
+}
+#endif
+
+fn sample_view_z(uv: vec2f, secondary: bool) -> f32 {
+#ifdef SECONDARY_DEPTH
+    if secondary {
+        return secondary_view_z(uv);
+    }
+#endif
+    return prepass_view_z(uv);
+}
+
+fn view_z_gradient_x(uv: vec2f, y: f32, thickness: f32, secondary: bool) -> f32 {
     let l_coord = uv + texel_size * vec2f(-thickness, y);    // left  coordinate
     let r_coord = uv + texel_size * vec2f( thickness, y);    // right coordinate
 
-    return prepass_view_z(r_coord) - prepass_view_z(l_coord); 
+    return sample_view_z(r_coord, secondary) - sample_view_z(l_coord, secondary);
 }
 
-fn view_z_gradient_y(uv: vec2f, x: f32, thickness: f32) -> f32 {
+fn view_z_gradient_y(uv: vec2f, x: f32, thickness: f32, secondary: bool) -> f32 {
     let d_coord = uv + texel_size * vec2f(x, -thickness);    // down coordinate
     let t_coord = uv + texel_size * vec2f(x,  thickness);    // top  coordinate
 
-    return prepass_view_z(t_coord) - prepass_view_z(d_coord);
+    return sample_view_z(t_coord, secondary) - sample_view_z(d_coord, secondary);
+}
+
+#ifdef EDGE_COLOR_NEARER_OBJECT
+/// The scene color of the nearest surface among the samples depth edges are detected over,
+/// i.e. the front side of a depth discontinuity at `uv`.
+fn nearer_object_color(uv: vec2f) -> vec3f {
+    let thickness = ed_uniform.depth_thickness;
+
+    var nearest_uv = uv;
+    var nearest_z = abs(prepass_view_z(uv));
+
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let sample_uv = uv + texel_size * vec2f(f32(x), f32(y)) * thickness;
+            let z = abs(prepass_view_z(sample_uv));
+            if z < nearest_z {
+                nearest_z = z;
+                nearest_uv = sample_uv;
+            }
+        }
+    }
+
+    let color = textureSampleLevel(screen_texture, texture_sampler, nearest_uv, 0.0).rgb;
+    return color * (1.0 - ed_uniform.nearer_object_darkening);
+}
+#endif
+
+/// The response of one detector, see `edge_response`, and the Sobel derivatives along x and y
+/// it was computed from. Like `uv`, y points down.
+struct DetectorOutput {
+    response: f32,
+    gradient: vec2f,
+}
+
+/// Whichever of `a` and `b` has the stronger response.
+fn stronger(a: DetectorOutput, b: DetectorOutput) -> DetectorOutput {
+    if b.response > a.response {
+        return b;
+    }
+    return a;
+}
+
+/// The outputs of the detectors combined so far, see `EdgeCombineMode` and `EdgeLogic`.
+struct CombinedDetectors {
+    strongest: DetectorOutput,
+    response_sum: f32,
+    count: f32,
+    // The number of detectors whose response exceeds 1.0.
+    fired_count: f32,
+    // Whether the depth or the normal detector fired.
+    geometry_fired: bool,
+}
+
+fn no_detectors() -> CombinedDetectors {
+    return CombinedDetectors(DetectorOutput(0.0, vec2f(0.0)), 0.0, 0.0, 0.0, false);
+}
+
+/// Adds the output of a detector, `geometry` for the depth and normal detectors.
+fn combine(combined: CombinedDetectors, detector: DetectorOutput, geometry: bool) -> CombinedDetectors {
+    let fired = detector.response > 1.0;
+    return CombinedDetectors(
+        stronger(combined.strongest, detector),
+        combined.response_sum + detector.response,
+        combined.count + 1.0,
+        combined.fired_count + f32(fired),
+        combined.geometry_fired || (geometry && fired),
+    );
+}
+
+/// Whether the detectors that fired satisfy `EdgeLogic`.
+fn passes_edge_logic(combined: CombinedDetectors) -> bool {
+#ifdef EDGE_LOGIC_ALL
+    return combined.fired_count == combined.count;
+#else ifdef EDGE_LOGIC_DEPTH_OR_NORMAL
+    return combined.geometry_fired;
+#else
+    return true;
+#endif
+}
+
+fn combined_response(combined: CombinedDetectors) -> f32 {
+    if !passes_edge_logic(combined) {
+        return 0.0;
+    }
+
+#ifdef EDGE_COMBINE_ADD
+    return combined.response_sum;
+#else ifdef EDGE_COMBINE_AVERAGE
+    return combined.response_sum / max(combined.count, 1.0);
+#else
+    return combined.strongest.response;
+#endif
+}
+
+/// The response of edges forced to full strength whatever the gradients, e.g. object ID changes
+/// and marker silhouettes. A response of N draws N strength steps, clamped to `strength_steps`, so
+/// one above the last step exceeds the threshold (1.0) and every step, even without steps.
+fn forced_edge_response() -> f32 {
+    return f32(max(ed_uniform.strength_steps, 1u)) + 1.0;
+}
+
+/// The derivatives of the component of a vector-valued Sobel filter changing the most.
+fn dominant_gradient(deri_x: vec3f, deri_y: vec3f) -> vec2f {
+    let strength = abs(deri_x) + abs(deri_y);
+
+    var component = 0;
+    if strength.y > strength[component] {
+        component = 1;
+    }
+    if strength.z > strength[component] {
+        component = 2;
+    }
+
+    return vec2f(deri_x[component], deri_y[component]);
 }
 
-fn detect_edge_depth(uv: vec2f, thickness: f32, fresnel: f32) -> f32 {
-    let deri_x = 
-        view_z_gradient_x(uv, thickness, thickness) +
-        2.0 * view_z_gradient_x(uv, 0.0, thickness) +
-        view_z_gradient_x(uv, -thickness, thickness);
+/// The derivatives of the view depth along x and y, by `depth_operator`.
+fn view_z_derivatives(uv: vec2f, thickness: f32, secondary: bool) -> vec2f {
+#ifdef DEPTH_OPERATOR_ROBERTS
+    // The differences across both diagonals of the 2x2 taps around `uv`; their sum runs along x
+    // and their difference along y.
+    let offset = 0.5 * thickness * texel_size;
+    let diagonal_down = sample_view_z(uv + offset, secondary) - sample_view_z(uv - offset, secondary);
+    let diagonal_up =
+        sample_view_z(uv + vec2f(offset.x, -offset.y), secondary) -
+        sample_view_z(uv + vec2f(-offset.x, offset.y), secondary);
+
+    return 2.0 * vec2f(diagonal_down + diagonal_up, diagonal_down - diagonal_up);
+#else
+#ifdef DEPTH_OPERATOR_SCHARR
+    let weights = SCHARR_WEIGHTS;
+#else
+    let weights = SOBEL_WEIGHTS;
+#endif
+    let deri_x =
+        weights.x * view_z_gradient_x(uv, thickness, thickness, secondary) +
+        weights.y * view_z_gradient_x(uv, 0.0, thickness, secondary) +
+        weights.x * view_z_gradient_x(uv, -thickness, thickness, secondary);
 
     let deri_y =
-        view_z_gradient_y(uv, thickness, thickness) +
-        2.0 * view_z_gradient_y(uv, 0.0, thickness) +
-        view_z_gradient_y(uv, -thickness, thickness);
+        weights.x * view_z_gradient_y(uv, thickness, thickness, secondary) +
+        weights.y * view_z_gradient_y(uv, 0.0, thickness, secondary) +
+        weights.x * view_z_gradient_y(uv, -thickness, thickness, secondary);
+
+    return vec2f(deri_x, deri_y);
+#endif
+}
+
+fn detect_edge_depth(uv: vec2f, thickness: f32, fresnel: f32, secondary: bool) -> DetectorOutput {
+    let deri = view_z_derivatives(uv, thickness, secondary);
+    let deri_x = deri.x;
+    let deri_y = deri.y;
 
     // why not `let grad = sqrt(deri_x * deri_x + deri_y * deri_y);`?
     //
@@ -135,14 +564,79 @@ fn detect_edge_depth(uv: vec2f, thickness: f32, fresnel: f32) -> f32 {
     // causing overflow in the calculation and resulting in incorrect results.
     let grad = max(abs(deri_x), abs(deri_y));
 
-    let view_z = abs(prepass_view_z(uv));
+#ifdef STEEP_ANGLE_CORRECTION
+    let view_z = abs(sample_view_z(uv, secondary));
 
     let steep_angle_adjustment = 
         smoothstep(ed_uniform.steep_angle_threshold, 1.0, fresnel) * ed_uniform.steep_angle_multiplier * view_z;
 
-    return f32(grad > ed_uniform.depth_threshold * (1.0 + steep_angle_adjustment));
+    let threshold = pixel_thresholds.x * (1.0 + steep_angle_adjustment);
+#else
+    let threshold = pixel_thresholds.x;
+#endif
+
+    return DetectorOutput(edge_response(grad, threshold), vec2f(deri_x, deri_y));
+}
+
+#ifdef DEPTH_BANDS
+/// The color of the depth band the surface at `uv` falls in, `edge_color` in front of every band.
+fn depth_band_color(uv: vec2f) -> vec3f {
+    let distance = abs(prepass_view_z(uv));
+
+    // The bands are sorted, so the last one started is the one containing the surface.
+    var color = ed_uniform.edge_color.rgb;
+    for (var i = 0u; i < ed_uniform.depth_band_count; i++) {
+        if distance >= ed_uniform.depth_band_starts[i / 4u][i % 4u] {
+            color = ed_uniform.depth_band_colors[i].rgb;
+        }
+    }
+    return color;
+}
+#endif
+
+#ifdef EDGE_COLOR_GRADIENT
+/// The edge color ramped across the screen along `edge_gradient_direction`, from its start at
+/// one side of the view to its end at the other.
+fn screen_gradient_color(uv: vec2f) -> vec3f {
+    // Centered, with y up like the direction.
+    let position = (view_uv(uv) - 0.5) * vec2f(1.0, -1.0);
+    let direction = ed_uniform.edge_gradient_direction;
+    // The corners furthest along the direction are at +-(|x| + |y|) / 2.
+    let extent = max(abs(direction.x) + abs(direction.y), 1e-6);
+    let t = saturate(dot(position, direction) / extent + 0.5);
+    return mix(ed_uniform.edge_gradient_start.rgb, ed_uniform.edge_gradient_end.rgb, t);
+}
+#endif
+
+#ifdef CONTOUR
+/// The screen-space direction from the nearer towards the farther side of the surface at `uv`,
+/// taken from the Sobel derivatives of the depth. Like `uv`, y points down.
+fn depth_gradient_direction(uv: vec2f, thickness: f32) -> vec2f {
+    let deri_x =
+        view_z_gradient_x(uv, thickness, thickness, false) +
+        2.0 * view_z_gradient_x(uv, 0.0, thickness, false) +
+        view_z_gradient_x(uv, -thickness, thickness, false);
+
+    let deri_y =
+        view_z_gradient_y(uv, thickness, thickness, false) +
+        2.0 * view_z_gradient_y(uv, 0.0, thickness, false) +
+        view_z_gradient_y(uv, -thickness, thickness, false);
+
+    // View z decreases away from the camera, so the far side is against its gradient.
+    let direction = -vec2f(deri_x, deri_y);
+    return direction / max(length(direction), 1e-6);
 }
 
+/// Lightens edges facing `contour_light_dir` and darkens those facing away, like a drawn contour.
+fn contour_shade(uv: vec2f, edge_rgb: vec3f) -> vec3f {
+    // `contour_light_dir` points up on screen, the opposite of uv.
+    let light_dir = ed_uniform.contour_light_dir * vec2f(1.0, -1.0);
+    let facing = dot(depth_gradient_direction(uv, ed_uniform.depth_thickness), light_dir);
+    let shade = clamp(facing * ed_uniform.contour_strength, -1.0, 1.0);
+    return select(mix(edge_rgb, vec3f(0.0), -shade), mix(edge_rgb, vec3f(1.0), shade), shade > 0.0);
+}
+#endif
+
 // -----------------------
 // Normal Detection ------
 // -----------------------
@@ -154,53 +648,159 @@ fn prepass_normal_unpack(uv: vec2f) -> vec3f {
 
 fn prepass_normal(uv: vec2f) -> vec3f {
 #ifdef MULTISAMPLED
-    let pixel_coord = vec2i(uv * texture_size);
-    let normal = textureLoad(normal_prepass_texture, pixel_coord, sample_index_i);
+    let normal = textureLoad(normal_prepass_texture, pixel_coord(uv), sample_index_i);
 #else
-    let normal = textureSample(normal_prepass_texture, texture_sampler, uv);
+    let normal = textureLoad(normal_prepass_texture, pixel_coord(uv), 0);
 #endif
     return normal.xyz;
 }
 
+/// The normal at `uv` compared by the normal detector, packed into [0, 1] like the prepass.
+/// The prepass holds world-space normals.
+fn detector_normal(uv: vec2f) -> vec3f {
+    let normal = prepass_normal(uv);
+#ifdef NORMAL_SPACE_VIEW
+    let world_normal = normal * 2.0 - vec3(1.0);
+    let view_normal = (view.view_from_world * vec4f(world_normal, 0.0)).xyz;
+    return view_normal * 0.5 + vec3(0.5);
+#else
+    return normal;
+#endif
+}
+
 fn normal_gradient_x(uv: vec2f, y: f32, thickness: f32) -> vec3f {
     let l_coord = uv + texel_size * vec2f(-thickness, y);    // left  coordinate
     let r_coord = uv + texel_size * vec2f( thickness, y);    // right coordinate
 
-    return prepass_normal(r_coord) - prepass_normal(l_coord);
+    return detector_normal(r_coord) - detector_normal(l_coord);
 }
 
 fn normal_gradient_y(uv: vec2f, x: f32, thickness: f32) -> vec3f {
     let d_coord = uv + texel_size * vec2f(x, -thickness);    // down coordinate
     let t_coord = uv + texel_size * vec2f(x,  thickness);    // top  coordinate
 
-    return prepass_normal(t_coord) - prepass_normal(d_coord);
+    return detector_normal(t_coord) - detector_normal(d_coord);
+}
+
+/// The derivatives of the normals by `normal_operator`, per normal component.
+struct NormalDerivatives {
+    x: vec3f,
+    y: vec3f,
 }
 
-fn detect_edge_normal(uv: vec2f, thickness: f32) -> f32 {
-    let deri_x = abs(
-        normal_gradient_x(uv,  thickness, thickness) +
-        2.0 * normal_gradient_x(uv,  0.0, thickness) +
-        normal_gradient_x(uv, -thickness, thickness));
+fn normal_derivatives(uv: vec2f, thickness: f32) -> NormalDerivatives {
+#ifdef NORMAL_OPERATOR_ROBERTS
+    // See `view_z_derivatives`.
+    let offset = 0.5 * thickness * texel_size;
+    let diagonal_down = detector_normal(uv + offset) - detector_normal(uv - offset);
+    let diagonal_up =
+        detector_normal(uv + vec2f(offset.x, -offset.y)) -
+        detector_normal(uv + vec2f(-offset.x, offset.y));
+
+    return NormalDerivatives(
+        2.0 * (diagonal_down + diagonal_up),
+        2.0 * (diagonal_down - diagonal_up),
+    );
+#else
+#ifdef NORMAL_OPERATOR_SCHARR
+    let weights = SCHARR_WEIGHTS;
+#else
+    let weights = SOBEL_WEIGHTS;
+#endif
+    let deri_x =
+        weights.x * normal_gradient_x(uv,  thickness, thickness) +
+        weights.y * normal_gradient_x(uv,  0.0, thickness) +
+        weights.x * normal_gradient_x(uv, -thickness, thickness);
+
+    let deri_y =
+        weights.x * normal_gradient_y(uv, thickness, thickness) +
+        weights.y * normal_gradient_y(uv, 0.0, thickness) +
+        weights.x * normal_gradient_y(uv, -thickness, thickness);
+
+    return NormalDerivatives(deri_x, deri_y);
+#endif
+}
 
-    let deri_y = abs(
-        normal_gradient_y(uv, thickness, thickness) +
-        2.0 * normal_gradient_y(uv, 0.0, thickness) +
-        normal_gradient_y(uv, -thickness, thickness));
+fn normal_gradient_magnitude(deri: NormalDerivatives) -> f32 {
+    let deri_x = abs(deri.x);
+    let deri_y = abs(deri.y);
 
     let x_max = max(deri_x.x, max(deri_x.y, deri_x.z));
     let y_max = max(deri_y.x, max(deri_y.y, deri_y.z));
     
-    let grad = max(x_max, y_max);
+    return max(x_max, y_max);
+}
+
+#ifdef NORMAL_EDGE_SPACING
+/// The screen-space direction across the edge, taken from the normal component changing the most.
+fn normal_gradient_direction(deri: NormalDerivatives) -> vec2f {
+    let direction = dominant_gradient(deri.x, deri.y);
+    return direction / max(length(direction), 1e-6);
+}
+
+/// Whether a stronger normal edge lies within `normal_edge_min_spacing` pixels across the edge,
+/// so only the dominant line of a cluster of creases is drawn.
+fn is_suppressed_normal_edge(uv: vec2f, thickness: f32, deri: NormalDerivatives) -> bool {
+    let magnitude = normal_gradient_magnitude(deri);
+    let direction = normal_gradient_direction(deri) * texel_size;
+
+    for (var i = 1u; i <= ed_uniform.normal_edge_min_spacing; i++) {
+        let offset = direction * f32(i);
+        let ahead = normal_gradient_magnitude(normal_derivatives(uv + offset, thickness));
+        let behind = normal_gradient_magnitude(normal_derivatives(uv - offset, thickness));
+
+        if ahead > magnitude || behind > magnitude {
+            return true;
+        }
+    }
+    return false;
+}
+#endif
+
+fn detect_edge_normal(uv: vec2f, thickness: f32) -> DetectorOutput {
+    let deri = normal_derivatives(uv, thickness);
+
+#ifdef NORMAL_EDGE_SPACING
+    if is_suppressed_normal_edge(uv, thickness, deri) {
+        return DetectorOutput(0.0, vec2f(0.0));
+    }
+#endif
+
+    let grad = normal_gradient_magnitude(deri);
 
-    return f32(grad > ed_uniform.normal_threshold);
+    return DetectorOutput(
+        edge_response(grad, pixel_thresholds.y),
+        dominant_gradient(deri.x, deri.y),
+    );
 }
 
 // ----------------------
 // Color Detection ------
 // ----------------------
 
+/// Convert a linear sRGB color to Oklab.
+fn linear_rgb_to_oklab(rgb: vec3f) -> vec3f {
+    let lms = mat3x3f(
+        0.4122214708, 0.2119034982, 0.0883024619,
+        0.5363325363, 0.6806995451, 0.2817188376,
+        0.0514459929, 0.1073969566, 0.6299787005,
+    ) * max(rgb, vec3f(0.0));
+    let lms_ = pow(lms, vec3f(1.0 / 3.0));
+    return mat3x3f(
+        0.2104542553, 1.9779984951, 0.0259040371,
+        0.7936177850, -2.4285922050, 0.7827717662,
+        -0.0040720468, 0.4505937099, -0.8086757660,
+    ) * lms_;
+}
+
 fn prepass_color(uv: vec2f) -> vec3f {
-    return textureSample(screen_texture, texture_sampler, uv).rgb;
+    let color = textureSampleLevel(screen_texture, texture_sampler, clamp_to_screen_rect(uv), 0.0).rgb;
+#ifdef COLOR_CHROMA_ONLY
+    // Only keep the Oklab chroma (a/b) so pure brightness changes, like shading, produce no gradient.
+    return vec3f(0.0, linear_rgb_to_oklab(color).yz);
+#else
+    return color;
+#endif
 }
 
 fn color_gradient_x(uv: vec2f, y: f32, thickness: f32) -> vec3f {
@@ -217,71 +817,1521 @@ fn color_gradient_y(uv: vec2f, x: f32, thickness: f32) -> vec3f {
     return prepass_color(t_coord) - prepass_color(d_coord);
 }
 
-fn detect_edge_color(uv: vec2f, thickness: f32) -> f32 {
-    let deri_x = 
-        color_gradient_x(uv,  thickness, thickness) +
-        2.0 * color_gradient_x(uv,  0.0, thickness) +
-        color_gradient_x(uv, -thickness, thickness);
+fn detect_edge_color(uv: vec2f, thickness: f32) -> DetectorOutput {
+#ifdef COLOR_OPERATOR_ROBERTS
+    // See `view_z_derivatives`.
+    let offset = 0.5 * thickness * texel_size;
+    let diagonal_down = prepass_color(uv + offset) - prepass_color(uv - offset);
+    let diagonal_up =
+        prepass_color(uv + vec2f(offset.x, -offset.y)) -
+        prepass_color(uv + vec2f(-offset.x, offset.y));
 
-    let deri_y =
-        color_gradient_y(uv,  thickness, thickness) +
-        2.0 * color_gradient_y(uv,  0.0, thickness) +
-        color_gradient_y(uv, -thickness, thickness);
+    var deri_x = 2.0 * (diagonal_down + diagonal_up);
+    var deri_y = 2.0 * (diagonal_down - diagonal_up);
+#else
+#ifdef COLOR_OPERATOR_SCHARR
+    let weights = SCHARR_WEIGHTS;
+#else
+    let weights = SOBEL_WEIGHTS;
+#endif
+    var deri_x =
+        weights.x * color_gradient_x(uv,  thickness, thickness) +
+        weights.y * color_gradient_x(uv,  0.0, thickness) +
+        weights.x * color_gradient_x(uv, -thickness, thickness);
+
+    var deri_y =
+        weights.x * color_gradient_y(uv,  thickness, thickness) +
+        weights.y * color_gradient_y(uv,  0.0, thickness) +
+        weights.x * color_gradient_y(uv, -thickness, thickness);
+#endif
+
+#ifdef COLOR_EDGE_LUMINANCE
+    // Weight every channel by its share of the luminance, so green differences dominate.
+    let luminance_weights = vec3f(0.2126, 0.7152, 0.0722);
+    deri_x *= luminance_weights;
+    deri_y *= luminance_weights;
+#endif
 
     let grad = max(length(deri_x), length(deri_y));
 
-    return f32(grad > ed_uniform.color_threshold);
+    return DetectorOutput(
+        edge_response(grad, pixel_thresholds.z),
+        dominant_gradient(deri_x, deri_y),
+    );
 }
 
-var<private> texture_size: vec2f;
-var<private> texel_size: vec2f;
-var<private> sample_index_i: i32 = 0;
+// ----------------------
+// Object ID Detection --
+// ----------------------
 
-@fragment
-fn fragment(
-#ifdef MULTISAMPLED
-    @builtin(sample_index) sample_index: u32,
+#ifdef ENABLE_OBJECT_ID
+/// The object ID texture is single sampled and may be rendered at its own size.
+fn object_id(uv: vec2f) -> u32 {
+    let size = vec2i(textureDimensions(object_id_texture));
+    let pixel_coord = clamp(vec2i(clamp_to_screen_rect(uv) * vec2f(size)), vec2i(0), size - 1);
+    return textureLoad(object_id_texture, pixel_coord, 0).r;
+}
+
+/// Fires at full strength where any of the 3x3 taps `thickness` texels apart has another object
+/// ID than the center, so interior creases of a mesh never produce an edge.
+fn detect_edge_object_id(uv: vec2f, thickness: f32) -> DetectorOutput {
+    let center = object_id(uv);
+    var differs = false;
+    var gradient = vec2f(0.0);
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2f(f32(x), f32(y));
+            if object_id(uv + texel_size * offset * thickness) != center {
+                differs = true;
+                gradient += offset;
+            }
+        }
+    }
+
+    if !differs {
+        return DetectorOutput(0.0, vec2f(0.0));
+    }
+    return DetectorOutput(forced_edge_response(), gradient);
+}
 #endif
-    in: FullscreenVertexOutput
-) -> @location(0) vec4f {
-#ifdef MULTISAMPLED
-    sample_index_i = i32(sample_index);
+
+// ----------------------
+// Material Detection ---
+// ----------------------
+
+#ifdef ENABLE_MATERIAL_ID
+/// The properties of the material at `uv` which the deferred G-buffer stores per material rather
+/// than per pixel: the lighting pass ID, the base color with the perceptual roughness, the
+/// reflectance with the metallic, and the flags. The emissive and the normal are left out, and so
+/// is the occlusion packed with the reflectance.
+fn material_id(uv: vec2f) -> vec4u {
+    let coord = pixel_coord(uv);
+    let gbuffer = textureLoad(deferred_prepass_texture, coord, 0);
+    let pass_id = textureLoad(deferred_lighting_pass_id_texture, coord, 0).r;
+    return vec4u(pass_id, gbuffer.r, gbuffer.b & 0xFFFFu, gbuffer.a >> 24u);
+}
+
+/// Fires at full strength where any of the 3x3 taps `thickness` texels apart has another
+/// material than the center, like `detect_edge_object_id`.
+fn detect_edge_material_id(uv: vec2f, thickness: f32) -> DetectorOutput {
+    let center = material_id(uv);
+    var differs = false;
+    var gradient = vec2f(0.0);
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2f(f32(x), f32(y));
+            if any(material_id(uv + texel_size * offset * thickness) != center) {
+                differs = true;
+                gradient += offset;
+            }
+        }
+    }
+
+    if !differs {
+        return DetectorOutput(0.0, vec2f(0.0));
+    }
+    return DetectorOutput(forced_edge_response(), gradient);
+}
 #endif
 
-    texture_size = vec2f(textureDimensions(screen_texture));
-    texel_size = 1.0 / texture_size;
+// ----------------------
+// Curvature Detection --
+// ----------------------
 
-    let near_ndc_pos = vec3f(uv_to_ndc(in.uv), 1.0);
-    let near_world_pos = position_ndc_to_world(near_ndc_pos);
+#ifdef CURVATURE_EDGES
+/// The curvature texture may have a different resolution than the view, so it's loaded by its own size.
+fn curvature(uv: vec2f) -> f32 {
+    let size = vec2i(textureDimensions(curvature_texture));
+    let coord = clamp(vec2i(clamp_to_screen_rect(uv) * vec2f(size)), vec2i(0), size - 1);
+    return textureLoad(curvature_texture, coord, 0).r;
+}
 
-    let view_direction = calculate_view(near_world_pos);
-    
-    let normal = prepass_normal_unpack(in.uv);
-    let fresnel = 1.0 - saturate(dot(normal, view_direction));;
+/// The Sobel derivatives of the curvature, compared with `curvature_threshold`.
+fn detect_edge_curvature(uv: vec2f, thickness: f32) -> DetectorOutput {
+    var deri = vec2f(0.0);
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2f(f32(x), f32(y));
+            // The Sobel weights: 1 at the corners, 2 at the edge centers and 0 in the middle.
+            let weights = offset * (2.0 - abs(offset.yx));
+            deri += weights * curvature(uv + texel_size * offset * thickness);
+        }
+    }
 
-    let sample_uv = in.position.xy * min(texel_size.x, texel_size.y);
-    let noise = textureSample(noise_texture, noise_sampler, sample_uv * ed_uniform.uv_distortion.xy);
-    let uv = in.uv + noise.xy * ed_uniform.uv_distortion.zw;
+    let grad = max(abs(deri.x), abs(deri.y));
+    return DetectorOutput(edge_response(grad, ed_uniform.curvature_threshold), deri);
+}
+#endif
 
-    var edge = 0.0;
+// ----------------------
+// Temporal Stability ---
+// ----------------------
 
-#ifdef ENABLE_DEPTH
-    let edge_depth = detect_edge_depth(uv, ed_uniform.depth_thickness, fresnel);
-    edge = max(edge, edge_depth);
+#ifdef MOTION_VECTORS
+/// Loaded at whole texels like the other prepasses, so the motion of a mover isn't blended with
+/// the background across its silhouette.
+fn prepass_motion_vector(uv: vec2f) -> vec2f {
+#ifdef MULTISAMPLED
+    let motion_vector = textureLoad(motion_vector_prepass_texture, pixel_coord(uv), sample_index_i);
+#else
+    let motion_vector = textureLoad(motion_vector_prepass_texture, pixel_coord(uv), 0);
+#endif
+    return motion_vector.rg;
+}
 #endif
 
-#ifdef ENABLE_NORMAL
-    let edge_normal = detect_edge_normal(uv, ed_uniform.normal_thickness);
-    edge = max(edge, edge_normal);
+#ifdef MOTION_GATE
+/// Whether any of the 3x3 taps `thickness` texels apart around `uv` moves faster on screen than
+/// `motion_threshold` pixels per frame, so both sides of the silhouette of a mover pass.
+fn is_moving(uv: vec2f, thickness: f32) -> bool {
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let tap_uv = uv + texel_size * vec2f(f32(x), f32(y)) * thickness;
+            // Motion vectors are in UV units of the viewport.
+            let speed = length(prepass_motion_vector(tap_uv) * view.viewport.zw);
+            if speed > ed_uniform.motion_threshold {
+                return true;
+            }
+        }
+    }
+    return false;
+}
 #endif
 
-#ifdef ENABLE_COLOR
-    let edge_color = detect_edge_color(uv, ed_uniform.color_thickness);
-    edge = max(edge, edge_color);
+#ifdef TEMPORAL
+
+/// Blend `edge` with the previous frame's edge mask, reprojected with the motion vectors.
+fn stabilize_edge(uv: vec2f, edge: f32) -> f32 {
+    let history_uv = uv - prepass_motion_vector(uv);
+
+    // Pixels coming from outside the screen have no history.
+    if any(saturate(history_uv) != history_uv) {
+        return edge;
+    }
+
+    let history = textureSampleLevel(history_texture, texture_sampler, history_uv, 0.0).r;
+    return mix(edge, history, ed_uniform.temporal_blend);
+}
 #endif
 
-    var color = textureSample(screen_texture, texture_sampler, in.uv).rgb;
-    color = mix(color, ed_uniform.edge_color.rgb, edge);
+// ----------------------
+// Posterization --------
+// ----------------------
 
-    return vec4f(color, 1.0);
-}
\ No newline at end of file
+/// Snap each channel of `color` to `levels` evenly spaced steps in a perceptual space.
+fn posterize(color: vec3f, levels: u32) -> vec3f {
+#ifdef HDR
+    // A cheap Reinhard tonemap brings HDR values into [0, 1) before quantizing; it's inverted afterwards.
+    let mapped = color / (1.0 + max(color, vec3f(0.0)));
+#else
+    let mapped = saturate(color);
+#endif
+
+    // Quantize in an approximately perceptual (gamma 2) space so dark steps aren't crushed.
+    let steps = f32(max(levels, 2u) - 1u);
+    var quantized = round(sqrt(max(mapped, vec3f(0.0))) * steps) / steps;
+
+#ifdef HDR
+    // The top level would invert to 1 / 1e-4, keep it half a step below 1.0 instead.
+    quantized = min(quantized, vec3f(1.0 - 0.5 / steps));
+#endif
+
+    let linear = quantized * quantized;
+
+#ifdef HDR
+    return linear / max(1.0 - linear, vec3f(1e-4));
+#else
+    return linear;
+#endif
+}
+
+// ----------------------
+// Hatching -------------
+// ----------------------
+
+/// Coverage in [0, 1] of parallel screen-space hatch lines rotated by `angle` at `frag_coord`.
+fn hatch_lines(frag_coord: vec2f, angle: f32) -> f32 {
+    let across = dot(frag_coord, vec2f(-sin(angle), cos(angle))) / ed_uniform.hatch_spacing;
+    // Distance in pixels to the center of the nearest line.
+    let distance = abs(across - round(across)) * ed_uniform.hatch_spacing;
+    return saturate(ed_uniform.hatch_line_width * 0.5 + 0.5 - distance);
+}
+
+/// Hatch coverage for a pixel of the given scene luminance: single hatching in mid-tones,
+/// cross-hatching in shadows.
+fn hatching(frag_coord: vec2f, luminance: f32) -> f32 {
+    var hatch = 0.0;
+    if luminance < ed_uniform.hatch_mid_threshold {
+        hatch = hatch_lines(frag_coord, ed_uniform.hatch_angle);
+    }
+    if luminance < ed_uniform.hatch_dark_threshold {
+        hatch = max(hatch, hatch_lines(frag_coord, ed_uniform.hatch_angle + 1.5707964));
+    }
+    return hatch;
+}
+
+// ----------------------
+// Halftone -------------
+// ----------------------
+
+#ifdef HALFTONE
+/// Coverage in [0, 1] of a grid of halftone dots rotated by `halftone_angle` at `frag_coord`,
+/// with dots sized so darker luminances cover more of their cell.
+fn halftone(frag_coord: vec2f, luminance: f32) -> f32 {
+    let spacing = ed_uniform.halftone_spacing;
+    let rotation = mat2x2f(
+        cos(ed_uniform.halftone_angle), -sin(ed_uniform.halftone_angle),
+        sin(ed_uniform.halftone_angle), cos(ed_uniform.halftone_angle),
+    );
+    let cell = rotation * frag_coord / spacing;
+    // Distance in pixels to the center of the nearest dot.
+    let distance = length(cell - round(cell)) * spacing;
+
+    // The area of a dot over the area of its cell is the coverage.
+    let coverage = pow(saturate(1.0 - luminance), ed_uniform.halftone_exponent);
+    let radius = sqrt(coverage / 3.1415927) * spacing;
+    return saturate(radius - distance + 0.5);
+}
+#endif
+
+// ----------------------
+// Dithering ------------
+// ----------------------
+
+const BAYER_4X4: array<f32, 16> = array<f32, 16>(
+     0.0,  8.0,  2.0, 10.0,
+    12.0,  4.0, 14.0,  6.0,
+     3.0, 11.0,  1.0,  9.0,
+    15.0,  7.0, 13.0,  5.0,
+);
+
+/// Ordered-dither threshold in (0, 1) of the 4x4 Bayer matrix cell at `frag_coord`.
+fn bayer_threshold(frag_coord: vec2f) -> f32 {
+    let cell = vec2u(frag_coord) % 4u;
+    return (BAYER_4X4[cell.y * 4u + cell.x] + 0.5) / 16.0;
+}
+
+// ----------------------
+// Stippling ------------
+// ----------------------
+
+#ifdef STIPPLE
+/// Coverage in [0, 1] of the stipple dot of the grid cell at `frag_coord` for an edge of
+/// strength `edge`. A random threshold per cell decides whether its dot is drawn, so weak edges
+/// get sparse dots; the dots also grow from `stipple_size` until they fill the cell at full
+/// strength.
+fn stipple(frag_coord: vec2f, edge: f32) -> f32 {
+    let spacing = ed_uniform.stipple_spacing;
+    let cell = floor(frag_coord / spacing);
+    let cell_id = bitcast<vec2u>(vec2i(cell));
+    let hash = pcg_hash(cell_id.x ^ pcg_hash(cell_id.y ^ ed_uniform.noise_seed));
+
+    let threshold = f32(hash & 0xffu) / 256.0;
+    if edge <= threshold {
+        return 0.0;
+    }
+
+    // Half the diagonal of a cell covers it entirely.
+    let radius = mix(0.5 * ed_uniform.stipple_size, 0.7071068 * spacing, edge * edge);
+    // Jitter the dot within the room its cell leaves around it.
+    let jitter = vec2f(vec2u(hash >> 8u, hash >> 20u) & vec2u(0xfffu)) / 4096.0 - 0.5;
+    let center = (cell + 0.5) * spacing + jitter * max(spacing - 2.0 * radius, 0.0);
+    return saturate(radius - distance(frag_coord, center) + 0.5);
+}
+#endif
+
+// ----------------------
+// Dilation -------------
+// ----------------------
+
+/// The maximum of the edge mask over a disk of `dilate_radius` pixels around `frag_coord`.
+fn dilate_edge_mask(frag_coord: vec2f) -> f32 {
+    let size = vec2i(textureDimensions(edge_mask_texture));
+    let center = vec2i(frag_coord);
+    let radius = i32(ed_uniform.dilate_radius);
+
+    var edge = 0.0;
+    for (var y = -radius; y <= radius; y++) {
+        for (var x = -radius; x <= radius; x++) {
+            if x * x + y * y > radius * radius {
+                continue;
+            }
+            let pixel_coord = clamp(center + vec2i(x, y), vec2i(0), size - 1);
+            edge = max(edge, textureLoad(edge_mask_texture, pixel_coord, 0).r);
+        }
+    }
+    return edge;
+}
+
+#ifdef DISTANCE_FIELD
+/// The distance in pixels from `frag_coord` to the nearest pixel of the edge mask, searched over
+/// a disk of `distance_field_radius` pixels, minus `dilate_radius` so it's negative inside the
+/// dilated lines. Pixels without an edge in reach get `distance_field_radius - dilate_radius`.
+fn edge_distance(frag_coord: vec2f) -> f32 {
+    let size = vec2i(textureDimensions(edge_mask_texture));
+    let center = vec2i(frag_coord);
+    let radius = i32(ed_uniform.distance_field_radius);
+
+    var distance_squared = radius * radius;
+    for (var y = -radius; y <= radius; y++) {
+        for (var x = -radius; x <= radius; x++) {
+            let offset_squared = x * x + y * y;
+            if offset_squared >= distance_squared {
+                continue;
+            }
+            let pixel_coord = clamp(center + vec2i(x, y), vec2i(0), size - 1);
+            if textureLoad(edge_mask_texture, pixel_coord, 0).r > 0.0 {
+                distance_squared = offset_squared;
+            }
+        }
+    }
+    return sqrt(f32(distance_squared)) - f32(ed_uniform.dilate_radius);
+}
+#endif
+
+#ifdef HALO
+/// The edge mask blurred over `halo_radius` pixels around `frag_coord`, weighted with a falloff
+/// towards the rim.
+fn halo_edge_mask(frag_coord: vec2f) -> f32 {
+    let size = vec2i(textureDimensions(edge_mask_texture));
+    let center = vec2i(frag_coord);
+    let radius = i32(ed_uniform.halo_radius);
+
+    var halo = 0.0;
+    var total_weight = 0.0;
+    for (var y = -radius; y <= radius; y++) {
+        for (var x = -radius; x <= radius; x++) {
+            let distance = length(vec2f(f32(x), f32(y))) / f32(radius + 1);
+            if distance >= 1.0 {
+                continue;
+            }
+            let weight = (1.0 - distance) * (1.0 - distance);
+            let pixel_coord = clamp(center + vec2i(x, y), vec2i(0), size - 1);
+            halo += textureLoad(edge_mask_texture, pixel_coord, 0).r * weight;
+            total_weight += weight;
+        }
+    }
+    return halo / total_weight;
+}
+#endif
+
+#ifdef POOLING
+/// The edge mask spread over `pooling_radius` pixels around `frag_coord` with a linear falloff.
+/// Three rings of eight taps keep the cost fixed whatever the radius.
+fn pooled_edge_mask(frag_coord: vec2f) -> f32 {
+    let size = vec2i(textureDimensions(edge_mask_texture));
+    let center = vec2i(frag_coord);
+
+    var pooling = textureLoad(edge_mask_texture, center, 0).r;
+    for (var ring = 1; ring <= 3; ring++) {
+        let distance = f32(ring) / 3.0;
+        let weight = 1.0 - distance + 1.0 / 3.0;
+        for (var i = 0; i < 8; i++) {
+            // Offset every ring by half a step so the taps don't line up along the diagonals.
+            let angle = (f32(i) + 0.5 * f32(ring % 2)) * 0.7853982;
+            let offset = vec2f(cos(angle), sin(angle)) * distance * ed_uniform.pooling_radius;
+            let pixel_coord = clamp(center + vec2i(round(offset)), vec2i(0), size - 1);
+            pooling = max(pooling, textureLoad(edge_mask_texture, pixel_coord, 0).r * weight);
+        }
+    }
+    return pooling;
+}
+#endif
+
+var<private> texture_size: vec2f;
+var<private> texel_size: vec2f;
+var<private> sample_index_i: i32 = 0;
+// The depth, normal and color thresholds of the pixel, set by `detect_edges`.
+var<private> pixel_thresholds: vec3f;
+
+// Read every frame rather than stored in the uniform, so it's never stale after a resize.
+fn init_texture_size() {
+    texture_size = vec2f(textureDimensions(screen_texture));
+#ifdef PIXELATE
+    // Gradients are taken between neighboring cells of the virtual low-resolution grid.
+    texel_size = 1.0 / ed_uniform.pixelate_grid;
+#else
+    texel_size = 1.0 / texture_size;
+#endif
+}
+
+#ifdef PIXELATE
+/// Move `in` to the center of its cell of the virtual low-resolution grid.
+fn pixelate(in: FullscreenVertexOutput) -> FullscreenVertexOutput {
+    var snapped = in;
+    snapped.uv = (floor(in.uv * ed_uniform.pixelate_grid) + 0.5) / ed_uniform.pixelate_grid;
+    snapped.position = vec4f(snapped.uv * texture_size, in.position.zw);
+    return snapped;
+}
+#endif
+
+/// Thins `thickness` with the distance of the surface, down to a single texel.
+/// Thicknesses below a texel are left unchanged.
+fn thickness_with_falloff(thickness: f32, view_distance: f32) -> f32 {
+    let tapered = thickness / (1.0 + ed_uniform.thickness_falloff * view_distance);
+    return max(tapered, min(thickness, 1.0));
+}
+
+/// A PCG hash, see "Hash Functions for GPU Rendering" (Jarzynski and Olano, 2020).
+fn pcg_hash(input: u32) -> u32 {
+    let state = input * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+/// Offset of the noise domain derived from `noise_seed`, so each seed gives a different but
+/// reproducible pattern. Seed 0 keeps the unshifted pattern.
+fn noise_seed_offset() -> vec2f {
+    let seed = ed_uniform.noise_seed;
+    if seed == 0u {
+        return vec2f(0.0);
+    }
+
+    // The noise texture repeats, so a fraction of it is enough.
+    let hash = vec2u(pcg_hash(seed), pcg_hash(seed ^ 0x9e3779b9u));
+    return vec2f(hash & vec2u(0xffffu)) / 65536.0;
+}
+
+#ifdef DISTORTION_WORLD
+/// Coordinates of the distortion noise on the surface seen by `in`, so the distortion sticks to
+/// objects as the camera moves. The world position is projected along the axis closest to the
+/// normal. The sky has no position and keeps the screen-space coordinates.
+fn world_noise_uv(in: FullscreenVertexOutput, normal: vec3f) -> vec2f {
+    let depth = prepass_depth(in.uv);
+    if is_background(depth) {
+        return in.position.xy / max(texture_size.x, texture_size.y);
+    }
+
+    let world_pos = position_ndc_to_world(vec3f(view_uv_to_ndc(in.uv), depth));
+    let axis = abs(normal);
+    if axis.x > axis.y && axis.x > axis.z {
+        return world_pos.yz;
+    } else if axis.y > axis.z {
+        return world_pos.xz;
+    }
+    return world_pos.xy;
+}
+#endif
+
+/// Whether the 3x3 neighborhood of `uv` is a flat fill, such as the clear color: a constant depth
+/// and a luminance variance below `min_neighborhood_variance`.
+fn is_flat_neighborhood(uv: vec2f) -> bool {
+    let center_depth = prepass_depth(uv);
+
+    var sum = 0.0;
+    var sum_squares = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let sample_uv = uv + texel_size * vec2f(f32(x), f32(y));
+            if abs(prepass_depth(sample_uv) - center_depth) > 1e-6 {
+                return false;
+            }
+
+            let color = textureSampleLevel(screen_texture, texture_sampler, sample_uv, 0.0).rgb;
+            let luminance = dot(color, vec3f(0.2126, 0.7152, 0.0722));
+            sum += luminance;
+            sum_squares += luminance * luminance;
+        }
+    }
+
+    let mean = sum / 9.0;
+    let variance = sum_squares / 9.0 - mean * mean;
+    return variance < ed_uniform.min_neighborhood_variance;
+}
+
+/// Runs the enabled detectors at `uv`, with the depth, normal and color thickness in `thickness`.
+fn run_detectors(uv: vec2f, thickness: vec3f, fresnel: f32) -> CombinedDetectors {
+    var detectors = no_detectors();
+
+#ifdef ENABLE_DEPTH
+    detectors = combine(detectors, detect_edge_depth(uv, thickness.x, fresnel, false), true);
+#endif
+
+#ifdef SECONDARY_DEPTH
+    detectors = combine(detectors, detect_edge_depth(uv, thickness.x, fresnel, true), true);
+#endif
+
+#ifdef ENABLE_NORMAL
+    detectors = combine(detectors, detect_edge_normal(uv, thickness.y), true);
+#endif
+
+#ifdef ENABLE_COLOR
+    detectors = combine(detectors, detect_edge_color(uv, thickness.z), false);
+#endif
+
+#ifdef ENABLE_OBJECT_ID
+    detectors = combine(detectors, detect_edge_object_id(uv, thickness.x), true);
+#endif
+
+#ifdef ENABLE_MATERIAL_ID
+    detectors = combine(detectors, detect_edge_material_id(uv, thickness.z), false);
+#endif
+
+#ifdef CURVATURE_EDGES
+    detectors = combine(detectors, detect_edge_curvature(uv, thickness.y), true);
+#endif
+
+    return detectors;
+}
+
+#ifdef THIN_EDGES
+/// Whether `response` is a local maximum across the edge, the non-maximum suppression of Canny.
+/// The gradient direction is snapped to one of the 8 neighbors, which are detected again, so
+/// only the center line of a wide edge is kept.
+fn is_maximum_across_edge(
+    uv: vec2f,
+    thickness: vec3f,
+    fresnel: f32,
+    response: f32,
+    gradient: vec2f,
+) -> bool {
+    let direction = round(gradient / max(length(gradient), 1e-6));
+    let offset = direction * texel_size;
+
+    let ahead = combined_response(run_detectors(uv + offset, thickness, fresnel));
+    let behind = combined_response(run_detectors(uv - offset, thickness, fresnel));
+
+    // Strict on one side only, so one of two equally strong pixels survives.
+    return response >= ahead && response > behind;
+}
+#endif
+
+/// The world position of the surface at `uv` with the prepass depth `depth`, which must be above 0
+/// since the background at a depth of 0 is infinitely far away.
+fn surface_world_position(uv: vec2f, depth: f32) -> vec3f {
+    return position_ndc_to_world(vec3f(view_uv_to_ndc(uv), depth));
+}
+
+#ifdef EDGE_VOLUME
+/// The signed distance from `position` to the boundary of the volume, negative inside.
+fn volume_distance(position: vec3f) -> f32 {
+#ifdef EDGE_VOLUME_BOX
+    let q = abs(position - ed_uniform.volume_center) - ed_uniform.volume_extents;
+    return length(max(q, vec3f(0.0))) + min(max(q.x, max(q.y, q.z)), 0.0);
+#else
+    return length(position - ed_uniform.volume_center) - ed_uniform.volume_extents.x;
+#endif
+}
+
+/// How strongly edges at `uv` are drawn for the world-space volume: 1 inside, fading out over
+/// `volume_falloff` towards the boundary and 0 outside.
+fn volume_weight(uv: vec2f) -> f32 {
+    let depth = prepass_depth(uv);
+    // The background is infinitely far away, outside of any volume.
+    if is_background(depth) {
+        return 0.0;
+    }
+
+    let distance = volume_distance(surface_world_position(uv, depth));
+
+    if ed_uniform.volume_falloff <= 0.0 {
+        return f32(distance <= 0.0);
+    }
+    return saturate(-distance / ed_uniform.volume_falloff);
+}
+#endif
+
+#ifdef HEIGHT_RANGE
+/// How strongly the effect applies at `uv` for the height range: 1 inside, fading out over
+/// `height_falloff` towards its bounds and 0 outside, including the background.
+fn height_weight(uv: vec2f) -> f32 {
+    let depth = prepass_depth(uv);
+    if is_background(depth) {
+        return 0.0;
+    }
+
+    let height = surface_world_position(uv, depth).y;
+    let inside = min(height - ed_uniform.height_min, ed_uniform.height_max - height);
+
+    if ed_uniform.height_falloff <= 0.0 {
+        return f32(inside >= 0.0);
+    }
+    return saturate(inside / ed_uniform.height_falloff);
+}
+#endif
+
+#ifdef EFFECT_DEPTH_RANGE
+/// Whether the view distance of the surface at `uv` is within `effect_depth_min` and
+/// `effect_depth_max`. The infinitely far background never is.
+fn in_effect_depth_range(uv: vec2f) -> bool {
+    let distance = abs(prepass_view_z(uv));
+    return distance >= ed_uniform.effect_depth_min && distance <= ed_uniform.effect_depth_max;
+}
+#endif
+
+#ifdef NO_BACKGROUND_EDGES
+/// Whether a pixel without geometry lies within `thickness` texels of `uv`, see `is_background`.
+fn touches_background(uv: vec2f, thickness: f32) -> bool {
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            if is_background(prepass_depth(uv + texel_size * vec2f(f32(x), f32(y)) * thickness)) {
+                return true;
+            }
+        }
+    }
+    return false;
+}
+#endif
+
+#ifdef FOLIAGE_SUPPRESSION
+/// How much more the normals vary along 5 taps `thickness` texels apart than between the outer
+/// taps, summed across and along `uv`. Flat and curved surfaces and single creases change
+/// direction once, so the variation adds up to the change between the outer taps, while the
+/// leaves of foliage turn back and forth.
+fn normal_noise(uv: vec2f, thickness: f32) -> f32 {
+    var noise = 0.0;
+    for (var axis = 0; axis < 2; axis++) {
+        var offset = vec2f(0.0);
+        offset[axis] = thickness;
+        offset *= texel_size;
+
+        let first = prepass_normal_unpack(uv - 2.0 * offset);
+        var previous = first;
+        var variation = 0.0;
+        for (var i = -1; i <= 2; i++) {
+            let normal = prepass_normal_unpack(uv + f32(i) * offset);
+            variation += length(normal - previous);
+            previous = normal;
+        }
+        noise += variation - length(previous - first);
+    }
+    return noise;
+}
+#endif
+
+#ifdef VIEWMODEL_CUTOFF
+/// Whether the surface at `uv` is nearer than `viewmodel_depth_cutoff`, e.g. a first-person weapon.
+fn is_viewmodel(uv: vec2f) -> bool {
+    let depth = prepass_depth(uv);
+    return !is_background(depth) && abs(depth_ndc_to_view_z(depth)) < ed_uniform.viewmodel_depth_cutoff;
+}
+
+/// Whether the viewmodel is the closest surface of any of the 3x3 taps `thickness` texels apart
+/// around `uv`, so neither its edges nor the silhouette of its outline are drawn.
+fn touches_viewmodel(uv: vec2f, thickness: f32) -> bool {
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            if is_viewmodel(uv + texel_size * vec2f(f32(x), f32(y)) * thickness) {
+                return true;
+            }
+        }
+    }
+    return false;
+}
+#endif
+
+#ifdef MARKED_ENTITIES
+struct MarkerCoverage {
+    // Whether an entity with `NoEdgeDetection` is the closest surface of any tap.
+    excluded: bool,
+    // The number of taps where an entity with `EdgeDetectionTarget` is the closest surface.
+    targets: u32,
+    // The number of taps where a transparent mesh is the closest surface.
+    transparent: u32,
+    // The `EdgeDetectionColor` of the colored mesh closest to the center tap, transparent where
+    // there is none.
+    color: vec4f,
+}
+
+/// Which marked entities are the closest surface of the 3x3 taps `thickness` texels apart
+/// around `uv`, so their silhouettes are covered as well.
+fn marker_coverage(uv: vec2f, thickness: f32) -> MarkerCoverage {
+    var coverage = MarkerCoverage(false, 0u, 0u, vec4f(0.0));
+    var color_distance = 3;
+
+    // The marker textures are single sampled and may be rendered at their own size.
+    let size = vec2i(textureDimensions(marker_depth_texture));
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let tap_uv = uv + texel_size * vec2f(f32(x), f32(y)) * thickness;
+            let pixel_coord = clamp(vec2i(clamp_to_screen_rect(tap_uv) * vec2f(size)), vec2i(0), size - 1);
+            let marker_depth = textureLoad(marker_depth_texture, pixel_coord, 0);
+            // Both passes transform the same vertices, but not with the same shader, so the
+            // depths are compared with a small tolerance.
+            if marker_depth <= 0.0 || marker_depth < prepass_depth(tap_uv) * (1.0 - 1e-4) {
+                continue;
+            }
+            // See `EdgeMarker`, unmarked meshes are only rendered for object IDs.
+            let marker = textureLoad(marker_texture, pixel_coord, 0).r;
+            if marker > 0.75 {
+                coverage.targets += 1u;
+            } else if marker > 0.375 {
+                coverage.excluded = true;
+            } else if marker > 0.125 {
+                coverage.transparent += 1u;
+            }
+#ifdef MESH_PARAMS
+            let color = textureLoad(marker_color_texture, pixel_coord, 0);
+            if color.a > 0.0 && x * x + y * y < color_distance {
+                coverage.color = color;
+                color_distance = x * x + y * y;
+            }
+#endif
+        }
+    }
+    return coverage;
+}
+#endif
+
+#ifdef MESH_PARAMS
+/// The thresholds of the mesh shown at `uv` where it has an `EdgeDetectionOverride`, and
+/// `thresholds` elsewhere.
+fn mesh_thresholds(uv: vec2f, thresholds: vec3f) -> vec3f {
+    let size = vec2i(textureDimensions(marker_depth_texture));
+    let pixel_coord = clamp(vec2i(clamp_to_screen_rect(uv) * vec2f(size)), vec2i(0), size - 1);
+    let marker_depth = textureLoad(marker_depth_texture, pixel_coord, 0);
+    // The same tolerance as `marker_coverage`.
+    if marker_depth <= 0.0 || marker_depth < prepass_depth(uv) * (1.0 - 1e-4) {
+        return thresholds;
+    }
+    // 0.0 keeps the threshold of the camera.
+    let overrides = textureLoad(marker_thresholds_texture, pixel_coord, 0).xyz;
+    return select(thresholds, overrides, overrides > vec3f(0.0));
+}
+#endif
+
+#ifdef XRAY_OUTLINES
+/// Whether the 3x3 taps `thickness` texels apart around `uv` straddle the silhouette of a target
+/// and other geometry is in front of it at one of them.
+fn on_hidden_silhouette(uv: vec2f, thickness: f32) -> bool {
+    var inside = 0u;
+    var hidden = false;
+
+    let size = vec2i(textureDimensions(xray_depth_texture));
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let tap_uv = uv + texel_size * vec2f(f32(x), f32(y)) * thickness;
+            let coord = clamp(vec2i(clamp_to_screen_rect(tap_uv) * vec2f(size)), vec2i(0), size - 1);
+            let target_depth = textureLoad(xray_depth_texture, coord, 0);
+            if target_depth <= 0.0 {
+                continue;
+            }
+            inside += 1u;
+            // The same tolerance as `marker_coverage`, so visible targets are never hidden.
+            if target_depth < prepass_depth(tap_uv) * (1.0 - 1e-4) {
+                hidden = true;
+            }
+        }
+    }
+    return inside > 0u && inside < 9u && hidden;
+}
+
+/// Whether `uv` is on the part of the silhouette of a target which other geometry hides, and
+/// no tap sees a target, where the edges take `occluded_edge_color`.
+fn is_occluded_edge(uv: vec2f, thickness: f32) -> bool {
+    let coverage = marker_coverage(uv, thickness);
+    return coverage.targets == 0u && !coverage.excluded && on_hidden_silhouette(uv, thickness);
+}
+#endif
+
+struct Detection {
+    // The largest ratio of a gradient to its threshold, see `edge_response`.
+    response: f32,
+    // The strength of the edge at the pixel.
+    edge: f32,
+    // The Sobel derivatives of the detector with the strongest response, see `DetectorOutput`.
+    gradient: vec2f,
+    // Whether the edge is the hidden silhouette of a target, see `is_occluded_edge`.
+    occluded: bool,
+    // The color of the mesh the edge belongs to, transparent for `edge_color`, see
+    // `MarkerCoverage`.
+    marker_color: vec4f,
+}
+
+#ifdef RADIAL_FALLOFF
+/// The weight of edges at `uv` from `radial_falloff`, 1 at the screen center unless inverted.
+fn radial_weight(uv: vec2f) -> f32 {
+    // Measured in pixels so the falloff is circular, then scaled to 1 in the corners.
+    let offset = (view_uv(uv) - 0.5) * view.viewport.zw;
+    let distance = length(offset) / length(0.5 * view.viewport.zw);
+    let weight = 1.0 - saturate(ed_uniform.radial_falloff * distance);
+#ifdef RADIAL_INVERT
+    return 1.0 - weight;
+#else
+    return weight;
+#endif
+}
+#endif
+
+fn detect_edges(fragment_in: FullscreenVertexOutput) -> Detection {
+#ifdef PIXELATE
+    // Every pixel of a grid cell samples at its center, so the cell gets a single edge value.
+    let in = pixelate(fragment_in);
+#else
+    let in = fragment_in;
+#endif
+
+    let normal = prepass_normal_unpack(in.uv);
+
+    let near_ndc_pos = vec3f(view_uv_to_ndc(in.uv), 1.0);
+    let near_world_pos = position_ndc_to_world(near_ndc_pos);
+
+    let view_direction = calculate_view(near_world_pos);
+    
+    let fresnel = 1.0 - saturate(dot(normal, view_direction));;
+
+#ifdef DISTORTION_WORLD
+    let sample_uv = world_noise_uv(in, normal);
+#else
+    let sample_uv = in.position.xy / max(texture_size.x, texture_size.y);
+#endif
+    let noise_uv = sample_uv * ed_uniform.uv_distortion.xy + noise_seed_offset();
+    let noise = textureSampleLevel(noise_texture, noise_sampler, noise_uv, 0.0);
+    var offset = noise.xy * ed_uniform.uv_distortion.zw;
+
+    // The second octave, skipped when it's off.
+    if any(ed_uniform.uv_distortion2.zw != vec2f(0.0)) {
+        // Shifted by half the texture so the octaves don't line up at the origin.
+        let noise_uv2 = sample_uv * ed_uniform.uv_distortion2.xy + noise_seed_offset() + 0.5;
+        let noise2 = textureSampleLevel(noise_texture, noise_sampler, noise_uv2, 0.0);
+        offset += noise2.xy * ed_uniform.uv_distortion2.zw;
+    }
+
+#ifdef UV_DISTORTION_PIXELS
+    let uv = in.uv + offset / texture_size;
+#else
+    let uv = in.uv + offset;
+#endif
+
+    var depth_thickness = ed_uniform.depth_thickness;
+    var normal_thickness = ed_uniform.normal_thickness;
+    var color_thickness = ed_uniform.color_thickness;
+
+    if ed_uniform.thickness_falloff > 0.0 {
+        let view_distance = abs(prepass_view_z(uv));
+        depth_thickness = thickness_with_falloff(depth_thickness, view_distance);
+        normal_thickness = thickness_with_falloff(normal_thickness, view_distance);
+        color_thickness = thickness_with_falloff(color_thickness, view_distance);
+    }
+
+    let thickness = vec3f(depth_thickness, normal_thickness, color_thickness);
+
+    pixel_thresholds = vec3f(ed_uniform.depth_threshold, ed_uniform.normal_threshold, ed_uniform.color_threshold);
+#ifdef MESH_PARAMS
+    pixel_thresholds = mesh_thresholds(uv, pixel_thresholds);
+#endif
+
+    let detectors = run_detectors(uv, thickness, fresnel);
+    var response = combined_response(detectors);
+
+#ifdef NO_BACKGROUND_EDGES
+    if touches_background(uv, max(thickness.x, max(thickness.y, thickness.z))) {
+        response = 0.0;
+    }
+#endif
+
+#ifdef FOLIAGE_SUPPRESSION
+    // Dividing the ratios of the gradients to the thresholds multiplies the thresholds.
+    response /= 1.0 + ed_uniform.foliage_suppression * normal_noise(uv, thickness.y);
+#endif
+
+#ifdef VIEWMODEL_CUTOFF
+    if touches_viewmodel(uv, max(thickness.x, max(thickness.y, thickness.z))) {
+        response = 0.0;
+    }
+#endif
+
+#ifdef MARKED_ENTITIES
+    let coverage = marker_coverage(uv, max(thickness.x, max(thickness.y, thickness.z)));
+    if coverage.excluded {
+        response = 0.0;
+    }
+#ifdef TRANSPARENT_OUTLINES
+    if coverage.transparent > 0u && coverage.transparent < 9u && !coverage.excluded {
+        // The silhouette of a transparent mesh, which isn't in the prepasses.
+        response = max(response, forced_edge_response());
+    }
+#endif
+#ifdef TARGETS_ONLY
+    if coverage.targets == 0u {
+        response = 0.0;
+    } else if coverage.targets < 9u && !coverage.excluded {
+        // The silhouette of a target against unmarked geometry, at full strength.
+        response = max(response, forced_edge_response());
+    }
+#endif
+#else ifdef TARGETS_ONLY
+    // No target is visible.
+    response = 0.0;
+#endif
+
+    var marker_color = vec4f(0.0);
+#ifdef MESH_PARAMS
+    marker_color = coverage.color;
+#endif
+
+    var occluded = false;
+#ifdef XRAY_OUTLINES
+    // Where a target is visible at any tap, its outline is the same as without x-ray.
+    if coverage.targets == 0u && !coverage.excluded
+        && on_hidden_silhouette(uv, max(thickness.x, max(thickness.y, thickness.z))) {
+        response = max(response, forced_edge_response());
+        occluded = true;
+    }
+#endif
+
+#ifdef MOTION_GATE
+    if !is_moving(uv, max(thickness.x, max(thickness.y, thickness.z))) {
+        response = 0.0;
+    }
+#endif
+
+    if ed_uniform.min_neighborhood_variance > 0.0 && is_flat_neighborhood(uv) {
+        response = 0.0;
+    }
+
+#ifdef THRESHOLD_MAP
+    // Multiplying every threshold by the map divides the ratios of the gradients to them.
+    let threshold_scale = textureSampleLevel(threshold_map_texture, texture_sampler, in.uv, 0.0).r;
+    response /= max(threshold_scale, 1e-6);
+#endif
+
+    var edge = f32(response > 1.0);
+#ifdef DITHER
+    // Keep the continuous response, so the dither pattern thins out as gradients fall below the
+    // thresholds instead of only dithering the binary edges.
+    edge = saturate(response);
+#endif
+
+    if ed_uniform.strength_steps > 0u {
+        // One step per multiple of the threshold exceeded, so stronger gradients draw heavier lines.
+        let steps = f32(ed_uniform.strength_steps);
+        edge = clamp(floor(response), 0.0, steps) / steps;
+    }
+
+#ifdef THIN_EDGES
+    if !is_maximum_across_edge(uv, thickness, fresnel, response, detectors.strongest.gradient) {
+        edge = 0.0;
+    }
+#endif
+
+    if ed_uniform.rim_modulation > 0.0 {
+        // Intensify edges where the surface turns away from the camera.
+        edge *= pow(fresnel, ed_uniform.rim_modulation);
+    }
+
+#ifdef DISTANCE_FADE
+    // Linear from the start to the end of the fade, a hard cut when they're equal.
+    let fade_distance = abs(prepass_view_z(uv));
+    let fade_length = max(ed_uniform.distance_fade_end - ed_uniform.distance_fade_start, 1e-6);
+    edge *= 1.0 - saturate((fade_distance - ed_uniform.distance_fade_start) / fade_length);
+#endif
+
+#ifdef EFFECT_DEPTH_RANGE
+    // Dropped before dilation and pooling, so edges outside the range don't grow into it.
+    if !in_effect_depth_range(in.uv) {
+        response = 0.0;
+        edge = 0.0;
+    }
+#endif
+
+#ifdef EDGE_VOLUME
+    edge *= volume_weight(in.uv);
+#endif
+
+#ifdef HEIGHT_RANGE
+    edge *= height_weight(in.uv);
+#endif
+
+#ifdef NEAR_FADE
+    let near_distance = abs(prepass_view_z(uv));
+    let near_length = max(ed_uniform.near_fade_end - ed_uniform.near_fade_start, 1e-6);
+    edge *= saturate((near_distance - ed_uniform.near_fade_start) / near_length);
+#endif
+
+#ifdef RADIAL_FALLOFF
+    edge *= radial_weight(in.uv);
+#endif
+
+    // Overlapping detectors never draw more than a single full-strength edge.
+    return Detection(response, saturate(edge), detectors.strongest.gradient, occluded, marker_color);
+}
+
+#ifdef GRADIENT_OUTPUT
+/// The angle of the gradient in radians, counter-clockwise from the right of the screen, and the
+/// response it belongs to, written for later passes.
+fn gradient_output(detection: Detection) -> vec2f {
+    let angle = atan2(-detection.gradient.y, detection.gradient.x);
+    return vec2f(angle, detection.response);
+}
+#endif
+
+#ifdef EDGE_SHADOW
+/// The edges `edge_shadow_offset` pixels up-left of the fragment, drawn as its drop shadow.
+fn detect_edge_shadow(in: FullscreenVertexOutput) -> f32 {
+    var shifted = in;
+    shifted.position = vec4f(in.position.xy - ed_uniform.edge_shadow_offset, in.position.zw);
+    shifted.uv = in.uv - ed_uniform.edge_shadow_offset / texture_size;
+    return detect_edges(shifted).edge;
+}
+#endif
+
+#ifdef FOG
+// The fog modes, see `EdgeDetectionUniform::FOG_MODE_LINEAR` and the other modes.
+const FOG_MODE_LINEAR: u32 = 1u;
+const FOG_MODE_EXPONENTIAL: u32 = 2u;
+const FOG_MODE_EXPONENTIAL_SQUARED: u32 = 3u;
+const FOG_MODE_ATMOSPHERIC: u32 = 4u;
+
+/// The distance from the camera to the nearest surface around `uv`, so edges on silhouettes are
+/// fogged like the object in front rather than the background.
+fn fog_distance(uv: vec2f) -> f32 {
+    var depth = prepass_depth(uv);
+    depth = max(depth, prepass_depth(uv + vec2f(texel_size.x, 0.0)));
+    depth = max(depth, prepass_depth(uv - vec2f(texel_size.x, 0.0)));
+    depth = max(depth, prepass_depth(uv + vec2f(0.0, texel_size.y)));
+    depth = max(depth, prepass_depth(uv - vec2f(0.0, texel_size.y)));
+
+    // A depth of 0 is infinitely far away, which can't be reconstructed.
+    let world_position = surface_world_position(uv, max(depth, 1e-7));
+    return length(world_position - view.world_position);
+}
+
+/// Applies the fog of the camera to the line color, with the falloff of Bevy's `DistanceFog`.
+fn apply_fog(line_color: vec3f, distance: f32) -> vec3f {
+    let fog_color = ed_uniform.fog_color;
+    let params = ed_uniform.fog_params;
+
+    if ed_uniform.fog_mode == FOG_MODE_ATMOSPHERIC {
+        // Atmospheric, with separate extinction and inscattering per channel.
+        let extinction = 1.0 - 1.0 / exp(distance * params.xyz);
+        let inscattering = 1.0 - 1.0 / exp(distance * ed_uniform.fog_inscattering.xyz);
+        return line_color * (1.0 - extinction * fog_color.a)
+            + fog_color.rgb * inscattering * fog_color.a;
+    }
+
+    var fog_intensity = 0.0;
+    if ed_uniform.fog_mode == FOG_MODE_LINEAR {
+        fog_intensity = 1.0 - saturate((params.y - distance) / (params.y - params.x));
+    } else if ed_uniform.fog_mode == FOG_MODE_EXPONENTIAL {
+        fog_intensity = 1.0 - 1.0 / exp(distance * params.x);
+    } else if ed_uniform.fog_mode == FOG_MODE_EXPONENTIAL_SQUARED {
+        let d = distance * params.x;
+        fog_intensity = 1.0 - 1.0 / exp(d * d);
+    }
+
+    return mix(line_color, fog_color.rgb, fog_intensity * fog_color.a);
+}
+#endif
+
+/// The color the edges are drawn with for an edge color of `rgb`.
+fn to_line_color(rgb: vec3f) -> vec3f {
+#ifdef HDR
+    // HDR targets keep edge colors above 1.0 so they can feed later passes (e.g. bloom).
+    return rgb * ed_uniform.edge_emissive_strength;
+#else
+    return saturate(rgb);
+#endif
+}
+
+/// Composite the edges over the scene color.
+fn composite(
+    in: FullscreenVertexOutput,
+    normal: vec3f,
+    response: f32,
+    edge_mask: f32,
+    shadow_mask: f32,
+    pooling: f32,
+    halo: f32,
+    occluded: bool,
+    marker_color: vec4f,
+) -> vec4f {
+    let source = textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0);
+
+#ifdef EXCLUSION_RECT
+    let rect = ed_uniform.exclusion_rect;
+    let rect_uv = view_uv(in.uv);
+    if all(rect_uv >= rect.xy) && all(rect_uv <= rect.zw) {
+        return source;
+    }
+#endif
+
+#ifdef EFFECT_DEPTH_RANGE
+    if !in_effect_depth_range(in.uv) {
+        return source;
+    }
+#endif
+
+#ifdef HEIGHT_RANGE
+    if height_weight(in.uv) <= 0.0 {
+        return source;
+    }
+#endif
+
+#ifdef DEBUG_SPLIT
+    // The unmodified scene on the left for comparison.
+    if view_uv(in.uv).x < ed_uniform.debug_split {
+        return source;
+    }
+#endif
+
+    var edge = pow(edge_mask, ed_uniform.edge_response_gamma);
+
+#ifdef BLEND_MODE_INVERT
+    edge = 1.0 - edge;
+#endif
+
+#ifdef STIPPLE
+    edge = stipple(in.position.xy, edge);
+#endif
+
+#ifdef HATCHING
+    // Hatch lines are drawn like edges, in the edge color.
+    let scene_luminance = dot(source.rgb, vec3f(0.2126, 0.7152, 0.0722));
+    edge = max(edge, hatching(in.position.xy, scene_luminance));
+#endif
+
+    // The alpha of the edge color is the opacity of the edges.
+    var edge_alpha = ed_uniform.edge_color.a;
+    if marker_color.a > 0.0 {
+        edge_alpha = marker_color.a;
+    }
+#ifdef XRAY_OUTLINES
+    if occluded {
+        edge_alpha = ed_uniform.occluded_edge_color.a;
+    }
+#endif
+    edge *= edge_alpha;
+
+#ifdef STRENGTH_MASK
+    edge *= textureSampleLevel(strength_mask_texture, texture_sampler, in.uv, 0.0).r;
+#endif
+
+#ifdef DITHER
+    // Draw partial coverage as an ordered-dither pattern instead of blending.
+    edge = f32(edge > bayer_threshold(in.position.xy));
+#endif
+
+#ifdef EDGE_LIGHTING
+    // Shift the edge color between the shadow and lit colors with the facing of the surface.
+    let lighting = dot(normal, ed_uniform.edge_light_direction) * 0.5 + 0.5;
+    var edge_rgb = mix(ed_uniform.edge_color_shadow.rgb, ed_uniform.edge_color_lit.rgb, lighting);
+#else ifdef EDGE_COLOR_NEARER_OBJECT
+    var edge_rgb = nearer_object_color(in.uv);
+#else ifdef DEPTH_BANDS
+    var edge_rgb = depth_band_color(in.uv);
+#else ifdef EDGE_COLOR_GRADIENT
+    var edge_rgb = screen_gradient_color(in.uv);
+#else
+    var edge_rgb = ed_uniform.edge_color.rgb;
+#endif
+
+    if marker_color.a > 0.0 {
+        edge_rgb = marker_color.rgb;
+    }
+
+#ifdef XRAY_OUTLINES
+    if occluded {
+        edge_rgb = ed_uniform.occluded_edge_color.rgb;
+    }
+#endif
+
+#ifdef CONTOUR
+    edge_rgb = contour_shade(in.uv, edge_rgb);
+#endif
+
+#ifdef EXPOSURE_SCENE_LUMINANCE
+    edge_rgb *= dot(source.rgb, vec3f(0.2126, 0.7152, 0.0722));
+#else ifdef EXPOSURE_CAMERA
+    edge_rgb *= ed_uniform.camera_exposure;
+#endif
+
+    var line_color = to_line_color(edge_rgb);
+
+#ifdef BACKGROUND_OVERRIDE
+    var background = ed_uniform.background_color.rgb;
+#else
+    // The response falls off smoothly around edges, which feathers the desaturation
+    // instead of leaving a hard halo around the lines.
+    let desaturation = ed_uniform.non_edge_desaturation * (1.0 - saturate(response));
+    let luminance = dot(source.rgb, vec3f(0.2126, 0.7152, 0.0722));
+    var background = mix(source.rgb, vec3f(luminance), desaturation);
+
+    if ed_uniform.posterize_levels > 0u {
+        background = posterize(background, ed_uniform.posterize_levels);
+    }
+#endif
+
+#ifdef BLEND_MODE_DUOTONE
+    let duotone_luminance = dot(source.rgb, vec3f(0.2126, 0.7152, 0.0722));
+    // A linear ramp over `duotone_softness` around the threshold, a hard split at 0.
+    let light = saturate(
+        (duotone_luminance - ed_uniform.duotone_threshold) / max(ed_uniform.duotone_softness, 1e-5) + 0.5
+    );
+    background = mix(ed_uniform.duotone_shadow_color.rgb, ed_uniform.duotone_light_color.rgb, light);
+#endif
+
+#ifdef BACKGROUND_TINT
+    // Feathered around the edges like the desaturation.
+    let tint = ed_uniform.background_tint_strength * (1.0 - saturate(response));
+    background = mix(background, ed_uniform.background_tint.rgb, tint);
+#endif
+
+#ifdef HALFTONE
+    // Dots of the background color printed on white paper.
+    let halftone_luminance = dot(source.rgb, vec3f(0.2126, 0.7152, 0.0722));
+    background = mix(vec3f(1.0), background, halftone(in.position.xy, halftone_luminance));
+#endif
+
+#ifdef POOLING
+    // Pigment pooled along the edges darkens the scene by multiplying, under the lines.
+    let pooling_amount = saturate(pooling * ed_uniform.pooling_strength);
+    background *= mix(vec3f(1.0), ed_uniform.pooling_color.rgb, pooling_amount);
+#endif
+
+#ifdef EDGE_SHADOW
+    // The shadow is drawn under the edges.
+    let shadow = shadow_mask * ed_uniform.edge_shadow_color.a;
+    background = mix(background, ed_uniform.edge_shadow_color.rgb, shadow);
+#endif
+
+#ifdef CONTRAST_GUARD
+    // Edges hardly differing in luminance from what is behind them are invisible anyway.
+    let luminance_weights = vec3f(0.2126, 0.7152, 0.0722);
+    let contrast = abs(dot(line_color, luminance_weights) - dot(background, luminance_weights));
+    let visibility = saturate(contrast / ed_uniform.contrast_guard);
+#ifdef CONTRAST_GUARD_FLIP
+    line_color = mix(to_line_color(ed_uniform.contrast_guard_color.rgb), line_color, visibility);
+#else
+    edge *= visibility;
+#endif
+#endif
+
+#ifdef FOG
+    // The background already went through the fog when the scene was rendered.
+    line_color = apply_fog(line_color, fog_distance(in.uv));
+#endif
+
+    var color = mix(background, line_color, edge);
+
+#ifdef HALO
+    // Added over the lines and the scene alike, like light glowing off the edges in their color.
+    color += line_color * halo * ed_uniform.halo_strength;
+#endif
+
+#ifdef OVERLAY
+    // Tile the overlay in screen space, `overlay_scale` screen pixels per overlay texel.
+    let overlay_size = vec2f(textureDimensions(overlay_texture)) * ed_uniform.overlay_scale;
+    let overlay = textureSampleLevel(overlay_texture, noise_sampler, in.position.xy / overlay_size, 0.0).rgb;
+    color = mix(color, color * overlay, ed_uniform.overlay_opacity);
+#endif
+
+#ifdef SCANLINES
+    // A raised cosine over the screen rows, dark at the center of each scanline. Rows are taken
+    // from their index rather than the pixel center so `0.5` alternates lit and dark rows.
+    let row = floor(in.position.y);
+    let scanline = 0.5 + 0.5 * cos(6.2831855 * row * ed_uniform.scanline_frequency);
+    color *= 1.0 - ed_uniform.scanline_strength * (1.0 - scanline);
+#endif
+
+#ifdef PRESERVE_ALPHA
+    return vec4f(color, source.a);
+#else ifdef EDGE_STRENGTH_ALPHA
+    return vec4f(color, edge);
+#else
+    return vec4f(color, 1.0);
+#endif
+}
+
+/// Detects and composites the edges in a single pass.
+@fragment
+fn fragment(
+#ifdef MULTISAMPLED
+    @builtin(sample_index) sample_index: u32,
+#endif
+    in: FullscreenVertexOutput
+) -> FragmentOutput {
+#ifdef MULTISAMPLED
+    sample_index_i = i32(sample_index);
+#endif
+
+    init_texture_size();
+
+    var out: FragmentOutput;
+
+#ifdef SCREEN_RECT
+    if !in_screen_rect(in.uv) {
+        out.color = textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0);
+        return out;
+    }
+#endif
+
+    let normal = prepass_normal_unpack(in.uv);
+    var detection = detect_edges(in);
+
+#ifdef TEMPORAL
+    detection.edge = stabilize_edge(in.uv, detection.edge);
+#endif
+
+    var shadow = 0.0;
+#ifdef EDGE_SHADOW
+    shadow = detect_edge_shadow(in);
+#endif
+
+    out.color = composite(in, normal, detection.response, detection.edge, shadow, 0.0, 0.0, detection.occluded, detection.marker_color);
+
+#ifdef TEMPORAL
+    out.edge_mask = detection.edge;
+#endif
+
+#ifdef GRADIENT_OUTPUT
+    out.gradient = gradient_output(detection);
+#endif
+
+    return out;
+}
+
+/// Writes the edge mask, the first pass when edges are dilated or pool.
+@fragment
+fn mask(
+#ifdef MULTISAMPLED
+    @builtin(sample_index) sample_index: u32,
+#endif
+    in: FullscreenVertexOutput
+) -> MaskOutput {
+#ifdef MULTISAMPLED
+    sample_index_i = i32(sample_index);
+#endif
+
+    init_texture_size();
+
+    var out: MaskOutput;
+
+#ifdef SCREEN_RECT
+    // No edges outside of the rect, so none are dilated into it either.
+    if !in_screen_rect(in.uv) {
+        return out;
+    }
+#endif
+
+    let detection = detect_edges(in);
+    out.edge_mask = detection.edge;
+
+#ifdef TEMPORAL
+    out.edge_mask = stabilize_edge(in.uv, out.edge_mask);
+#endif
+
+#ifdef GRADIENT_OUTPUT
+    out.gradient = gradient_output(detection);
+#endif
+
+    return out;
+}
+
+/// Dilates the edge mask and composites it, the second pass when edges are dilated or pool.
+@fragment
+fn dilate(
+#ifdef MULTISAMPLED
+    @builtin(sample_index) sample_index: u32,
+#endif
+    in: FullscreenVertexOutput
+) -> DilateOutput {
+#ifdef MULTISAMPLED
+    sample_index_i = i32(sample_index);
+#endif
+
+    init_texture_size();
+
+    var out: DilateOutput;
+
+#ifdef DISTANCE_FIELD
+    // The mask is empty outside of the screen rect, so the distance is measured to the edges in it.
+    out.distance = edge_distance(in.position.xy);
+#endif
+
+#ifdef SCREEN_RECT
+    if !in_screen_rect(in.uv) {
+        out.color = textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0);
+        return out;
+    }
+#endif
+
+    let normal = prepass_normal_unpack(in.uv);
+    var edge = dilate_edge_mask(in.position.xy);
+
+    var shadow = 0.0;
+#ifdef EDGE_SHADOW
+    // The shadow reuses the dilated mask instead of detecting the edges a second time.
+    shadow = dilate_edge_mask(in.position.xy - ed_uniform.edge_shadow_offset);
+#endif
+
+    var pooling = 0.0;
+#ifdef POOLING
+    pooling = pooled_edge_mask(in.position.xy);
+#endif
+
+    var halo = 0.0;
+#ifdef HALO
+    halo = halo_edge_mask(in.position.xy);
+#endif
+
+#ifdef VIEWMODEL_CUTOFF
+    // Edges next to the viewmodel aren't grown, pooled or glowing onto it either.
+    if is_viewmodel(in.uv) {
+        edge = 0.0;
+        shadow = 0.0;
+        pooling = 0.0;
+        halo = 0.0;
+    }
+#endif
+
+    // The mask doesn't tell hidden silhouettes and mesh colors apart, so they are looked up again,
+    // over the grown width of the lines.
+    let thickness = max(ed_uniform.depth_thickness, max(ed_uniform.normal_thickness, ed_uniform.color_thickness));
+    var grown_thickness = thickness + f32(ed_uniform.dilate_radius);
+#ifdef HALO
+    // The halo takes the color of the lines it glows off, out to its radius.
+    grown_thickness = max(grown_thickness, thickness + f32(ed_uniform.halo_radius));
+#endif
+
+    var occluded = false;
+#ifdef XRAY_OUTLINES
+    occluded = is_occluded_edge(in.uv, grown_thickness);
+#endif
+
+    var marker_color = vec4f(0.0);
+#ifdef MESH_PARAMS
+    marker_color = marker_coverage(in.uv, grown_thickness).color;
+#endif
+
+    // The dilated mask stands in for the response, so desaturation fades out along the grown lines.
+    out.color = composite(in, normal, edge, edge, shadow, pooling, halo, occluded, marker_color);
+    return out;
+}
+
+#ifdef COMPUTE
+/// Detects and composites the edges of one pixel into the storage texture, the compute
+/// alternative of `fragment`. Textures are sampled with an explicit level throughout since
+/// compute shaders have no derivatives.
+@compute @workgroup_size(8, 8, 1)
+fn compute(@builtin(global_invocation_id) global_id: vec3u) {
+    init_texture_size();
+
+    // The dispatch is rounded up to whole workgroups.
+    if any(vec2f(global_id.xy) >= texture_size) {
+        return;
+    }
+
+    var in: FullscreenVertexOutput;
+    in.position = vec4f(vec2f(global_id.xy) + 0.5, 0.0, 1.0);
+    in.uv = in.position.xy / texture_size;
+
+#ifdef SCREEN_RECT
+    if !in_screen_rect(in.uv) {
+        textureStore(output_texture, global_id.xy, textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0));
+        return;
+    }
+#endif
+
+    let normal = prepass_normal_unpack(in.uv);
+    let detection = detect_edges(in);
+
+    var shadow = 0.0;
+#ifdef EDGE_SHADOW
+    shadow = detect_edge_shadow(in);
+#endif
+
+    let color = composite(in, normal, detection.response, detection.edge, shadow, 0.0, 0.0, detection.occluded, detection.marker_color);
+    textureStore(output_texture, global_id.xy, color);
+}
+#endif
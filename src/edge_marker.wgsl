@@ -0,0 +1,58 @@
+// Writes the marker of the closest rendered mesh, see `EdgeDetectionMarkerTextures`, and its
+// object ID with `OBJECT_ID` and its color and thresholds with `MESH_PARAMS`. The depth is written
+// by the depth attachment.
+
+#import bevy_pbr::forward_io::VertexOutput
+
+#ifdef MESH_UNIFORM
+// See `EdgeMarkerMeshUniform`.
+struct MeshParams {
+    color: vec4f,
+    thresholds: vec4f,
+    object_id: u32,
+}
+
+@group(2) @binding(0) var<uniform> mesh_params: MeshParams;
+#endif
+
+struct MarkerOutput {
+    @location(0) marker: vec4f,
+#ifdef OBJECT_ID
+    @location(1) object_id: u32,
+#ifdef MESH_PARAMS
+    @location(2) color: vec4f,
+    @location(3) thresholds: vec4f,
+#endif
+#else ifdef MESH_PARAMS
+    @location(1) color: vec4f,
+    @location(2) thresholds: vec4f,
+#endif
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> MarkerOutput {
+    var out: MarkerOutput;
+
+    // See `EdgeMarker`.
+#ifdef MARKER_TARGET
+    out.marker = vec4f(1.0);
+#else ifdef MARKER_EXCLUDED
+    out.marker = vec4f(0.5);
+#else ifdef MARKER_TRANSPARENT
+    out.marker = vec4f(0.25);
+#else
+    out.marker = vec4f(0.0);
+#endif
+
+#ifdef OBJECT_ID
+    // See `EdgeDetectionObjectIds`, 0 is left for the background.
+    out.object_id = mesh_params.object_id;
+#endif
+
+#ifdef MESH_PARAMS
+    out.color = mesh_params.color;
+    out.thresholds = mesh_params.thresholds;
+#endif
+
+    return out;
+}
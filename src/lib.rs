@@ -1,53 +1,131 @@
 use bevy::{
     asset::{embedded_asset, load_internal_asset},
+    core::FrameCount,
     core_pipeline::{
         core_3d::{
             graph::{Core3d, Node3d},
             DEPTH_TEXTURE_SAMPLING_SUPPORTED,
         },
         fullscreen_vertex_shader::fullscreen_shader_vertex_state,
-        prepass::{DepthPrepass, NormalPrepass, ViewPrepassTextures},
+        oit::OrderIndependentTransparencySettings,
+        prepass::{
+            DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass,
+            OpaqueNoLightmap3dBinKey, ViewPrepassTextures,
+        },
+    },
+    ecs::{
+        entity::EntityHashSet,
+        query::{QueryItem, ROQueryItem},
+        system::{lifetimeless::SRes, SystemParamItem},
+    },
+    pbr::{
+        DrawMesh, Lightmap, MeshPipeline, MeshPipelineKey, RenderMeshInstanceFlags,
+        RenderMeshInstances, SetMeshBindGroup, SetMeshViewBindGroup,
     },
-    ecs::query::QueryItem,
     prelude::*,
     render::{
+        camera::{CameraMainTextureUsages, Exposure, ExtractedCamera},
+        diagnostic::RecordDiagnostics,
         extract_component::{
-            ComponentUniforms, DynamicUniformIndex, ExtractComponent, UniformComponentPlugin,
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
         },
+        mesh::{MeshVertexBufferLayoutRef, RenderMesh},
         render_asset::RenderAssets,
         render_graph::{
             NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
         },
+        render_phase::{
+            AddRenderCommand, BinnedPhaseItem, BinnedRenderPhasePlugin, BinnedRenderPhaseType,
+            CachedRenderPipelinePhaseItem, DrawFunctionId, DrawFunctions, PhaseItem,
+            PhaseItemExtraIndex, RenderCommand, RenderCommandResult, SetItemPipeline,
+            TrackedRenderPass, ViewBinnedRenderPhases,
+        },
         render_resource::{
             binding_types::{texture_2d, uniform_buffer},
             *,
         },
-        renderer::{RenderContext, RenderDevice},
+        renderer::{RenderContext, RenderDevice, RenderQueue},
         sync_component::SyncComponentPlugin,
-        sync_world::RenderEntity,
-        texture::GpuImage,
-        view::{ExtractedView, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms},
+        sync_world::{MainEntity, MainEntityHashMap, MainEntityHashSet, RenderEntity},
+        texture::{CachedTexture, FallbackImage, GpuImage, TextureCache},
+        view::{
+            check_visibility, ExtractedView, RenderLayers, RenderVisibleEntities, ViewTarget,
+            ViewUniform, ViewUniformOffset, ViewUniforms, VisibilitySystems,
+        },
         Extract, Render, RenderApp, RenderSet,
     },
 };
+use core::ops::Range;
+
 use binding_types::{
     sampler, texture_2d_multisampled, texture_depth_2d, texture_depth_2d_multisampled,
+    texture_storage_2d,
 };
 
+#[cfg(feature = "picking")]
+pub mod picking;
+
+/// The names the passes of the effect are recorded under by Bevy's render diagnostics: the
+/// single pass or the compositing pass, the mask pass, the compute pass, the pass rendering
+/// the entities with [`NoEdgeDetection`] or [`EdgeDetectionTarget`] and the pass rendering the
+/// targets alone for [`EdgeDetection::occluded_edge_color`].
+pub const EDGE_DETECTION_DIAGNOSTIC_PASSES: [&str; 5] = [
+    "edge_detection_pass",
+    "edge_detection_mask_pass",
+    "edge_detection_compute_pass",
+    "edge_detection_marker_pass",
+    "edge_detection_xray_pass",
+];
+
 pub const EDGE_DETECTION_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(98765432109876543210987654321098765);
 
+pub const EDGE_MARKER_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(98765432109876543210987654321098766);
+
 /// An edge detection post-processing plugin based on the sobel filter.
+///
+/// Where the effect runs in the 3D render graph is chosen by `placement`. Content rendered by
+/// nodes after it, e.g. editor overlays, isn't edged.
+///
+/// With Bevy's `RenderDiagnosticsPlugin`, the CPU and GPU time of the passes are recorded as
+/// `render/edge_detection_pass/elapsed_gpu` and alike, see [`EDGE_DETECTION_DIAGNOSTIC_PASSES`].
+/// GPU times need a device supporting timestamp queries.
+#[derive(Default)]
 pub struct EdgeDetectionPlugin {
-    pub before: Node3d,
+    /// Where the effect runs in the render graph.
+    pub placement: EdgePlacement,
 }
 
-impl Default for EdgeDetectionPlugin {
-    fn default() -> Self {
-        Self {
-            before: Node3d::Fxaa,
-        }
-    }
+/// Where the effect runs in the 3D render graph, see [`EdgeDetectionPlugin::placement`].
+///
+/// With Bevy's [`Bloom`](bevy::core_pipeline::bloom::Bloom), use [`EdgePlacement::BeforeBloom`]
+/// for glowing edges and any of the later placements to keep them out of the bloom, see the
+/// `bloom` example.
+#[derive(Clone, Debug, Default)]
+pub enum EdgePlacement {
+    /// Before bloom and after motion blur, so edges brighter than 1.0 on HDR cameras (see
+    /// [`EdgeDetection::edge_emissive_strength`]) bloom like the emissive surfaces of the scene.
+    /// Depth of field runs later, so edges are blurred with the out of focus areas.
+    ///
+    /// The edges aren't ordered against temporal anti-aliasing, which runs between motion blur
+    /// and bloom. With `TemporalAntiAliasPlugin`, use
+    /// `Custom { after: Node3d::Taa, before: Node3d::Bloom }` instead.
+    BeforeBloom,
+    /// Before tonemapping and after Bevy's other post processing, e.g. bloom and depth of
+    /// field. HDR cameras detect color edges on HDR colors, and edges brighter than 1.0 (see
+    /// [`EdgeDetection::edge_emissive_strength`]) are tonemapped with the scene. Bloom runs
+    /// earlier, so it doesn't pick up the edges.
+    BeforeTonemapping,
+    /// After tonemapping and before FXAA and SMAA. Edges are drawn in the final display colors
+    /// and anti-aliased with the scene when anti-aliasing is enabled.
+    #[default]
+    AfterTonemappingBeforeAA,
+    /// After FXAA and SMAA, so edges stay crisp and aliased, e.g. for pixel art.
+    AfterAA,
+    /// Between two arbitrary nodes of the graph.
+    Custom { after: Node3d, before: Node3d },
 }
 
 impl Plugin for EdgeDetectionPlugin {
@@ -59,12 +137,53 @@ impl Plugin for EdgeDetectionPlugin {
             Shader::from_wgsl
         );
 
+        load_internal_asset!(
+            app,
+            EDGE_MARKER_SHADER_HANDLE,
+            "edge_marker.wgsl",
+            Shader::from_wgsl
+        );
+
         embedded_asset!(app, "perlin_noise.png");
 
-        app.register_type::<EdgeDetection>();
+        app.register_type::<EdgeDetection>()
+            .register_type::<EdgeDetectionPreset>()
+            .register_type::<HatchingSettings>()
+            .register_type::<HalftoneSettings>()
+            .register_type::<StippleSettings>()
+            .register_type::<EdgeDetectionStatus>()
+            .register_type::<EdgeDetectionCompute>()
+            .register_type::<EdgeDetectionPulse>()
+            .register_type::<NoEdgeDetection>()
+            .register_type::<EdgeDetectionTarget>()
+            .register_type::<EdgeDetectionColor>()
+            .register_type::<EdgeDetectionOverride>()
+            .register_type::<EdgeDetectionLayers>();
 
         app.add_plugins(SyncComponentPlugin::<EdgeDetection>::default())
-            .add_plugins(UniformComponentPlugin::<EdgeDetectionUniform>::default());
+            .add_plugins(UniformComponentPlugin::<EdgeDetectionUniform>::default())
+            .add_plugins(ExtractComponentPlugin::<EdgeDetectionCompute>::default())
+            .add_plugins(ExtractComponentPlugin::<EdgeDetectionLayers>::default())
+            .add_plugins(ExtractComponentPlugin::<EdgeDetectionMarkerPass>::default())
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        insert_edge_detection_prepasses,
+                        update_edge_detection_status,
+                    )
+                        .chain(),
+                    insert_edge_detection_storage_usages,
+                    animate_edge_detection_pulses,
+                    (
+                        check_visibility::<WithNoEdgeDetection>,
+                        check_visibility::<WithEdgeDetectionTarget>,
+                    )
+                        .in_set(VisibilitySystems::CheckVisibility),
+                ),
+            )
+            .add_plugins(BinnedRenderPhasePlugin::<EdgeMarker3d, MeshPipeline>::default())
+            .add_plugins(BinnedRenderPhasePlugin::<EdgeXray3d, MeshPipeline>::default());
 
         // We need to get the render app from the main app
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
@@ -73,28 +192,85 @@ impl Plugin for EdgeDetectionPlugin {
 
         render_app
             .init_resource::<SpecializedRenderPipelines<EdgeDetectionPipeline>>()
+            .init_resource::<SpecializedComputePipelines<EdgeDetectionPipeline>>()
+            .init_resource::<SpecializedMeshPipelines<EdgeMarkerPipeline>>()
+            .init_resource::<DrawFunctions<EdgeMarker3d>>()
+            .init_resource::<DrawFunctions<EdgeXray3d>>()
+            .init_resource::<EdgeDetectionLightmappedMeshes>()
+            .init_resource::<EdgeDetectionMeshLayers>()
+            .init_resource::<EdgeDetectionTransparentMeshes>()
+            .init_resource::<EdgeDetectionMeshParams>()
+            .init_resource::<EdgeDetectionObjectIds>()
+            .add_render_command::<EdgeMarker3d, DrawEdgeMarker>()
+            .add_render_command::<EdgeXray3d, DrawEdgeMarker>()
             .add_systems(
                 ExtractSchedule,
-                EdgeDetectionUniform::extract_edge_detection_settings,
+                (
+                    EdgeDetectionUniform::extract_edge_detection_settings,
+                    extract_edge_marker_phases,
+                    extract_edge_detection_lightmapped_meshes,
+                    extract_edge_detection_mesh_layers,
+                    extract_edge_detection_transparent_meshes,
+                    extract_edge_detection_mesh_params,
+                    extract_edge_detection_object_ids,
+                ),
             )
             .add_systems(
                 Render,
-                prepare_edge_detection_pipelines.in_set(RenderSet::Prepare),
+                (
+                    queue_edge_marker_meshes.in_set(RenderSet::QueueMeshes),
+                    prepare_edge_detection_pipelines.in_set(RenderSet::Prepare),
+                    (
+                        prepare_edge_detection_history_textures,
+                        prepare_edge_detection_mask_textures,
+                        prepare_edge_detection_gradient_textures,
+                        prepare_edge_detection_distance_textures,
+                        prepare_edge_detection_marker_textures,
+                        prepare_edge_marker_mesh_uniforms,
+                    )
+                        .in_set(RenderSet::PrepareResources),
+                ),
             )
             .add_render_graph_node::<ViewNodeRunner<EdgeDetectionNode>>(Core3d, EdgeDetectionLabel)
+            .add_render_graph_node::<ViewNodeRunner<EdgeDetectionComputeNode>>(
+                Core3d,
+                EdgeDetectionComputeLabel,
+            )
+            .add_render_graph_node::<ViewNodeRunner<EdgeMarkerNode>>(Core3d, EdgeMarkerLabel)
+            .add_render_graph_edges(Core3d, (EdgeDetectionLabel, EdgeDetectionComputeLabel))
             .add_render_graph_edges(
                 Core3d,
-                (
-                    Node3d::PostProcessing,
-                    EdgeDetectionLabel,
-                    self.before.clone(),
-                ),
+                (Node3d::EndPrepasses, EdgeMarkerLabel, EdgeDetectionLabel),
             );
+
+        let (after, before): (&[Node3d], &[Node3d]) = match &self.placement {
+            EdgePlacement::BeforeBloom => (&[Node3d::MotionBlur], &[Node3d::Bloom]),
+            EdgePlacement::BeforeTonemapping => (&[Node3d::PostProcessing], &[Node3d::Tonemapping]),
+            EdgePlacement::AfterTonemappingBeforeAA => {
+                (&[Node3d::Tonemapping], &[Node3d::Fxaa, Node3d::Smaa])
+            }
+            EdgePlacement::AfterAA => (
+                &[Node3d::Fxaa, Node3d::Smaa],
+                &[Node3d::EndMainPassPostProcessing],
+            ),
+            EdgePlacement::Custom { after, before } => {
+                (core::slice::from_ref(after), core::slice::from_ref(before))
+            }
+        };
+
+        for node in after {
+            render_app.add_render_graph_edge(Core3d, node.clone(), EdgeDetectionLabel);
+        }
+        for node in before {
+            render_app.add_render_graph_edge(Core3d, EdgeDetectionComputeLabel, node.clone());
+        }
     }
 
     fn finish(&self, app: &mut App) {
         app.sub_app_mut(RenderApp)
-            .init_resource::<EdgeDetectionPipeline>();
+            .init_resource::<EdgeDetectionPipeline>()
+            .init_resource::<EdgeMarkerPipeline>()
+            .init_resource::<EdgeMarkerMeshUniforms>();
     }
 }
 
@@ -103,9 +279,25 @@ impl Plugin for EdgeDetectionPlugin {
 pub struct EdgeDetectionPipeline {
     pub noise_texture: Handle<Image>,
     pub linear_sampler: Sampler,
+    pub nearest_sampler: Sampler,
     pub noise_sampler: Sampler,
+    /// 1x1 zeroed textures bound in place of prepass textures the view doesn't have, see
+    /// [`EdgeDetectionPipeline::fallback_texture`].
+    pub fallback_texture: TextureView,
+    pub multisampled_fallback_texture: TextureView,
+    pub fallback_depth_texture: TextureView,
+    pub multisampled_fallback_depth_texture: TextureView,
+    pub fallback_uint_texture: TextureView,
     pub layout_with_msaa: BindGroupLayout,
     pub layout_without_msaa: BindGroupLayout,
+    pub compute_layout_with_msaa: BindGroupLayout,
+    pub compute_layout_without_msaa: BindGroupLayout,
+    /// The second bind group of pipelines reading the textures of the marker pass or the
+    /// curvature texture, see [`EdgeDetectionKey::uses_marker_bind_group`]. Other pipelines leave
+    /// it out, which keeps them within the 16 sampled textures per shader stage WebGPU and WebGL2
+    /// allow by default. Pipelines with both bind groups need the higher limits of the native
+    /// backends.
+    pub marker_layout: BindGroupLayout,
 }
 
 impl EdgeDetectionPipeline {
@@ -116,6 +308,164 @@ impl EdgeDetectionPipeline {
             &self.layout_without_msaa
         }
     }
+
+    /// The layout of the compute pipeline, see [`EdgeDetectionComputeNode`].
+    pub fn compute_bind_group_layout(&self, multisampled: bool) -> &BindGroupLayout {
+        if multisampled {
+            &self.compute_layout_with_msaa
+        } else {
+            &self.compute_layout_without_msaa
+        }
+    }
+
+    /// The bind group layouts of a pipeline specialized with `key`.
+    pub fn pipeline_layout(&self, key: EdgeDetectionKey, compute: bool) -> Vec<BindGroupLayout> {
+        let layout = if compute {
+            self.compute_bind_group_layout(key.multisampled)
+        } else {
+            self.bind_group_layout(key.multisampled)
+        };
+
+        let mut layouts = vec![layout.clone()];
+        if key.uses_marker_bind_group() {
+            layouts.push(self.marker_layout.clone());
+        }
+        layouts
+    }
+
+    /// The sampler of the scene color for the given filter.
+    pub fn sampler(&self, filter: SamplerFilter) -> &Sampler {
+        match filter {
+            SamplerFilter::Linear => &self.linear_sampler,
+            SamplerFilter::Nearest => &self.nearest_sampler,
+        }
+    }
+
+    /// A zeroed float texture to bind in place of a missing normal or motion vector prepass.
+    pub fn fallback_texture(&self, multisampled: bool) -> &TextureView {
+        if multisampled {
+            &self.multisampled_fallback_texture
+        } else {
+            &self.fallback_texture
+        }
+    }
+
+    /// A zeroed depth texture to bind in place of a missing depth prepass.
+    pub fn fallback_depth_texture(&self, multisampled: bool) -> &TextureView {
+        if multisampled {
+            &self.multisampled_fallback_depth_texture
+        } else {
+            &self.fallback_depth_texture
+        }
+    }
+}
+
+/// The entries shared by the render and compute layouts.
+fn bind_group_layout_entries(
+    visibility: ShaderStages,
+    multisampled: bool,
+) -> DynamicBindGroupLayoutEntries {
+    let (depth_prepass, normal_prepass, motion_vector_prepass) = if multisampled {
+        (
+            texture_depth_2d_multisampled(),
+            texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
+            texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
+        )
+    } else {
+        (
+            texture_depth_2d(),
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            texture_2d(TextureSampleType::Float { filterable: true }),
+        )
+    };
+
+    DynamicBindGroupLayoutEntries::sequential(
+        visibility,
+        (
+            // color attachment
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // depth prepass
+            depth_prepass,
+            // normal prepass
+            normal_prepass,
+            // texture sampler
+            sampler(SamplerBindingType::Filtering),
+            // perlin-noise texture
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // perlin-noise sampler
+            sampler(SamplerBindingType::Filtering),
+            // view
+            uniform_buffer::<ViewUniform>(true),
+            // The uniform that will control the effect
+            uniform_buffer::<EdgeDetectionUniform>(true),
+            // secondary depth
+            texture_2d(TextureSampleType::Float { filterable: false }),
+            // overlay texture
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // motion vector prepass
+            motion_vector_prepass,
+            // edge history
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // edge mask
+            texture_2d(TextureSampleType::Float { filterable: false }),
+            // threshold map
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // strength mask
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // object IDs
+            texture_2d(TextureSampleType::Uint),
+            // deferred G-buffer
+            texture_2d(TextureSampleType::Uint),
+            // deferred lighting pass IDs
+            texture_2d(TextureSampleType::Uint),
+        ),
+    )
+}
+
+/// The entries of the second bind group, see [`EdgeDetectionPipeline::marker_layout`].
+fn marker_bind_group_layout_entries() -> BindGroupLayoutEntries<6> {
+    BindGroupLayoutEntries::sequential(
+        ShaderStages::FRAGMENT | ShaderStages::COMPUTE,
+        (
+            // depth of the marked entities
+            texture_depth_2d(),
+            // markers of the marked entities
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // depth of the targets alone
+            texture_depth_2d(),
+            // curvature
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // colors of the marked entities
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            // thresholds of the marked entities
+            texture_2d(TextureSampleType::Float { filterable: true }),
+        ),
+    )
+}
+
+fn create_fallback_texture(
+    render_device: &RenderDevice,
+    label: &'static str,
+    format: TextureFormat,
+    sample_count: u32,
+) -> TextureView {
+    render_device
+        .create_texture(&TextureDescriptor {
+            label: Some(label),
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format,
+            // Multisampled textures must be renderable.
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&TextureViewDescriptor::default())
 }
 
 impl FromWorld for EdgeDetectionPipeline {
@@ -126,54 +476,40 @@ impl FromWorld for EdgeDetectionPipeline {
 
         let layout_with_msaa = render_device.create_bind_group_layout(
             "edge_detection: bind_group_layout with msaa",
-            &BindGroupLayoutEntries::sequential(
-                // The layout entries will only be visible in the fragment stage
-                ShaderStages::FRAGMENT,
-                (
-                    // color attachment
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    // depth prepass
-                    texture_depth_2d_multisampled(),
-                    // normal prepass
-                    texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
-                    // texture sampler
-                    sampler(SamplerBindingType::Filtering),
-                    // perlin-noise texture
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    // perlin-noise sampler
-                    sampler(SamplerBindingType::Filtering),
-                    // view
-                    uniform_buffer::<ViewUniform>(true),
-                    // The uniform that will control the effect
-                    uniform_buffer::<EdgeDetectionUniform>(true),
-                ),
-            ),
+            // The layout entries will only be visible in the fragment stage
+            &bind_group_layout_entries(ShaderStages::FRAGMENT, true),
         );
 
         let layout_without_msaa = render_device.create_bind_group_layout(
             "edge_detection: bind_group_layout without msaa",
-            &BindGroupLayoutEntries::sequential(
-                // The layout entries will only be visible in the fragment stage
-                ShaderStages::FRAGMENT,
-                (
-                    // color attachment
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    // depth prepass
-                    texture_depth_2d(),
-                    // normal prepass
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    // texture sampler
-                    sampler(SamplerBindingType::Filtering),
-                    // perlin-noise texture
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    // perlin-noise sampler
-                    sampler(SamplerBindingType::Filtering),
-                    // view
-                    uniform_buffer::<ViewUniform>(true),
-                    // The uniform that will control the effect
-                    uniform_buffer::<EdgeDetectionUniform>(true),
+            &bind_group_layout_entries(ShaderStages::FRAGMENT, false),
+        );
+
+        // The compute pass writes into the view target as a storage texture instead of a color
+        // attachment.
+        let compute_layout_with_msaa = render_device.create_bind_group_layout(
+            "edge_detection: compute bind_group_layout with msaa",
+            &bind_group_layout_entries(ShaderStages::COMPUTE, true).extend_sequential((
+                texture_storage_2d(
+                    ViewTarget::TEXTURE_FORMAT_HDR,
+                    StorageTextureAccess::WriteOnly,
                 ),
-            ),
+            )),
+        );
+
+        let compute_layout_without_msaa = render_device.create_bind_group_layout(
+            "edge_detection: compute bind_group_layout without msaa",
+            &bind_group_layout_entries(ShaderStages::COMPUTE, false).extend_sequential((
+                texture_storage_2d(
+                    ViewTarget::TEXTURE_FORMAT_HDR,
+                    StorageTextureAccess::WriteOnly,
+                ),
+            )),
+        );
+
+        let marker_layout = render_device.create_bind_group_layout(
+            "edge_detection: marker bind_group_layout",
+            &marker_bind_group_layout_entries(),
         );
 
         let linear_sampler = render_device.create_sampler(&SamplerDescriptor {
@@ -183,6 +519,13 @@ impl FromWorld for EdgeDetectionPipeline {
             ..default()
         });
 
+        let nearest_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("edge detection nearest sampler"),
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            ..default()
+        });
+
         let noise_sampler = render_device.create_sampler(&SamplerDescriptor {
             label: Some("edge detection noise sampler"),
             mag_filter: FilterMode::Linear,
@@ -192,12 +535,55 @@ impl FromWorld for EdgeDetectionPipeline {
             ..default()
         });
 
+        // The sample count of a bound texture doesn't have to match the view's, so a single
+        // multisampled fallback works for every `Msaa` setting.
+        let fallback_texture = create_fallback_texture(
+            render_device,
+            "edge detection fallback texture",
+            TextureFormat::Rgba16Float,
+            1,
+        );
+        let multisampled_fallback_texture = create_fallback_texture(
+            render_device,
+            "edge detection multisampled fallback texture",
+            TextureFormat::Rgba16Float,
+            4,
+        );
+        let fallback_depth_texture = create_fallback_texture(
+            render_device,
+            "edge detection fallback depth texture",
+            TextureFormat::Depth32Float,
+            1,
+        );
+        let multisampled_fallback_depth_texture = create_fallback_texture(
+            render_device,
+            "edge detection multisampled fallback depth texture",
+            TextureFormat::Depth32Float,
+            4,
+        );
+
+        let fallback_uint_texture = create_fallback_texture(
+            render_device,
+            "edge detection fallback uint texture",
+            EDGE_OBJECT_ID_TEXTURE_FORMAT,
+            1,
+        );
+
         Self {
             noise_texture,
             linear_sampler,
+            nearest_sampler,
             noise_sampler,
+            fallback_texture,
+            multisampled_fallback_texture,
+            fallback_depth_texture,
+            multisampled_fallback_depth_texture,
+            fallback_uint_texture,
             layout_with_msaa,
             layout_without_msaa,
+            compute_layout_with_msaa,
+            compute_layout_without_msaa,
+            marker_layout,
         }
     }
 }
@@ -206,48 +592,66 @@ impl SpecializedRenderPipeline for EdgeDetectionPipeline {
     type Key = EdgeDetectionKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
-        let targets = vec![Some(ColorTargetState {
-            format: if key.hdr {
-                ViewTarget::TEXTURE_FORMAT_HDR
+        // The destination is a fresh texture obtained from `post_process_write()`, so every
+        // channel must be written. The content of the alpha channel (see `OutputAlpha`) is
+        // chosen in the shader, e.g. by copying the source alpha through, rather than masking
+        // out the alpha channel here.
+        let mut targets = vec![Some(ColorTargetState {
+            format: if key.mask {
+                EDGE_MASK_TEXTURE_FORMAT
             } else {
-                TextureFormat::bevy_default()
+                key.view_target_format()
             },
             blend: None,
             write_mask: ColorWrites::ALL,
         })];
 
-        let mut shader_defs = vec![];
-
-        if key.enable_depth {
-            shader_defs.push("ENABLE_DEPTH".into());
-        }
-
-        if key.enable_normal {
-            shader_defs.push("ENABLE_NORMAL".into());
+        // The stabilized edge mask is written into the history texture for the next frame.
+        // When dilating, the mask pass writes it there directly instead.
+        if key.temporal && !key.dilate {
+            targets.push(Some(ColorTargetState {
+                format: EDGE_MASK_TEXTURE_FORMAT,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            }));
         }
 
-        if key.enable_color {
-            shader_defs.push("ENABLE_COLOR".into());
+        // The gradient is written by the pass detecting the edges, always into the third target.
+        if key.gradient_output && (key.mask || !key.dilate) {
+            targets.resize(2, None);
+            targets.push(Some(ColorTargetState {
+                format: EDGE_GRADIENT_TEXTURE_FORMAT,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            }));
         }
 
-        if key.multisampled {
-            shader_defs.push("MULTISAMPLED".into());
+        // The distance field is written by the second pass, into the fourth target.
+        if key.distance_field && key.dilate && !key.mask {
+            targets.resize(3, None);
+            targets.push(Some(ColorTargetState {
+                format: EDGE_DISTANCE_TEXTURE_FORMAT,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            }));
         }
 
-        match key.projection {
-            ProjectionType::Perspective => shader_defs.push("VIEW_PROJECTION_PERSPECTIVE".into()),
-            ProjectionType::Orthographic => shader_defs.push("VIEW_PROJECTION_ORTHOGRAPHIC".into()),
-            _ => (),
-        };
+        let shader_defs = key.shader_defs();
 
         RenderPipelineDescriptor {
             label: Some("edge_detection: pipeline".into()),
-            layout: vec![self.bind_group_layout(key.multisampled).clone()],
+            layout: self.pipeline_layout(key, false),
             vertex: fullscreen_shader_vertex_state(),
             fragment: Some(FragmentState {
                 shader: EDGE_DETECTION_SHADER_HANDLE,
                 shader_defs,
-                entry_point: "fragment".into(),
+                entry_point: if key.mask {
+                    "mask".into()
+                } else if key.dilate {
+                    "dilate".into()
+                } else {
+                    "fragment".into()
+                },
                 targets,
             }),
             primitive: default(),
@@ -259,123 +663,2631 @@ impl SpecializedRenderPipeline for EdgeDetectionPipeline {
     }
 }
 
+impl SpecializedComputePipeline for EdgeDetectionPipeline {
+    type Key = EdgeDetectionKey;
+
+    fn specialize(&self, key: Self::Key) -> ComputePipelineDescriptor {
+        let mut shader_defs = key.shader_defs();
+        shader_defs.push("COMPUTE".into());
+
+        ComputePipelineDescriptor {
+            label: Some("edge_detection: compute pipeline".into()),
+            layout: self.pipeline_layout(key, true),
+            push_constant_ranges: vec![],
+            shader: EDGE_DETECTION_SHADER_HANDLE,
+            shader_defs,
+            entry_point: "compute".into(),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// The pipeline of the edge detection pass of a view, the view target format it was
+/// specialized for and whether it has the second bind group, see
+/// [`EdgeDetectionKey::uses_marker_bind_group`].
+#[derive(Component, Clone, Copy)]
+pub struct EdgeDetectionPipelineId(CachedRenderPipelineId, TextureFormat, bool);
+
+/// The pipeline of the mask pass of views with [`EdgeDetection::dilate_radius`].
 #[derive(Component, Clone, Copy)]
-pub struct EdgeDetectionPipelineId(CachedRenderPipelineId);
+pub struct EdgeDetectionMaskPipelineId(CachedRenderPipelineId);
 
+/// The pipeline of views with [`EdgeDetectionCompute`], see [`EdgeDetectionComputeNode`], and
+/// whether it has the second bind group.
+#[derive(Component, Clone, Copy)]
+pub struct EdgeDetectionComputePipelineId(CachedComputePipelineId, bool);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn prepare_edge_detection_pipelines(
     mut commands: Commands,
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<EdgeDetectionPipeline>>,
+    mut compute_pipelines: ResMut<SpecializedComputePipelines<EdgeDetectionPipeline>>,
     edge_detection_pipeline: Res<EdgeDetectionPipeline>,
     view_targets: Query<(
         Entity,
         &ExtractedView,
         &EdgeDetection,
-        &Msaa,
+        Option<&Msaa>,
         Option<&Projection>,
+        Option<&ExtractedCamera>,
+        Has<EdgeDetectionCompute>,
+        Has<DistanceFog>,
+        Has<DepthPrepass>,
+        Has<DeferredPrepass>,
+        Has<EdgeDetectionMarkerPass>,
     )>,
 ) {
-    for (entity, view, edge_detection, msaa, projection) in view_targets.iter() {
-        let (hdr, multisampled) = (view.hdr, *msaa != Msaa::Off);
+    for (
+        entity,
+        view,
+        edge_detection,
+        msaa,
+        projection,
+        camera,
+        compute,
+        fog,
+        depth_prepass,
+        deferred,
+        marker_pass,
+    ) in view_targets.iter()
+    {
+        // A view without `Msaa` is treated as not multisampled instead of being skipped.
+        let msaa = msaa.copied().unwrap_or(Msaa::Off);
+        let (hdr, multisampled) = (view.hdr, msaa != Msaa::Off);
+
+        // Fog is evaluated at the depth of the edges, so it's skipped until the prepass exists.
+        let fog = fog && depth_prepass;
+        // Only views with a depth prepass and `EdgeDetectionMarkerPass` queue the entities, see
+        // `queue_edge_marker_meshes`. Neither depends on the visible meshes, so they can't switch
+        // the pipeline every frame.
+        let marked_entities = depth_prepass && marker_pass;
+        let key = EdgeDetectionKey::new(
+            edge_detection,
+            hdr,
+            multisampled,
+            projection,
+            fog,
+            marked_entities,
+            camera.is_some_and(|camera| camera.viewport.is_some()),
+            deferred,
+        );
+
+        if uses_edge_detection_compute(compute, hdr) {
+            // Only one of the nodes finds a pipeline id on the view, so only one of them runs.
+            // The compute pass doesn't support the features needing extra passes.
+            let key = EdgeDetectionKey {
+                temporal: false,
+                dilate: false,
+                pooling: false,
+                halo: false,
+                gradient_output: false,
+                distance_field: false,
+                ..key
+            };
+
+            commands
+                .entity(entity)
+                .insert(EdgeDetectionComputePipelineId(
+                    compute_pipelines.specialize(&pipeline_cache, &edge_detection_pipeline, key),
+                    key.uses_marker_bind_group(),
+                ))
+                .remove::<(EdgeDetectionPipelineId, EdgeDetectionMaskPipelineId)>();
+            continue;
+        }
+
+        if compute {
+            warn_once!(
+                "EdgeDetectionCompute requires an HDR camera, falling back to the fragment pass"
+            );
+        }
 
         commands
             .entity(entity)
-            .insert(EdgeDetectionPipelineId(pipelines.specialize(
-                &pipeline_cache,
-                &edge_detection_pipeline,
-                EdgeDetectionKey::new(edge_detection, hdr, multisampled, projection),
-            )));
+            .remove::<EdgeDetectionComputePipelineId>();
+
+        commands.entity(entity).insert(EdgeDetectionPipelineId(
+            pipelines.specialize(&pipeline_cache, &edge_detection_pipeline, key),
+            key.view_target_format(),
+            key.uses_marker_bind_group(),
+        ));
+
+        if key.dilate {
+            commands
+                .entity(entity)
+                .insert(EdgeDetectionMaskPipelineId(pipelines.specialize(
+                    &pipeline_cache,
+                    &edge_detection_pipeline,
+                    EdgeDetectionKey { mask: true, ..key },
+                )));
+        } else {
+            commands
+                .entity(entity)
+                .remove::<EdgeDetectionMaskPipelineId>();
+        }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub enum ProjectionType {
-    None,
-    Perspective,
-    Orthographic,
+/// Whether a view runs [`EdgeDetectionComputeNode`] instead of [`EdgeDetectionNode`].
+/// The compute pass writes into the view target as a storage texture, which requires the HDR
+/// texture format.
+fn uses_edge_detection_compute(compute: bool, hdr: bool) -> bool {
+    compute && hdr
 }
 
-impl From<Option<&Projection>> for ProjectionType {
-    fn from(proj: Option<&Projection>) -> Self {
-        if let Some(projection) = proj {
-            return match projection {
-                Projection::Perspective(_) => Self::Perspective,
-                Projection::Orthographic(_) => Self::Orthographic,
-            };
+/// The maximum number of [`EdgeDetection::depth_bands`].
+pub const MAX_DEPTH_BANDS: usize = 8;
+
+/// The maximum [`EdgeDetection::halo_radius`], larger radii are clamped to it.
+pub const MAX_HALO_RADIUS: u32 = 8;
+
+/// The format of the edge mask textures, used by [`EdgeDetection::temporal_blend`] and
+/// [`EdgeDetection::dilate_radius`].
+pub const EDGE_MASK_TEXTURE_FORMAT: TextureFormat = TextureFormat::R16Float;
+
+/// The edge masks of the previous and current frame of a view with temporal stabilization.
+#[derive(Component)]
+pub struct EdgeDetectionHistoryTextures {
+    /// The edge mask of the previous frame, reprojected into the current one.
+    pub read: CachedTexture,
+    /// The stabilized edge mask of the current frame.
+    pub write: CachedTexture,
+}
+
+pub fn prepare_edge_detection_history_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    frame_count: Res<FrameCount>,
+    views: Query<(
+        Entity,
+        &ExtractedCamera,
+        &EdgeDetection,
+        Has<EdgeDetectionCompute>,
+    )>,
+) {
+    for (entity, camera, edge_detection, compute) in &views {
+        let (true, Some(physical_target_size)) = (
+            edge_detection.temporal_blend > 0.0
+                && !uses_edge_detection_compute(compute, camera.hdr),
+            camera.physical_target_size,
+        ) else {
+            // Render world views are retained, so drop the history once the effect is turned off.
+            commands
+                .entity(entity)
+                .remove::<EdgeDetectionHistoryTextures>();
+            continue;
         };
 
-        Self::None
+        let mut texture_descriptor = TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: physical_target_size.x,
+                height: physical_target_size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: EDGE_MASK_TEXTURE_FORMAT,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        };
+
+        texture_descriptor.label = Some("edge_detection_history_1_texture");
+        let history_1_texture = texture_cache.get(&render_device, texture_descriptor.clone());
+
+        texture_descriptor.label = Some("edge_detection_history_2_texture");
+        let history_2_texture = texture_cache.get(&render_device, texture_descriptor);
+
+        // Ping-pong between the two textures so last frame's mask can be read while writing this one.
+        let textures = if frame_count.0 % 2 == 0 {
+            EdgeDetectionHistoryTextures {
+                read: history_2_texture,
+                write: history_1_texture,
+            }
+        } else {
+            EdgeDetectionHistoryTextures {
+                read: history_1_texture,
+                write: history_2_texture,
+            }
+        };
+
+        commands.entity(entity).insert(textures);
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct EdgeDetectionKey {
-    /// Whether to enable depth-based edge detection.
-    /// If `true`, edges will be detected based on depth variations.
-    pub enable_depth: bool,
-    /// Whether to enable normal-based edge detection.
-    /// If `true`, edges will be detected based on normal direction variations.
-    pub enable_normal: bool,
-    /// Whether to enable color-based edge detection.
-    /// If `true`, edges will be detected based on color variations.
-    pub enable_color: bool,
+/// The edge mask written by the mask pass of views with [`EdgeDetection::dilate_radius`].
+///
+/// Views that also use [`EdgeDetection::temporal_blend`] write the mask into
+/// [`EdgeDetectionHistoryTextures::write`] instead.
+#[derive(Component)]
+pub struct EdgeDetectionMaskTexture(pub CachedTexture);
 
-    /// Whether we're using HDR.
-    pub hdr: bool,
-    /// Whether the render target is multisampled.
-    pub multisampled: bool,
-    /// The projection type of view
-    pub projection: ProjectionType,
+pub fn prepare_edge_detection_mask_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(
+        Entity,
+        &ExtractedCamera,
+        &EdgeDetection,
+        Has<EdgeDetectionCompute>,
+    )>,
+) {
+    for (entity, camera, edge_detection, compute) in &views {
+        let (true, false, Some(physical_target_size)) = (
+            edge_detection.uses_mask_pass() && !uses_edge_detection_compute(compute, camera.hdr),
+            edge_detection.temporal_blend > 0.0,
+            camera.physical_target_size,
+        ) else {
+            commands.entity(entity).remove::<EdgeDetectionMaskTexture>();
+            continue;
+        };
+
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("edge_detection_mask_texture"),
+                size: Extent3d {
+                    width: physical_target_size.x,
+                    height: physical_target_size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: EDGE_MASK_TEXTURE_FORMAT,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(EdgeDetectionMaskTexture(texture));
+    }
 }
 
-impl EdgeDetectionKey {
-    pub fn new(
-        edge_detection: &EdgeDetection,
-        hdr: bool,
-        multisampled: bool,
-        projection: Option<&Projection>,
-    ) -> Self {
-        Self {
-            enable_depth: edge_detection.enable_depth,
-            enable_normal: edge_detection.enable_normal,
-            enable_color: edge_detection.enable_color,
+/// The format of [`EdgeDetectionGradientTexture`].
+pub const EDGE_GRADIENT_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rg16Float;
 
-            hdr,
-            multisampled,
-            projection: projection.into(),
-        }
+/// The gradient of the edges of a view with [`EdgeDetection::gradient_output`], for passes
+/// running after edge detection.
+///
+/// The red channel is the angle of the gradient in radians, counter-clockwise from the right of
+/// the screen. The green channel is the detection response it belongs to: the ratio of the
+/// gradient to its threshold, above 1.0 on edges. Pixels with a response of 0.0 have no defined
+/// angle.
+#[derive(Component)]
+pub struct EdgeDetectionGradientTexture(pub CachedTexture);
+
+pub fn prepare_edge_detection_gradient_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(
+        Entity,
+        &ExtractedCamera,
+        &EdgeDetection,
+        Has<EdgeDetectionCompute>,
+    )>,
+) {
+    for (entity, camera, edge_detection, compute) in &views {
+        let (true, Some(physical_target_size)) = (
+            edge_detection.gradient_output && !uses_edge_detection_compute(compute, camera.hdr),
+            camera.physical_target_size,
+        ) else {
+            commands
+                .entity(entity)
+                .remove::<EdgeDetectionGradientTexture>();
+            continue;
+        };
+
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("edge_detection_gradient_texture"),
+                size: Extent3d {
+                    width: physical_target_size.x,
+                    height: physical_target_size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: EDGE_GRADIENT_TEXTURE_FORMAT,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(EdgeDetectionGradientTexture(texture));
     }
 }
 
-#[derive(Component, Clone, Copy, Debug, Reflect)]
+/// Excludes a mesh from edge detection: no edges are drawn on it or along its silhouette.
+///
+/// The marked meshes are rendered into extra textures after the prepasses, see
+/// [`EdgeDetectionMarkerTextures`], and the edge detection pass drops the edges of the pixels
+/// where they are the closest surface. This adds a depth prepass to the cameras with
+/// [`EdgeDetection`] while any entity has the component. Lightmapped meshes aren't supported and
+/// keep their edges.
+///
+/// Useful for particles, UI in world space or any mesh which should never be outlined.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
 #[reflect(Component, Default)]
-#[require(DepthPrepass, NormalPrepass)]
-pub struct EdgeDetection {
-    /// Depth threshold, used to detect edges with significant depth changes.
-    /// Areas where the depth variation exceeds this threshold will be marked as edges.
-    pub depth_threshold: f32,
-    /// Normal threshold, used to detect edges with significant normal direction changes.
-    /// Areas where the normal direction variation exceeds this threshold will be marked as edges.
-    pub normal_threshold: f32,
-    /// Color threshold, used to detect edges with significant color changes.
-    /// Areas where the color variation exceeds this threshold will be marked as edges.
-    pub color_threshold: f32,
+pub struct NoEdgeDetection;
 
-    /// Thickness of the edges detected based on depth variations.
-    /// This value controls the width of the edges drawn when depth-based edge detection is enabled.
-    /// Higher values result in thicker edges.
-    pub depth_thickness: f32,
-    /// Thickness of the edges detected based on normal direction variations.
-    /// This value controls the width of the edges drawn when normal-based edge detection is enabled.
-    /// Higher values result in thicker edges.
-    pub normal_thickness: f32,
-    /// Thickness of the edges detected based on color variations.
-    /// This value controls the width of the edges drawn when color-based edge detection is enabled.
-    /// Higher values result in thicker edges.
-    pub color_thickness: f32,
+/// Marks a mesh as outlined by the cameras with [`EdgeDetection::targets_only`], e.g. the hovered
+/// or selected object. Other cameras ignore it.
+///
+/// Like [`NoEdgeDetection`], the marked meshes are rendered into [`EdgeDetectionMarkerTextures`],
+/// and only the pixels within the edge thickness of one where it's the closest surface keep their
+/// edges. Where a mesh meets unmarked geometry, its silhouette is drawn even without a depth,
+/// normal or color gradient. [`NoEdgeDetection`] takes precedence and lightmapped meshes aren't
+/// supported.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionTarget;
 
-    /// Steep angle threshold, used to adjust the depth threshold when viewing surfaces at steep angles.
-    /// When the angle between the view direction and the surface normal is very steep, the depth gradient
+/// Outline color of a mesh, replacing [`EdgeDetection::edge_color`] for the edges around it,
+/// e.g. red for enemies, green for allies and yellow for loot, all at once.
+///
+/// The colored meshes are rendered into [`EdgeDetectionMarkerTextures::color`], one draw call
+/// each like the ones with [`EdgeDetectionOverride`], and an edge pixel takes the color of the
+/// closest colored mesh within the edge thickness of it. The alpha is the opacity of the edges
+/// like for `edge_color`, and fully transparent colors fall back to `edge_color`. The other edge
+/// color settings, like [`EdgeDetection::edge_color_source`], are replaced as well.
+///
+/// The colors are rendered per pixel rather than looked up in a palette indexed by the object IDs
+/// of [`EdgeDetectionObjectIds`], so they don't need [`EdgeDetection::enable_object_id`] and there
+/// is no palette buffer to resize as meshes come and go.
+///
+/// Only the cameras with [`EdgeDetection::mesh_params`] use it, and a depth prepass is added to
+/// them while any entity has the component. [`NoEdgeDetection`] takes precedence and lightmapped
+/// meshes aren't supported.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionColor(pub Color);
+
+/// Thresholds of a mesh, replacing the ones of [`EdgeDetection`] for the pixels it covers, e.g. a
+/// higher normal threshold for smooth sculpted characters in a hard-surface environment.
+///
+/// `None` keeps the threshold of the camera, and so do thresholds of 0.0 or below. The threshold
+/// map and the other adjustments of the thresholds still apply on top.
+///
+/// The thresholds are rendered into [`EdgeDetectionMarkerTextures::thresholds`], and each pixel
+/// takes the ones of the mesh it shows, so the edges along a silhouette follow the mesh on their
+/// side of it. There is no fixed limit on how many meshes have overrides: each takes 256 bytes of
+/// a uniform buffer rebuilt every frame, see [`EdgeMarkerMeshUniforms`], and a draw call of its
+/// own in the marker pass, which is what limits them in practice.
+///
+/// Only the cameras with [`EdgeDetection::mesh_params`] use it, and a depth prepass is added to
+/// them while any entity has the component. [`NoEdgeDetection`] takes precedence and lightmapped
+/// meshes aren't supported.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionOverride {
+    /// Replaces [`EdgeDetection::depth_threshold`].
+    pub depth_threshold: Option<f32>,
+    /// Replaces [`EdgeDetection::normal_threshold`].
+    pub normal_threshold: Option<f32>,
+    /// Replaces [`EdgeDetection::color_threshold`].
+    pub color_threshold: Option<f32>,
+}
+
+/// Inserted by [`insert_edge_detection_prepasses`] on the cameras whose marker pass has anything
+/// to write, see [`EdgeDetectionMarkerTextures`]. The other cameras skip the pass and don't
+/// allocate its textures.
+///
+/// It follows which components exist in the world rather than which meshes are visible, so meshes
+/// coming into view don't switch the pipeline.
+#[derive(Component, Clone, Copy, Debug, Default, ExtractComponent)]
+pub struct EdgeDetectionMarkerPass;
+
+/// Restricts the edge detection of a camera to the meshes on the given layers, e.g. to leave out
+/// debug geometry on a layer of its own which the camera also renders.
+///
+/// Meshes without [`RenderLayers`] are on layer 0. The meshes on none of the layers are treated
+/// like [`NoEdgeDetection`] by this camera: no edges are drawn on them or along their silhouettes,
+/// also where they cover other geometry. Lightmapped meshes aren't supported and keep their edges,
+/// and geometry which isn't a mesh, like gizmos, isn't in the prepasses and only ever produces
+/// color edges.
+#[derive(Component, Clone, Debug, Default, Reflect, ExtractComponent)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionLayers(pub RenderLayers);
+
+/// The meshes with [`NoEdgeDetection`] rendered into [`EdgeDetectionMarkerTextures`].
+pub type WithNoEdgeDetection = (With<Mesh3d>, With<NoEdgeDetection>, Without<Lightmap>);
+
+/// The meshes with [`EdgeDetectionTarget`] rendered into [`EdgeDetectionMarkerTextures`].
+pub type WithEdgeDetectionTarget = (
+    With<Mesh3d>,
+    With<EdgeDetectionTarget>,
+    Without<NoEdgeDetection>,
+    Without<Lightmap>,
+);
+
+/// The meshes with [`EdgeDetectionColor`] rendered into [`EdgeDetectionMarkerTextures`].
+pub type WithEdgeDetectionColor = (
+    With<Mesh3d>,
+    With<EdgeDetectionColor>,
+    Without<NoEdgeDetection>,
+    Without<Lightmap>,
+);
+
+/// The meshes with [`EdgeDetectionOverride`] rendered into [`EdgeDetectionMarkerTextures`].
+pub type WithEdgeDetectionOverride = (
+    With<Mesh3d>,
+    With<EdgeDetectionOverride>,
+    Without<NoEdgeDetection>,
+    Without<Lightmap>,
+);
+
+/// The phase rendering the meshes with [`NoEdgeDetection`] or [`EdgeDetectionTarget`] into
+/// [`EdgeDetectionMarkerTextures`], see [`EdgeMarkerNode`].
+pub struct EdgeMarker3d {
+    pub key: OpaqueNoLightmap3dBinKey,
+    pub representative_entity: (Entity, MainEntity),
+    pub batch_range: Range<u32>,
+    pub extra_index: PhaseItemExtraIndex,
+}
+
+impl PhaseItem for EdgeMarker3d {
+    #[inline]
+    fn entity(&self) -> Entity {
+        self.representative_entity.0
+    }
+
+    fn main_entity(&self) -> MainEntity {
+        self.representative_entity.1
+    }
+
+    #[inline]
+    fn draw_function(&self) -> DrawFunctionId {
+        self.key.draw_function
+    }
+
+    #[inline]
+    fn batch_range(&self) -> &Range<u32> {
+        &self.batch_range
+    }
+
+    #[inline]
+    fn batch_range_mut(&mut self) -> &mut Range<u32> {
+        &mut self.batch_range
+    }
+
+    #[inline]
+    fn extra_index(&self) -> PhaseItemExtraIndex {
+        self.extra_index
+    }
+
+    #[inline]
+    fn batch_range_and_extra_index_mut(&mut self) -> (&mut Range<u32>, &mut PhaseItemExtraIndex) {
+        (&mut self.batch_range, &mut self.extra_index)
+    }
+}
+
+impl BinnedPhaseItem for EdgeMarker3d {
+    type BinKey = OpaqueNoLightmap3dBinKey;
+
+    #[inline]
+    fn new(
+        key: Self::BinKey,
+        representative_entity: (Entity, MainEntity),
+        batch_range: Range<u32>,
+        extra_index: PhaseItemExtraIndex,
+    ) -> Self {
+        Self {
+            key,
+            representative_entity,
+            batch_range,
+            extra_index,
+        }
+    }
+}
+
+impl CachedRenderPipelinePhaseItem for EdgeMarker3d {
+    #[inline]
+    fn cached_pipeline(&self) -> CachedRenderPipelineId {
+        self.key.pipeline
+    }
+}
+
+/// The phase rendering the depth of the meshes with [`EdgeDetectionTarget`] alone into
+/// [`EdgeDetectionMarkerTextures::xray_depth`], for [`EdgeDetection::occluded_edge_color`].
+///
+/// Unlike [`EdgeMarker3d`], no other mesh hides the targets, see [`EdgeMarkerNode`].
+pub struct EdgeXray3d {
+    pub key: OpaqueNoLightmap3dBinKey,
+    pub representative_entity: (Entity, MainEntity),
+    pub batch_range: Range<u32>,
+    pub extra_index: PhaseItemExtraIndex,
+}
+
+impl PhaseItem for EdgeXray3d {
+    #[inline]
+    fn entity(&self) -> Entity {
+        self.representative_entity.0
+    }
+
+    fn main_entity(&self) -> MainEntity {
+        self.representative_entity.1
+    }
+
+    #[inline]
+    fn draw_function(&self) -> DrawFunctionId {
+        self.key.draw_function
+    }
+
+    #[inline]
+    fn batch_range(&self) -> &Range<u32> {
+        &self.batch_range
+    }
+
+    #[inline]
+    fn batch_range_mut(&mut self) -> &mut Range<u32> {
+        &mut self.batch_range
+    }
+
+    #[inline]
+    fn extra_index(&self) -> PhaseItemExtraIndex {
+        self.extra_index
+    }
+
+    #[inline]
+    fn batch_range_and_extra_index_mut(&mut self) -> (&mut Range<u32>, &mut PhaseItemExtraIndex) {
+        (&mut self.batch_range, &mut self.extra_index)
+    }
+}
+
+impl BinnedPhaseItem for EdgeXray3d {
+    type BinKey = OpaqueNoLightmap3dBinKey;
+
+    #[inline]
+    fn new(
+        key: Self::BinKey,
+        representative_entity: (Entity, MainEntity),
+        batch_range: Range<u32>,
+        extra_index: PhaseItemExtraIndex,
+    ) -> Self {
+        Self {
+            key,
+            representative_entity,
+            batch_range,
+            extra_index,
+        }
+    }
+}
+
+impl CachedRenderPipelinePhaseItem for EdgeXray3d {
+    #[inline]
+    fn cached_pipeline(&self) -> CachedRenderPipelineId {
+        self.key.pipeline
+    }
+}
+
+type DrawEdgeMarker = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    SetEdgeMarkerMeshBindGroup<2>,
+    DrawMesh,
+);
+
+/// The [`EdgeDetectionColor`], [`EdgeDetectionOverride`] and object ID of a mesh, written into
+/// [`EdgeDetectionMarkerTextures::color`], [`EdgeDetectionMarkerTextures::thresholds`] and
+/// [`EdgeDetectionMarkerTextures::object_id`].
+#[derive(Clone, Copy, Debug, ShaderType)]
+pub struct EdgeMarkerMeshUniform {
+    /// Transparent for the meshes without a color.
+    pub color: LinearRgba,
+    /// The depth, normal and color thresholds, 0.0 for the ones of the camera.
+    pub thresholds: Vec4,
+    /// See [`EdgeDetectionObjectIds`], 0 while no camera writes object IDs.
+    pub object_id: u32,
+}
+
+impl Default for EdgeMarkerMeshUniform {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::NONE,
+            thresholds: Vec4::ZERO,
+            object_id: 0,
+        }
+    }
+}
+
+/// The parameters of the meshes with [`EdgeDetectionColor`] or [`EdgeDetectionOverride`] and the
+/// object IDs of the meshes, in a uniform buffer bound at a dynamic offset per mesh by
+/// [`SetEdgeMarkerMeshBindGroup`].
+///
+/// Every offset is aligned to the minimum uniform buffer offset alignment of the device, 256
+/// bytes on most of them.
+#[derive(Resource, Default)]
+pub struct EdgeMarkerMeshUniforms {
+    /// The default parameters at offset 0 for the other meshes, then one entry per mesh.
+    pub buffer: DynamicUniformBuffer<EdgeMarkerMeshUniform>,
+    /// The offset of the parameters of every mesh with any in `buffer`.
+    pub offsets: MainEntityHashMap<u32>,
+    /// The bind group of `buffer`, `None` until it's first prepared.
+    pub bind_group: Option<BindGroup>,
+}
+
+pub fn prepare_edge_marker_mesh_uniforms(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    marker_pipeline: Res<EdgeMarkerPipeline>,
+    mesh_params: Res<EdgeDetectionMeshParams>,
+    object_ids: Res<EdgeDetectionObjectIds>,
+    mut uniforms: ResMut<EdgeMarkerMeshUniforms>,
+) {
+    let uniforms = uniforms.as_mut();
+    uniforms.offsets.clear();
+
+    // The default parameters are always written, the pipelines of the views with
+    // `EdgeDetection::mesh_params` bind them even while no mesh has any.
+    uniforms.buffer.clear();
+    uniforms.buffer.push(&EdgeMarkerMeshUniform::default());
+    for (entity, params) in &mesh_params.0 {
+        let offset = uniforms.buffer.push(&EdgeMarkerMeshUniform {
+            object_id: object_ids.0.get(entity).copied().unwrap_or(0),
+            ..*params
+        });
+        uniforms.offsets.insert(*entity, offset);
+    }
+    for (entity, object_id) in &object_ids.0 {
+        if !mesh_params.0.contains_key(entity) {
+            let offset = uniforms.buffer.push(&EdgeMarkerMeshUniform {
+                object_id: *object_id,
+                ..default()
+            });
+            uniforms.offsets.insert(*entity, offset);
+        }
+    }
+    uniforms.buffer.write_buffer(&render_device, &render_queue);
+
+    uniforms.bind_group = uniforms.buffer.binding().map(|binding| {
+        render_device.create_bind_group(
+            "edge_marker_mesh_bind_group",
+            &marker_pipeline.mesh_layout,
+            &BindGroupEntries::single(binding),
+        )
+    });
+}
+
+/// Binds the parameters of the mesh from [`EdgeMarkerMeshUniforms`], or the default ones for the
+/// meshes without any.
+///
+/// Batched meshes share the parameters of the first one, so the meshes with parameters or object
+/// IDs are queued unbatched.
+pub struct SetEdgeMarkerMeshBindGroup<const I: usize>;
+
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetEdgeMarkerMeshBindGroup<I> {
+    type Param = SRes<EdgeMarkerMeshUniforms>;
+    type ViewQuery = ();
+    type ItemQuery = ();
+
+    fn render<'w>(
+        item: &P,
+        _view: ROQueryItem<'w, Self::ViewQuery>,
+        _entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
+        uniforms: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let uniforms = uniforms.into_inner();
+        // Pipelines without `EdgeMarkerPipelineKey::mesh_params` or
+        // `EdgeMarkerPipelineKey::object_id` ignore the extra bind group.
+        if let Some(bind_group) = &uniforms.bind_group {
+            let offset = uniforms
+                .offsets
+                .get(&item.main_entity())
+                .copied()
+                .unwrap_or(0);
+            pass.set_bind_group(I, bind_group, &[offset]);
+        }
+        RenderCommandResult::Success
+    }
+}
+
+/// The pipeline of [`EdgeMarker3d`], built on Bevy's mesh pipeline.
+#[derive(Resource)]
+pub struct EdgeMarkerPipeline {
+    pub mesh_pipeline: MeshPipeline,
+    /// The layout of the parameters of the mesh, see [`EdgeMarkerMeshUniforms`].
+    pub mesh_layout: BindGroupLayout,
+}
+
+/// The marker a mesh writes into [`EdgeDetectionMarkerTextures::marker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeMarker {
+    /// Neither [`NoEdgeDetection`] nor [`EdgeDetectionTarget`], only rendered for
+    /// [`EdgeDetection::enable_object_id`].
+    Unmarked,
+    /// [`NoEdgeDetection`], or outside of the [`EdgeDetectionLayers`] of the view.
+    Excluded,
+    /// [`EdgeDetectionTarget`].
+    Target,
+    /// A transparent mesh, see [`EdgeDetection::transparent_outlines`].
+    Transparent,
+}
+
+/// The key of [`EdgeMarkerPipeline`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeMarkerPipelineKey {
+    pub mesh_key: MeshPipelineKey,
+    pub marker: EdgeMarker,
+    /// Whether the object ID of the mesh is written as well, see
+    /// [`EdgeDetection::enable_object_id`].
+    pub object_id: bool,
+    /// Whether only the depth is written, for the [`EdgeXray3d`] phase.
+    pub depth_only: bool,
+    /// Whether the color and the thresholds of the mesh are written as well, see
+    /// [`EdgeDetectionColor`] and [`EdgeDetectionOverride`].
+    pub mesh_params: bool,
+}
+
+impl FromWorld for EdgeMarkerPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let mesh_layout = render_device.create_bind_group_layout(
+            "edge_marker_mesh_bind_group_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::FRAGMENT,
+                uniform_buffer::<EdgeMarkerMeshUniform>(true),
+            ),
+        );
+
+        Self {
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+            mesh_layout,
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for EdgeMarkerPipeline {
+    type Key = EdgeMarkerPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key.mesh_key, layout)?;
+
+        // The marker of the closest mesh is written into single sampled textures whatever the
+        // `Msaa` of the view. The view bind group still has to match the view, so the key keeps
+        // its samples.
+        // The fragment shader reads the vertex output of the mesh shader.
+        let mut shader_defs = descriptor.vertex.shader_defs.clone();
+        let mut targets = vec![Some(ColorTargetState {
+            format: EDGE_MARKER_TEXTURE_FORMAT,
+            blend: None,
+            write_mask: ColorWrites::ALL,
+        })];
+
+        match key.marker {
+            EdgeMarker::Unmarked => (),
+            EdgeMarker::Excluded => shader_defs.push("MARKER_EXCLUDED".into()),
+            EdgeMarker::Target => shader_defs.push("MARKER_TARGET".into()),
+            EdgeMarker::Transparent => shader_defs.push("MARKER_TRANSPARENT".into()),
+        }
+
+        // Both read the parameters of the mesh.
+        if key.mesh_params || key.object_id {
+            shader_defs.push("MESH_UNIFORM".into());
+        }
+
+        if key.object_id {
+            shader_defs.push("OBJECT_ID".into());
+            targets.push(Some(ColorTargetState {
+                format: EDGE_OBJECT_ID_TEXTURE_FORMAT,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            }));
+        }
+
+        if key.mesh_params {
+            shader_defs.push("MESH_PARAMS".into());
+            for format in [
+                EDGE_MARKER_COLOR_TEXTURE_FORMAT,
+                EDGE_MARKER_THRESHOLDS_TEXTURE_FORMAT,
+            ] {
+                targets.push(Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                }));
+            }
+        }
+
+        if key.depth_only {
+            descriptor.label = Some("edge_detection: x-ray pipeline".into());
+            descriptor.fragment = None;
+        } else {
+            if key.mesh_params || key.object_id {
+                descriptor.layout.push(self.mesh_layout.clone());
+            }
+            descriptor.label = Some("edge_detection: marker pipeline".into());
+            descriptor.fragment = Some(FragmentState {
+                shader: EDGE_MARKER_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets,
+            });
+        }
+        descriptor.depth_stencil = Some(DepthStencilState {
+            format: EDGE_MARKER_DEPTH_TEXTURE_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::GreaterEqual,
+            stencil: default(),
+            bias: default(),
+        });
+        descriptor.multisample = default();
+
+        Ok(descriptor)
+    }
+}
+
+/// Creates the [`EdgeMarker3d`] phases of the cameras with [`EdgeDetection`], and the
+/// [`EdgeXray3d`] phases of the ones with [`EdgeDetection::occluded_edge_color`].
+pub fn extract_edge_marker_phases(
+    mut phases: ResMut<ViewBinnedRenderPhases<EdgeMarker3d>>,
+    mut xray_phases: ResMut<ViewBinnedRenderPhases<EdgeXray3d>>,
+    cameras: Extract<Query<(RenderEntity, &Camera, &EdgeDetection)>>,
+    mut live_entities: Local<EntityHashSet>,
+    mut live_xray_entities: Local<EntityHashSet>,
+) {
+    live_entities.clear();
+    live_xray_entities.clear();
+
+    for (entity, camera, edge_detection) in &cameras {
+        if !camera.is_active {
+            continue;
+        }
+
+        phases.insert_or_clear(entity);
+        live_entities.insert(entity);
+
+        if edge_detection.occluded_edge_color.is_some() {
+            xray_phases.insert_or_clear(entity);
+            live_xray_entities.insert(entity);
+        }
+    }
+
+    phases.retain(|entity, _| live_entities.contains(entity));
+    xray_phases.retain(|entity, _| live_xray_entities.contains(entity));
+}
+
+/// The lightmapped meshes, which [`EdgeMarkerPipeline`] doesn't support.
+#[derive(Resource, Default)]
+pub struct EdgeDetectionLightmappedMeshes(pub MainEntityHashSet);
+
+#[allow(clippy::type_complexity)]
+pub fn extract_edge_detection_lightmapped_meshes(
+    mut lightmapped_meshes: ResMut<EdgeDetectionLightmappedMeshes>,
+    meshes: Extract<Query<Entity, (With<Mesh3d>, With<Lightmap>)>>,
+) {
+    lightmapped_meshes.0.clear();
+    lightmapped_meshes
+        .0
+        .extend(meshes.iter().map(MainEntity::from));
+}
+
+/// The parameters of the meshes with [`EdgeDetectionColor`] or [`EdgeDetectionOverride`].
+#[derive(Resource, Default)]
+pub struct EdgeDetectionMeshParams(pub MainEntityHashMap<EdgeMarkerMeshUniform>);
+
+#[allow(clippy::type_complexity)]
+pub fn extract_edge_detection_mesh_params(
+    mut mesh_params: ResMut<EdgeDetectionMeshParams>,
+    colored_meshes: Extract<Query<(Entity, &EdgeDetectionColor), WithEdgeDetectionColor>>,
+    overridden_meshes: Extract<Query<(Entity, &EdgeDetectionOverride), WithEdgeDetectionOverride>>,
+) {
+    mesh_params.0.clear();
+    for (entity, color) in &colored_meshes {
+        mesh_params.0.insert(
+            entity.into(),
+            EdgeMarkerMeshUniform {
+                color: color.0.to_linear(),
+                ..default()
+            },
+        );
+    }
+    for (entity, thresholds) in &overridden_meshes {
+        let params = mesh_params.0.entry(entity.into()).or_default();
+        params.thresholds = Vec4::new(
+            thresholds.depth_threshold.unwrap_or(0.0),
+            thresholds.normal_threshold.unwrap_or(0.0),
+            thresholds.color_threshold.unwrap_or(0.0),
+            0.0,
+        );
+    }
+}
+
+/// The object IDs of the meshes, while a camera has [`EdgeDetection::enable_object_id`].
+///
+/// The ID of a mesh is the index of its entity plus one, so it stays the same from frame to frame
+/// and across cameras for as long as the entity lives. 0 is left for the background.
+#[derive(Resource, Default)]
+pub struct EdgeDetectionObjectIds(pub MainEntityHashMap<u32>);
+
+/// Only collects the IDs while a camera writes them.
+#[allow(clippy::type_complexity)]
+pub fn extract_edge_detection_object_ids(
+    mut object_ids: ResMut<EdgeDetectionObjectIds>,
+    cameras: Extract<Query<&EdgeDetection>>,
+    meshes: Extract<Query<Entity, (With<Mesh3d>, Without<Lightmap>)>>,
+) {
+    object_ids.0.clear();
+    if !cameras.iter().any(|camera| camera.enable_object_id) {
+        return;
+    }
+
+    object_ids.0.extend(
+        meshes
+            .iter()
+            .map(|entity| (MainEntity::from(entity), entity.index() + 1)),
+    );
+}
+
+/// The meshes with a transparent [`StandardMaterial`], for
+/// [`EdgeDetection::transparent_outlines`].
+#[derive(Resource, Default)]
+pub struct EdgeDetectionTransparentMeshes(pub MainEntityHashSet);
+
+/// Only collects the meshes while a camera outlines them.
+#[allow(clippy::type_complexity)]
+pub fn extract_edge_detection_transparent_meshes(
+    mut transparent_meshes: ResMut<EdgeDetectionTransparentMeshes>,
+    cameras: Extract<Query<&EdgeDetection>>,
+    meshes: Extract<Query<(Entity, &MeshMaterial3d<StandardMaterial>), With<Mesh3d>>>,
+    materials: Extract<Res<Assets<StandardMaterial>>>,
+) {
+    transparent_meshes.0.clear();
+    if !cameras.iter().any(|camera| camera.transparent_outlines) {
+        return;
+    }
+
+    transparent_meshes.0.extend(
+        meshes
+            .iter()
+            .filter(|(_, material)| {
+                materials.get(&material.0).is_some_and(|material| {
+                    matches!(
+                        material.alpha_mode,
+                        AlphaMode::Blend
+                            | AlphaMode::Premultiplied
+                            | AlphaMode::Add
+                            | AlphaMode::Multiply
+                    )
+                })
+            })
+            .map(|(entity, _)| MainEntity::from(entity)),
+    );
+}
+
+/// The [`RenderLayers`] of the meshes which have them, for [`EdgeDetectionLayers`].
+#[derive(Resource, Default)]
+pub struct EdgeDetectionMeshLayers(pub MainEntityHashMap<RenderLayers>);
+
+pub fn extract_edge_detection_mesh_layers(
+    mut mesh_layers: ResMut<EdgeDetectionMeshLayers>,
+    meshes: Extract<Query<(Entity, &RenderLayers), With<Mesh3d>>>,
+) {
+    mesh_layers.0.clear();
+    mesh_layers.0.extend(
+        meshes
+            .iter()
+            .map(|(entity, layers)| (MainEntity::from(entity), layers.clone())),
+    );
+}
+
+/// Queues the visible meshes with [`NoEdgeDetection`] or [`EdgeDetectionTarget`] of the views
+/// with a depth prepass and [`EdgeDetectionMarkerPass`], the visible meshes outside of the [`EdgeDetectionLayers`] of the views,
+/// the transparent ones of the views with [`EdgeDetection::transparent_outlines`], and every
+/// other visible mesh of the views with [`EdgeDetection::enable_object_id`].
+///
+/// The targets are queued a second time into the [`EdgeXray3d`] phases, which only exist for the
+/// views with [`EdgeDetection::occluded_edge_color`].
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn queue_edge_marker_meshes(
+    draw_functions: Res<DrawFunctions<EdgeMarker3d>>,
+    xray_draw_functions: Res<DrawFunctions<EdgeXray3d>>,
+    marker_pipeline: Res<EdgeMarkerPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<EdgeMarkerPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    render_meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    lightmapped_meshes: Res<EdgeDetectionLightmappedMeshes>,
+    mesh_layers: Res<EdgeDetectionMeshLayers>,
+    transparent_meshes: Res<EdgeDetectionTransparentMeshes>,
+    mesh_params: Res<EdgeDetectionMeshParams>,
+    mut phases: ResMut<ViewBinnedRenderPhases<EdgeMarker3d>>,
+    mut xray_phases: ResMut<ViewBinnedRenderPhases<EdgeXray3d>>,
+    views: Query<
+        (
+            Entity,
+            &RenderVisibleEntities,
+            &Msaa,
+            &ViewPrepassTextures,
+            &EdgeDetection,
+            Option<&EdgeDetectionLayers>,
+            Has<OrderIndependentTransparencySettings>,
+        ),
+        (With<ExtractedView>, With<EdgeDetectionMarkerPass>),
+    >,
+) {
+    let draw_function = draw_functions.read().id::<DrawEdgeMarker>();
+    let xray_draw_function = xray_draw_functions.read().id::<DrawEdgeMarker>();
+    let default_layers = RenderLayers::default();
+
+    for (view, visible_entities, msaa, prepass_textures, edge_detection, layers, oit) in &views {
+        let Some(phase) = phases.get_mut(&view) else {
+            continue;
+        };
+        let mut xray_phase = xray_phases.get_mut(&view);
+
+        if prepass_textures.depth.is_none() {
+            continue;
+        }
+
+        // The view bind group of the mesh pipeline depends on the prepasses of the view.
+        let mut view_key =
+            MeshPipelineKey::from_msaa_samples(msaa.samples()) | MeshPipelineKey::DEPTH_PREPASS;
+        if prepass_textures.normal.is_some() {
+            view_key |= MeshPipelineKey::NORMAL_PREPASS;
+        }
+        if prepass_textures.motion_vectors.is_some() {
+            view_key |= MeshPipelineKey::MOTION_VECTOR_PREPASS;
+        }
+        if prepass_textures.deferred.is_some() {
+            view_key |= MeshPipelineKey::DEFERRED_PREPASS;
+        }
+        if oit {
+            view_key |= MeshPipelineKey::OIT_ENABLED;
+        }
+
+        let excluded = visible_entities
+            .iter::<WithNoEdgeDetection>()
+            .map(|entity| (entity, EdgeMarker::Excluded));
+        let targets = visible_entities
+            .iter::<WithEdgeDetectionTarget>()
+            .map(|entity| (entity, EdgeMarker::Target));
+
+        let object_id = edge_detection.enable_object_id;
+        let transparent = edge_detection.transparent_outlines;
+        let params = edge_detection.mesh_params;
+        let entities: Vec<_> = if object_id
+            || layers.is_some()
+            || transparent
+            || (params && !mesh_params.0.is_empty())
+        {
+            // Every mesh writes its ID or is matched against the layers, so the marked ones are
+            // looked up among all of them.
+            let marked: MainEntityHashMap<EdgeMarker> = excluded
+                .chain(targets)
+                .map(|((_, main_entity), marker)| (*main_entity, marker))
+                .collect();
+
+            visible_entities
+                .iter::<With<Mesh3d>>()
+                .filter(|(_, main_entity)| !lightmapped_meshes.0.contains(main_entity))
+                .filter_map(|entity| {
+                    let on_layers = layers.is_none_or(|layers| {
+                        let mesh_layers = mesh_layers.0.get(&entity.1).unwrap_or(&default_layers);
+                        mesh_layers.intersects(&layers.0)
+                    });
+                    let marker = match marked.get(&entity.1) {
+                        _ if !on_layers => EdgeMarker::Excluded,
+                        Some(marker) => *marker,
+                        None if transparent && transparent_meshes.0.contains(&entity.1) => {
+                            EdgeMarker::Transparent
+                        }
+                        None => EdgeMarker::Unmarked,
+                    };
+
+                    // Without object IDs only the marked meshes and the ones with parameters are
+                    // rendered.
+                    let rendered = object_id
+                        || marker != EdgeMarker::Unmarked
+                        || (params && mesh_params.0.contains_key(&entity.1));
+                    rendered.then_some((entity, marker))
+                })
+                .collect()
+        } else {
+            excluded.chain(targets).collect()
+        };
+
+        for ((render_entity, visible_entity), marker) in entities {
+            let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*visible_entity)
+            else {
+                continue;
+            };
+            let Some(mesh) = render_meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+
+            let mut mesh_key = view_key | MeshPipelineKey::from_bits_retain(mesh.key_bits.bits());
+
+            // The mesh bind group holds the previous skin and morph targets when the view has
+            // motion vectors.
+            if prepass_textures.motion_vectors.is_some() {
+                if mesh_instance
+                    .flags
+                    .contains(RenderMeshInstanceFlags::HAS_PREVIOUS_SKIN)
+                {
+                    mesh_key |= MeshPipelineKey::HAS_PREVIOUS_SKIN;
+                }
+                if mesh_instance
+                    .flags
+                    .contains(RenderMeshInstanceFlags::HAS_PREVIOUS_MORPH)
+                {
+                    mesh_key |= MeshPipelineKey::HAS_PREVIOUS_MORPH;
+                }
+            }
+
+            let key = EdgeMarkerPipelineKey {
+                mesh_key,
+                marker,
+                object_id,
+                depth_only: false,
+                mesh_params: params,
+            };
+            let pipeline =
+                match pipelines.specialize(&pipeline_cache, &marker_pipeline, key, &mesh.layout) {
+                    Ok(pipeline) => pipeline,
+                    Err(err) => {
+                        error!("{}", err);
+                        continue;
+                    }
+                };
+
+            phase.add(
+                OpaqueNoLightmap3dBinKey {
+                    pipeline,
+                    draw_function,
+                    asset_id: mesh_instance.mesh_asset_id.into(),
+                    material_bind_group_id: None,
+                },
+                (*render_entity, *visible_entity),
+                // A batch is drawn with the parameters and the object ID of its first mesh, see
+                // `SetEdgeMarkerMeshBindGroup`.
+                if object_id || (params && mesh_params.0.contains_key(visible_entity)) {
+                    BinnedRenderPhaseType::UnbatchableMesh
+                } else {
+                    BinnedRenderPhaseType::mesh(mesh_instance.should_batch())
+                },
+            );
+
+            let Some(xray_phase) = xray_phase.as_mut().filter(|_| marker == EdgeMarker::Target)
+            else {
+                continue;
+            };
+            let xray_key = EdgeMarkerPipelineKey {
+                object_id: false,
+                depth_only: true,
+                mesh_params: false,
+                ..key
+            };
+            let xray_pipeline = match pipelines.specialize(
+                &pipeline_cache,
+                &marker_pipeline,
+                xray_key,
+                &mesh.layout,
+            ) {
+                Ok(pipeline) => pipeline,
+                Err(err) => {
+                    error!("{}", err);
+                    continue;
+                }
+            };
+
+            xray_phase.add(
+                OpaqueNoLightmap3dBinKey {
+                    pipeline: xray_pipeline,
+                    draw_function: xray_draw_function,
+                    asset_id: mesh_instance.mesh_asset_id.into(),
+                    material_bind_group_id: None,
+                },
+                (*render_entity, *visible_entity),
+                BinnedRenderPhaseType::mesh(mesh_instance.should_batch()),
+            );
+        }
+    }
+}
+
+/// The format of [`EdgeDetectionMarkerTextures::depth`].
+pub const EDGE_MARKER_DEPTH_TEXTURE_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// The format of [`EdgeDetectionMarkerTextures::marker`].
+pub const EDGE_MARKER_TEXTURE_FORMAT: TextureFormat = TextureFormat::R8Unorm;
+
+/// The format of [`EdgeDetectionMarkerTextures::object_id`].
+pub const EDGE_OBJECT_ID_TEXTURE_FORMAT: TextureFormat = TextureFormat::R32Uint;
+
+/// The format of [`EdgeDetectionMarkerTextures::color`].
+pub const EDGE_MARKER_COLOR_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// The format of [`EdgeDetectionMarkerTextures::thresholds`].
+pub const EDGE_MARKER_THRESHOLDS_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// The meshes with [`NoEdgeDetection`] or [`EdgeDetectionTarget`] of a view, and all of its
+/// meshes with [`EdgeDetection::enable_object_id`].
+///
+/// Present on the views with a [`DepthPrepass`] and [`EdgeDetectionMarkerPass`], and cleared
+/// while none of these meshes are visible.
+#[derive(Component)]
+pub struct EdgeDetectionMarkerTextures {
+    /// The depth of the closest rendered mesh, 0.0 where there are none.
+    pub depth: CachedTexture,
+    /// The marker of the closest rendered mesh: 1.0 for [`EdgeDetectionTarget`], 0.5 for
+    /// [`NoEdgeDetection`], 0.25 for transparent meshes and 0.0 for the others, see
+    /// [`EdgeMarker`].
+    pub marker: CachedTexture,
+    /// The object ID of the closest mesh, 0 where there are none, see
+    /// [`EdgeDetectionObjectIds`].
+    pub object_id: Option<CachedTexture>,
+    /// The depth of the closest mesh with [`EdgeDetectionTarget`], whatever is in front of it,
+    /// 0.0 where there are none, see [`EdgeDetection::occluded_edge_color`].
+    pub xray_depth: Option<CachedTexture>,
+    /// The [`EdgeDetectionColor`] of the closest mesh, transparent where it has none. Only
+    /// present with [`EdgeDetection::mesh_params`].
+    pub color: Option<CachedTexture>,
+    /// The depth, normal and color thresholds of the [`EdgeDetectionOverride`] of the closest
+    /// mesh, 0.0 where it has none. Present along with `color`.
+    pub thresholds: Option<CachedTexture>,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn prepare_edge_detection_marker_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(
+        Entity,
+        &ExtractedCamera,
+        &EdgeDetection,
+        Has<DepthPrepass>,
+        Has<EdgeDetectionMarkerPass>,
+    )>,
+) {
+    for (entity, camera, edge_detection, depth_prepass, marker_pass) in &views {
+        let (true, Some(physical_target_size)) =
+            (depth_prepass && marker_pass, camera.physical_target_size)
+        else {
+            commands
+                .entity(entity)
+                .remove::<EdgeDetectionMarkerTextures>();
+            continue;
+        };
+
+        let descriptor = TextureDescriptor {
+            label: Some("edge_detection_marker_depth_texture"),
+            size: Extent3d {
+                width: physical_target_size.x,
+                height: physical_target_size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: EDGE_MARKER_DEPTH_TEXTURE_FORMAT,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        };
+
+        let depth = texture_cache.get(&render_device, descriptor.clone());
+        let marker = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("edge_detection_marker_texture"),
+                format: EDGE_MARKER_TEXTURE_FORMAT,
+                ..descriptor.clone()
+            },
+        );
+        let xray_depth = edge_detection.occluded_edge_color.is_some().then(|| {
+            texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some("edge_detection_xray_depth_texture"),
+                    ..descriptor.clone()
+                },
+            )
+        });
+        let color = edge_detection.mesh_params.then(|| {
+            texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some("edge_detection_marker_color_texture"),
+                    format: EDGE_MARKER_COLOR_TEXTURE_FORMAT,
+                    ..descriptor.clone()
+                },
+            )
+        });
+        let thresholds = edge_detection.mesh_params.then(|| {
+            texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some("edge_detection_marker_thresholds_texture"),
+                    format: EDGE_MARKER_THRESHOLDS_TEXTURE_FORMAT,
+                    ..descriptor.clone()
+                },
+            )
+        });
+        let object_id = edge_detection.enable_object_id.then(|| {
+            texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some("edge_detection_object_id_texture"),
+                    format: EDGE_OBJECT_ID_TEXTURE_FORMAT,
+                    ..descriptor
+                },
+            )
+        });
+
+        commands.entity(entity).insert(EdgeDetectionMarkerTextures {
+            depth,
+            marker,
+            object_id,
+            xray_depth,
+            color,
+            thresholds,
+        });
+    }
+}
+
+/// The format of [`EdgeDetectionDistanceTexture`].
+pub const EDGE_DISTANCE_TEXTURE_FORMAT: TextureFormat = TextureFormat::R16Float;
+
+/// The distance in pixels to the nearest edge of a view with
+/// [`EdgeDetection::distance_field_radius`], for passes running after edge detection.
+#[derive(Component)]
+pub struct EdgeDetectionDistanceTexture(pub CachedTexture);
+
+pub fn prepare_edge_detection_distance_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(
+        Entity,
+        &ExtractedCamera,
+        &EdgeDetection,
+        Has<EdgeDetectionCompute>,
+    )>,
+) {
+    for (entity, camera, edge_detection, compute) in &views {
+        let (true, Some(physical_target_size)) = (
+            edge_detection.distance_field_radius > 0
+                && !uses_edge_detection_compute(compute, camera.hdr),
+            camera.physical_target_size,
+        ) else {
+            commands
+                .entity(entity)
+                .remove::<EdgeDetectionDistanceTexture>();
+            continue;
+        };
+
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("edge_detection_distance_texture"),
+                size: Extent3d {
+                    width: physical_target_size.x,
+                    height: physical_target_size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: EDGE_DISTANCE_TEXTURE_FORMAT,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(EdgeDetectionDistanceTexture(texture));
+    }
+}
+
+/// Runs the edge detection of a camera as a compute shader instead of a fullscreen fragment
+/// pass, see [`EdgeDetectionComputeNode`].
+///
+/// The result is written straight into the view target as a storage texture, so the effect can
+/// be chained with other compute effects. This requires an HDR camera, whose main textures are
+/// given [`TextureUsages::STORAGE_BINDING`] automatically; other cameras keep using the fragment
+/// pass. [`EdgeDetection::temporal_blend`], [`EdgeDetection::dilate_radius`],
+/// [`EdgeDetection::pooling_radius`], [`EdgeDetection::gradient_output`] and
+/// [`EdgeDetection::distance_field_radius`] need extra passes or render targets and are ignored
+/// by the compute pass.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect, ExtractComponent)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionCompute;
+
+/// Adds [`TextureUsages::STORAGE_BINDING`] to the main textures of HDR cameras with
+/// [`EdgeDetectionCompute`], which the compute pass writes into.
+pub fn insert_edge_detection_storage_usages(
+    mut cameras: Query<(&Camera, &mut CameraMainTextureUsages), With<EdgeDetectionCompute>>,
+) {
+    for (camera, mut usages) in &mut cameras {
+        if camera.hdr && !usages.0.contains(TextureUsages::STORAGE_BINDING) {
+            usages.0 |= TextureUsages::STORAGE_BINDING;
+        }
+    }
+}
+
+/// Animates [`EdgeDetection::edge_color`] of the same entity back and forth, e.g. to highlight
+/// interactables.
+///
+/// The color oscillates between `color_a` and `color_b` and its alpha, the opacity of the edges,
+/// is scaled between `min_intensity` and `max_intensity`. When the component is removed, the
+/// animation stops and the edge color it started from is restored.
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionPulse {
+    /// Duration in seconds of a full cycle from `color_a` to `color_b` and back.
+    ///
+    /// Range: (0.0, inf)
+    pub period: f32,
+    /// Scale of the edge opacity at `color_a`.
+    pub min_intensity: f32,
+    /// Scale of the edge opacity at `color_b`.
+    pub max_intensity: f32,
+    /// Edge color at the start of each cycle.
+    pub color_a: Color,
+    /// Edge color halfway through each cycle.
+    pub color_b: Color,
+}
+
+impl Default for EdgeDetectionPulse {
+    fn default() -> Self {
+        Self {
+            period: 1.0,
+            min_intensity: 0.0,
+            max_intensity: 1.0,
+            color_a: Color::BLACK,
+            color_b: Color::BLACK,
+        }
+    }
+}
+
+/// The edge color of an entity before [`EdgeDetectionPulse`] started animating it.
+#[derive(Component)]
+pub struct EdgeDetectionPulseOrigin(pub Color);
+
+/// Animates the edge color of entities with [`EdgeDetectionPulse`], and restores it once the
+/// component is removed.
+#[allow(clippy::type_complexity)]
+pub fn animate_edge_detection_pulses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pulses: Query<(
+        Entity,
+        &EdgeDetectionPulse,
+        &mut EdgeDetection,
+        Has<EdgeDetectionPulseOrigin>,
+    )>,
+    mut stopped: Query<
+        (Entity, &mut EdgeDetection, &EdgeDetectionPulseOrigin),
+        Without<EdgeDetectionPulse>,
+    >,
+) {
+    for (entity, pulse, mut edge_detection, started) in &mut pulses {
+        if !started {
+            commands
+                .entity(entity)
+                .insert(EdgeDetectionPulseOrigin(edge_detection.edge_color));
+        }
+
+        // A raised cosine, 0 at the start of a cycle and 1 halfway through.
+        let phase = time.elapsed_secs() / pulse.period.max(f32::EPSILON);
+        let wave = 0.5 - 0.5 * (std::f32::consts::TAU * phase).cos();
+
+        let color = pulse.color_a.mix(&pulse.color_b, wave);
+        let intensity = pulse.min_intensity.lerp(pulse.max_intensity, wave);
+        edge_detection.edge_color = color.with_alpha(color.alpha() * intensity);
+    }
+
+    for (entity, mut edge_detection, origin) in &mut stopped {
+        edge_detection.edge_color = origin.0;
+        commands.entity(entity).remove::<EdgeDetectionPulseOrigin>();
+    }
+}
+
+/// Inserts the prepasses needed by the enabled features of [`EdgeDetection`] on its cameras.
+///
+/// Bevy only creates the prepass textures of views with an [`Msaa`] component, so cameras without
+/// one get `Msaa::Off`, matching how the edge detection pass treats them.
+///
+/// Prepasses are never removed, since other effects of the camera may rely on them.
+#[allow(clippy::type_complexity)]
+pub fn insert_edge_detection_prepasses(
+    mut commands: Commands,
+    cameras: Query<(
+        Entity,
+        &EdgeDetection,
+        Has<DepthPrepass>,
+        Has<NormalPrepass>,
+        Has<MotionVectorPrepass>,
+        Has<DistanceFog>,
+        Has<EdgeDetectionLayers>,
+        Has<Msaa>,
+        Has<EdgeDetectionMarkerPass>,
+    )>,
+    excluded_entities: Query<(), With<NoEdgeDetection>>,
+    target_entities: Query<(), With<EdgeDetectionTarget>>,
+    param_entities: Query<(), Or<(With<EdgeDetectionColor>, With<EdgeDetectionOverride>)>>,
+) {
+    // Entities with `NoEdgeDetection` are matched against the depth prepass, and so are the
+    // entities outside of `EdgeDetectionLayers` and, in the views with
+    // `EdgeDetection::mesh_params`, the ones with `EdgeDetectionColor` or `EdgeDetectionOverride`.
+    let excluded_entities = !excluded_entities.is_empty();
+    let target_entities = !target_entities.is_empty();
+    let param_entities = !param_entities.is_empty();
+
+    for (
+        entity,
+        edge_detection,
+        depth_prepass,
+        normal_prepass,
+        motion_vector_prepass,
+        fog,
+        layers,
+        msaa,
+        has_marker_pass,
+    ) in &cameras
+    {
+        if !msaa {
+            commands.entity(entity).insert(Msaa::Off);
+        }
+
+        let fog = fog && edge_detection.respect_fog;
+        let excluded_entities =
+            excluded_entities || layers || (param_entities && edge_detection.mesh_params);
+        if (edge_detection.needs_depth_prepass() || fog || excluded_entities) && !depth_prepass {
+            commands.entity(entity).insert(DepthPrepass);
+        }
+
+        // The marker pass also writes the targets, object IDs and transparent silhouettes.
+        let marker_pass = excluded_entities
+            || target_entities
+            || edge_detection.enable_object_id
+            || edge_detection.transparent_outlines;
+        if marker_pass && !has_marker_pass {
+            commands.entity(entity).insert(EdgeDetectionMarkerPass);
+        } else if !marker_pass && has_marker_pass {
+            commands.entity(entity).remove::<EdgeDetectionMarkerPass>();
+        }
+
+        if edge_detection.needs_normal_prepass() && !normal_prepass {
+            commands.entity(entity).insert(NormalPrepass);
+        }
+
+        if edge_detection.needs_motion_vector_prepass() && !motion_vector_prepass {
+            commands.entity(entity).insert(MotionVectorPrepass);
+        }
+    }
+}
+
+/// Whether edge detection is effectively running for a camera, see [`update_edge_detection_status`].
+///
+/// Inserted on every camera with [`EdgeDetection`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionStatus {
+    /// Whether the edge detection pass runs for the camera.
+    pub active: bool,
+    /// Why the edge detection pass doesn't run, `None` while it's active or not evaluated yet.
+    pub reason: Option<DisabledReason>,
+}
+
+/// The reason edge detection doesn't run for a camera, see [`EdgeDetectionStatus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum DisabledReason {
+    /// The platform can't sample depth textures correctly.
+    Unsupported,
+    /// The camera has no [`DepthPrepass`].
+    MissingDepthPrepass,
+    /// The camera has no [`NormalPrepass`].
+    MissingNormalPrepass,
+    /// The camera isn't active.
+    Disabled,
+}
+
+/// Updates the [`EdgeDetectionStatus`] of cameras with [`EdgeDetection`].
+#[allow(clippy::type_complexity)]
+pub fn update_edge_detection_status(
+    mut cameras: Query<(
+        &Camera,
+        &EdgeDetection,
+        Has<DepthPrepass>,
+        Has<NormalPrepass>,
+        &mut EdgeDetectionStatus,
+    )>,
+) {
+    for (camera, edge_detection, depth_prepass, normal_prepass, mut status) in &mut cameras {
+        let reason = if !DEPTH_TEXTURE_SAMPLING_SUPPORTED {
+            Some(DisabledReason::Unsupported)
+        } else if !camera.is_active {
+            Some(DisabledReason::Disabled)
+        } else if !depth_prepass && edge_detection.needs_depth_prepass() {
+            Some(DisabledReason::MissingDepthPrepass)
+        } else if !normal_prepass && edge_detection.needs_normal_prepass() {
+            Some(DisabledReason::MissingNormalPrepass)
+        } else {
+            None
+        };
+
+        status.set_if_neq(EdgeDetectionStatus {
+            active: reason.is_none(),
+            reason,
+        });
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProjectionType {
+    None,
+    Perspective,
+    Orthographic,
+}
+
+impl From<Option<&Projection>> for ProjectionType {
+    fn from(proj: Option<&Projection>) -> Self {
+        if let Some(projection) = proj {
+            return match projection {
+                Projection::Perspective(_) => Self::Perspective,
+                Projection::Orthographic(_) => Self::Orthographic,
+            };
+        };
+
+        Self::None
+    }
+}
+
+/// The specialization key of the edge detection pipelines, see [`EdgeDetectionKey::new`].
+///
+/// `PartialEq`, `Eq` and `Hash` are derived so every field takes part in them, and
+/// [`EdgeDetectionKey::shader_defs`] destructures the key exhaustively, so new fields can't be
+/// left out of the pipeline cache or the shader.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeDetectionKey {
+    /// Whether to enable depth-based edge detection.
+    /// If `true`, edges will be detected based on depth variations.
+    pub enable_depth: bool,
+    /// Whether to enable normal-based edge detection.
+    /// If `true`, edges will be detected based on normal direction variations.
+    pub enable_normal: bool,
+    /// Whether weaker normal edges close to stronger ones are suppressed.
+    pub normal_edge_spacing: bool,
+    /// Whether edges are thinned to a single pixel by non-maximum suppression.
+    pub thin_edges: bool,
+    /// The space normals are compared in.
+    pub normal_space: NormalSpace,
+    /// Whether to enable color-based edge detection.
+    /// If `true`, edges will be detected based on color variations.
+    pub enable_color: bool,
+    /// Whether color-based edge detection only considers chroma changes.
+    pub chroma_only: bool,
+    /// How the color detector weights the channels of color differences.
+    pub color_edge_space: ColorEdgeSpace,
+    pub enable_object_id: bool,
+    /// Whether material IDs are detected, only set for cameras with a [`DeferredPrepass`].
+    pub enable_material_id: bool,
+    /// Whether edges are detected in a curvature texture, see
+    /// [`EdgeDetection::curvature_texture`].
+    pub curvature_edges: bool,
+    /// Whether a secondary depth texture is combined with the depth prepass.
+    pub secondary_depth: bool,
+    /// How the responses of the detectors are combined.
+    pub combine_mode: EdgeCombineMode,
+    /// Which of the detectors must fire for an edge.
+    pub edge_logic: EdgeLogic,
+    /// The derivative kernel of the depth detector.
+    pub depth_operator: EdgeOperator,
+    /// The derivative kernel of the normal detector.
+    pub normal_operator: EdgeOperator,
+    /// The derivative kernel of the color detector.
+    pub color_operator: EdgeOperator,
+
+    /// Whether edges are detected on a virtual low-resolution grid.
+    pub pixelate: bool,
+    /// Whether the edge mask is blended with the reprojected mask of the previous frame.
+    pub temporal: bool,
+    /// Whether edges are limited to moving pixels, see [`EdgeDetection::motion_threshold`].
+    pub motion_gate: bool,
+    /// Whether the edge mask is dilated in a second pass before compositing.
+    pub dilate: bool,
+    /// Whether the edges pool into the scene, read from the edge mask like `dilate`.
+    pub pooling: bool,
+    /// Whether the edges bleed a halo into the scene, read from the edge mask like `dilate`.
+    pub halo: bool,
+    /// Whether this is the pipeline of the mask pass, which only writes the edge mask.
+    /// Only used together with `dilate`.
+    pub mask: bool,
+
+    /// Whether the depth threshold is adjusted at steep viewing angles.
+    pub steep_angle_correction: bool,
+
+    /// How the edges are composited with the scene.
+    pub blend_mode: EdgeBlendMode,
+    /// Whether the edge color follows the surface normal, see [`EdgeDetection::edge_color_lit`].
+    pub edge_lighting: bool,
+    /// Where the color of the edges comes from.
+    pub edge_color_source: EdgeColorSource,
+    /// How the edge color follows the exposure of the scene.
+    pub exposure_compensation: ExposureCompensation,
+    /// Whether [`EdgeDetection::uv_distortion_strength`] is in pixels.
+    pub uv_distortion_in_pixels: bool,
+    /// The space the distortion noise is laid out in.
+    pub distortion_space: DistortionSpace,
+    /// Whether edges are shaded by their facing in screen space, see
+    /// [`EdgeDetection::contour_strength`].
+    pub contour: bool,
+    /// Whether edges fade out with the distance.
+    pub distance_fade: bool,
+    /// Whether edges near the camera fade out.
+    pub near_fade: bool,
+    /// Whether the pixels nearer than [`EdgeDetection::viewmodel_depth_cutoff`] and their
+    /// neighbors have no edges.
+    pub viewmodel_cutoff: bool,
+    /// Whether edges fade out towards the edges of the screen, see
+    /// [`EdgeDetection::radial_falloff`].
+    pub radial_falloff: bool,
+    /// Whether the radial falloff is inverted, fading edges out towards the screen center.
+    pub radial_invert: bool,
+    /// Whether the effect only applies within a range of view distances.
+    pub effect_depth_range: bool,
+    /// The shape of the world-space volume edges are limited to.
+    pub volume: Option<EdgeVolumeShape>,
+    /// Whether edges are limited to a range of world heights.
+    pub height_range: bool,
+    /// Whether the camera's [`DistanceFog`] is applied to the edge color.
+    pub fog: bool,
+    /// Whether edges are colored by the depth band of their surface.
+    pub depth_bands: bool,
+    /// Whether the edge color ramps across the screen.
+    pub edge_color_gradient: bool,
+    /// Whether the edges are duplicated at an offset as a drop shadow.
+    pub edge_shadow: bool,
+    /// Whether dark regions are hatched.
+    pub hatching: bool,
+    /// Whether non-edge regions are drawn as halftone dots.
+    pub halftone: bool,
+    /// Whether edges are drawn as stipple dots.
+    pub stipple: bool,
+    /// Whether edges fade out where they barely contrast with the scene behind them.
+    pub contrast_guard: bool,
+    /// Whether low-contrast edges switch to [`EdgeDetection::contrast_guard_color`] instead of
+    /// fading out.
+    pub contrast_guard_flip: bool,
+    /// Whether edges are drawn as an ordered-dither pattern.
+    pub dither: bool,
+    /// Whether non-edge pixels are replaced by a flat background color.
+    pub background_override: bool,
+    /// Whether non-edge pixels are tinted, see [`EdgeDetection::background_tint`].
+    pub background_tint: bool,
+    /// Whether edges against the background are drawn.
+    pub background_edges: bool,
+    /// Whether the thresholds are multiplied by a texture.
+    pub threshold_map: bool,
+    /// Whether the edge strength is multiplied by a texture, see [`EdgeDetection::mask`].
+    pub strength_mask: bool,
+    /// Whether the view has [`EdgeDetectionMarkerTextures`], i.e. a depth prepass and
+    /// [`EdgeDetectionMarkerPass`]. The textures are cleared when no entity with
+    /// [`NoEdgeDetection`] or [`EdgeDetectionTarget`] is visible, so the pipeline doesn't change
+    /// with the visible meshes.
+    pub marked_entities: bool,
+    /// Whether only the entities with [`EdgeDetectionTarget`] are outlined.
+    pub targets_only: bool,
+    /// Whether the silhouettes of transparent meshes are outlined.
+    pub transparent_outlines: bool,
+    /// Whether the silhouettes of targets behind other geometry are outlined, see
+    /// [`EdgeDetection::occluded_edge_color`].
+    pub xray_outlines: bool,
+    /// Whether the view has the parameter textures of [`EdgeDetection::mesh_params`].
+    pub mesh_params: bool,
+    /// Whether the thresholds rise where the normals are noisy, see
+    /// [`EdgeDetection::foliage_suppression`].
+    pub foliage_suppression: bool,
+    /// Whether an overlay texture is multiplied over the output.
+    pub overlay: bool,
+    /// Whether the output is darkened by horizontal scanlines.
+    pub scanlines: bool,
+    /// Whether the scene is output unmodified inside a screen rect.
+    pub exclusion_rect: bool,
+    /// Whether the effect is limited to a screen rect, also set for cameras with a viewport so
+    /// the effect stays within it.
+    pub screen_rect: bool,
+    /// Whether the left of the view shows the unmodified scene for comparison.
+    pub debug_split: bool,
+    /// What is written into the alpha channel of the output.
+    pub output_alpha: OutputAlpha,
+    /// Whether the gradient of the edges is written into a second color target.
+    pub gradient_output: bool,
+    /// Whether the distance to the nearest edge is written into a color target of the
+    /// second pass, see [`EdgeDetection::distance_field_radius`].
+    pub distance_field: bool,
+
+    /// Whether we're using HDR.
+    pub hdr: bool,
+    /// Whether the render target is multisampled.
+    pub multisampled: bool,
+    /// The projection type of view
+    pub projection: ProjectionType,
+}
+
+impl EdgeDetectionKey {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        edge_detection: &EdgeDetection,
+        hdr: bool,
+        multisampled: bool,
+        projection: Option<&Projection>,
+        fog: bool,
+        marked_entities: bool,
+        viewport: bool,
+        deferred: bool,
+    ) -> Self {
+        Self {
+            enable_depth: edge_detection.enable_depth,
+            enable_normal: edge_detection.enable_normal,
+            normal_edge_spacing: edge_detection.enable_normal
+                && edge_detection.normal_edge_min_spacing > 0,
+            thin_edges: edge_detection.thin_edges,
+            normal_space: edge_detection.normal_space,
+            enable_color: edge_detection.enable_color,
+            chroma_only: edge_detection.enable_color && edge_detection.chroma_only,
+            color_edge_space: edge_detection.color_edge_space,
+            enable_object_id: edge_detection.enable_object_id,
+            enable_material_id: edge_detection.enable_material_id && deferred,
+            curvature_edges: edge_detection.curvature_texture.is_some(),
+            secondary_depth: edge_detection.enable_depth
+                && edge_detection.secondary_depth.is_some(),
+            combine_mode: edge_detection.combine_mode,
+            edge_logic: edge_detection.edge_logic,
+            depth_operator: edge_detection.depth_operator,
+            normal_operator: edge_detection.normal_operator,
+            color_operator: edge_detection.color_operator,
+
+            pixelate: edge_detection.pixelate_grid.is_some(),
+            temporal: edge_detection.temporal_blend > 0.0,
+            motion_gate: edge_detection.motion_threshold > 0.0,
+            dilate: edge_detection.uses_mask_pass(),
+            pooling: edge_detection.pooling(),
+            halo: edge_detection.halo(),
+            mask: false,
+
+            steep_angle_correction: edge_detection.enable_depth
+                && edge_detection.enable_steep_angle_correction,
+
+            blend_mode: edge_detection.blend_mode,
+            edge_lighting: edge_detection.edge_lighting(),
+            edge_color_source: edge_detection.edge_color_source,
+            exposure_compensation: edge_detection.exposure_compensation,
+            uv_distortion_in_pixels: edge_detection.uv_distortion_in_pixels,
+            distortion_space: edge_detection.distortion_space,
+            contour: edge_detection.contour_strength != 0.0,
+            distance_fade: edge_detection.distance_fade(),
+            near_fade: edge_detection.near_fade(),
+            viewmodel_cutoff: edge_detection.viewmodel_depth_cutoff.is_some(),
+            radial_falloff: edge_detection.radial_falloff > 0.0,
+            radial_invert: edge_detection.radial_falloff > 0.0 && edge_detection.radial_invert,
+            effect_depth_range: edge_detection.effect_depth_range.is_some(),
+            volume: edge_detection.volume.map(|volume| volume.shape()),
+            height_range: edge_detection.height_range.is_some(),
+            fog: fog && edge_detection.respect_fog,
+            depth_bands: !edge_detection.depth_bands.is_empty(),
+            edge_color_gradient: edge_detection.edge_color_gradient.is_some(),
+            edge_shadow: edge_detection.edge_shadow_offset != Vec2::ZERO,
+            hatching: edge_detection.hatching.is_some(),
+            halftone: edge_detection.halftone.is_some(),
+            stipple: edge_detection.stipple.is_some(),
+            contrast_guard: edge_detection.contrast_guard > 0.0,
+            contrast_guard_flip: edge_detection.contrast_guard > 0.0
+                && edge_detection.contrast_guard_color.is_some(),
+            dither: edge_detection.dither,
+            background_override: edge_detection.background_override.is_some(),
+            background_tint: edge_detection.background_tint.is_some(),
+            background_edges: edge_detection.background_edges,
+            threshold_map: edge_detection.threshold_map.is_some(),
+            strength_mask: edge_detection.mask.is_some(),
+            marked_entities,
+            targets_only: edge_detection.targets_only,
+            transparent_outlines: edge_detection.transparent_outlines,
+            xray_outlines: edge_detection.occluded_edge_color.is_some() && marked_entities,
+            mesh_params: edge_detection.mesh_params && marked_entities,
+            foliage_suppression: edge_detection.foliage_suppression > 0.0,
+            overlay: edge_detection.overlay_texture.is_some(),
+            scanlines: edge_detection.scanline_strength > 0.0,
+            exclusion_rect: edge_detection.exclusion_rect.is_some(),
+            screen_rect: edge_detection.screen_rect.is_some() || viewport,
+            debug_split: edge_detection.debug_split.is_some(),
+            gradient_output: edge_detection.gradient_output,
+            distance_field: edge_detection.distance_field_radius > 0,
+            output_alpha: edge_detection.output_alpha,
+
+            hdr,
+            multisampled,
+            projection: projection.into(),
+        }
+    }
+
+    /// Whether the pipelines specialized with this key read the textures of the second bind
+    /// group, see [`EdgeDetectionPipeline::marker_layout`].
+    pub fn uses_marker_bind_group(&self) -> bool {
+        self.marked_entities || self.xray_outlines || self.curvature_edges || self.mesh_params
+    }
+
+    /// The format of the view target the pipelines specialized with this key write into.
+    pub fn view_target_format(&self) -> TextureFormat {
+        if self.hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::bevy_default()
+        }
+    }
+
+    /// The shader defs of the pipelines specialized with this key.
+    pub fn shader_defs(&self) -> Vec<ShaderDefVal> {
+        // Destructured exhaustively, so a field added to the key can't be forgotten here. The
+        // passes a view runs only select the entry point and targets, see `specialize`.
+        let Self {
+            enable_depth,
+            enable_normal,
+            normal_edge_spacing,
+            thin_edges,
+            normal_space,
+            enable_color,
+            chroma_only,
+            color_edge_space,
+            enable_object_id,
+            enable_material_id,
+            curvature_edges,
+            secondary_depth,
+            combine_mode,
+            edge_logic,
+            depth_operator,
+            normal_operator,
+            color_operator,
+            pixelate,
+            temporal,
+            motion_gate,
+            dilate: _,
+            pooling,
+            halo,
+            mask: _,
+            steep_angle_correction,
+            blend_mode,
+            edge_lighting,
+            edge_color_source,
+            exposure_compensation,
+            uv_distortion_in_pixels,
+            distortion_space,
+            contour,
+            distance_fade,
+            near_fade,
+            viewmodel_cutoff,
+            radial_falloff,
+            radial_invert,
+            effect_depth_range,
+            volume,
+            height_range,
+            fog,
+            depth_bands,
+            edge_color_gradient,
+            edge_shadow,
+            hatching,
+            halftone,
+            stipple,
+            contrast_guard,
+            contrast_guard_flip,
+            dither,
+            background_override,
+            background_tint,
+            background_edges,
+            threshold_map,
+            strength_mask,
+            marked_entities,
+            targets_only,
+            transparent_outlines,
+            xray_outlines,
+            mesh_params,
+            foliage_suppression,
+            overlay,
+            scanlines,
+            exclusion_rect,
+            screen_rect,
+            debug_split,
+            output_alpha,
+            gradient_output,
+            distance_field,
+            hdr,
+            multisampled,
+            projection,
+        } = *self;
+
+        let mut shader_defs = vec![];
+
+        if enable_depth {
+            shader_defs.push("ENABLE_DEPTH".into());
+        }
+
+        if steep_angle_correction {
+            shader_defs.push("STEEP_ANGLE_CORRECTION".into());
+        }
+
+        if secondary_depth {
+            shader_defs.push("SECONDARY_DEPTH".into());
+        }
+
+        match edge_logic {
+            EdgeLogic::Any => (),
+            EdgeLogic::All => shader_defs.push("EDGE_LOGIC_ALL".into()),
+            EdgeLogic::DepthOrNormal => shader_defs.push("EDGE_LOGIC_DEPTH_OR_NORMAL".into()),
+        };
+
+        for (detector, operator) in [
+            ("DEPTH", depth_operator),
+            ("NORMAL", normal_operator),
+            ("COLOR", color_operator),
+        ] {
+            match operator {
+                EdgeOperator::Sobel => (),
+                EdgeOperator::Scharr => {
+                    shader_defs.push(format!("{detector}_OPERATOR_SCHARR").into())
+                }
+                EdgeOperator::Roberts => {
+                    shader_defs.push(format!("{detector}_OPERATOR_ROBERTS").into())
+                }
+            }
+        }
+
+        match combine_mode {
+            EdgeCombineMode::Max => (),
+            EdgeCombineMode::Add => shader_defs.push("EDGE_COMBINE_ADD".into()),
+            EdgeCombineMode::Average => shader_defs.push("EDGE_COMBINE_AVERAGE".into()),
+        };
+
+        if enable_normal {
+            shader_defs.push("ENABLE_NORMAL".into());
+        }
+
+        if normal_edge_spacing {
+            shader_defs.push("NORMAL_EDGE_SPACING".into());
+        }
+
+        if thin_edges {
+            shader_defs.push("THIN_EDGES".into());
+        }
+
+        if enable_normal && normal_space == NormalSpace::View {
+            shader_defs.push("NORMAL_SPACE_VIEW".into());
+        }
+
+        if enable_color {
+            shader_defs.push("ENABLE_COLOR".into());
+        }
+
+        if chroma_only {
+            shader_defs.push("COLOR_CHROMA_ONLY".into());
+        } else if enable_color && color_edge_space == ColorEdgeSpace::Luminance {
+            shader_defs.push("COLOR_EDGE_LUMINANCE".into());
+        }
+
+        if enable_object_id {
+            shader_defs.push("ENABLE_OBJECT_ID".into());
+        }
+
+        if enable_material_id {
+            shader_defs.push("ENABLE_MATERIAL_ID".into());
+        }
+
+        if curvature_edges {
+            shader_defs.push("CURVATURE_EDGES".into());
+        }
+
+        match blend_mode {
+            EdgeBlendMode::Overlay => (),
+            EdgeBlendMode::Invert => shader_defs.push("BLEND_MODE_INVERT".into()),
+            EdgeBlendMode::Duotone => shader_defs.push("BLEND_MODE_DUOTONE".into()),
+        };
+
+        if temporal {
+            shader_defs.push("TEMPORAL".into());
+        }
+
+        if motion_gate {
+            shader_defs.push("MOTION_GATE".into());
+        }
+
+        if temporal || motion_gate {
+            shader_defs.push("MOTION_VECTORS".into());
+        }
+
+        if edge_lighting {
+            shader_defs.push("EDGE_LIGHTING".into());
+        }
+
+        if uv_distortion_in_pixels {
+            shader_defs.push("UV_DISTORTION_PIXELS".into());
+        }
+
+        match distortion_space {
+            DistortionSpace::Screen => (),
+            DistortionSpace::World => shader_defs.push("DISTORTION_WORLD".into()),
+        };
+
+        if distance_fade {
+            shader_defs.push("DISTANCE_FADE".into());
+        }
+
+        if near_fade {
+            shader_defs.push("NEAR_FADE".into());
+        }
+
+        if viewmodel_cutoff {
+            shader_defs.push("VIEWMODEL_CUTOFF".into());
+        }
+
+        if radial_falloff {
+            shader_defs.push("RADIAL_FALLOFF".into());
+        }
+
+        if radial_invert {
+            shader_defs.push("RADIAL_INVERT".into());
+        }
+
+        if effect_depth_range {
+            shader_defs.push("EFFECT_DEPTH_RANGE".into());
+        }
+
+        match volume {
+            None => (),
+            Some(EdgeVolumeShape::Sphere) => shader_defs.push("EDGE_VOLUME".into()),
+            Some(EdgeVolumeShape::Box) => {
+                shader_defs.push("EDGE_VOLUME".into());
+                shader_defs.push("EDGE_VOLUME_BOX".into());
+            }
+        };
+
+        if height_range {
+            shader_defs.push("HEIGHT_RANGE".into());
+        }
+
+        if fog {
+            shader_defs.push("FOG".into());
+        }
+
+        if contour {
+            shader_defs.push("CONTOUR".into());
+        }
+
+        if pooling {
+            shader_defs.push("POOLING".into());
+        }
+
+        if halo {
+            shader_defs.push("HALO".into());
+        }
+
+        match edge_color_source {
+            EdgeColorSource::Fixed => (),
+            EdgeColorSource::NearerObject => {
+                shader_defs.push("EDGE_COLOR_NEARER_OBJECT".into());
+            }
+        };
+
+        if depth_bands {
+            shader_defs.push("DEPTH_BANDS".into());
+        }
+
+        if edge_color_gradient {
+            shader_defs.push("EDGE_COLOR_GRADIENT".into());
+        }
+
+        match exposure_compensation {
+            ExposureCompensation::None => (),
+            ExposureCompensation::SceneLuminance => {
+                shader_defs.push("EXPOSURE_SCENE_LUMINANCE".into());
+            }
+            ExposureCompensation::CameraExposure => {
+                shader_defs.push("EXPOSURE_CAMERA".into());
+            }
+        };
+
+        if edge_shadow {
+            shader_defs.push("EDGE_SHADOW".into());
+        }
+
+        if hatching {
+            shader_defs.push("HATCHING".into());
+        }
+
+        if halftone {
+            shader_defs.push("HALFTONE".into());
+        }
+
+        if stipple {
+            shader_defs.push("STIPPLE".into());
+        }
+
+        if contrast_guard {
+            shader_defs.push("CONTRAST_GUARD".into());
+        }
+
+        if contrast_guard_flip {
+            shader_defs.push("CONTRAST_GUARD_FLIP".into());
+        }
+
+        if pixelate {
+            shader_defs.push("PIXELATE".into());
+        }
+
+        if dither {
+            shader_defs.push("DITHER".into());
+        }
+
+        if background_override {
+            shader_defs.push("BACKGROUND_OVERRIDE".into());
+        }
+
+        if background_tint {
+            shader_defs.push("BACKGROUND_TINT".into());
+        }
+
+        if threshold_map {
+            shader_defs.push("THRESHOLD_MAP".into());
+        }
+
+        if strength_mask {
+            shader_defs.push("STRENGTH_MASK".into());
+        }
+
+        if marked_entities {
+            shader_defs.push("MARKED_ENTITIES".into());
+        }
+
+        if targets_only {
+            shader_defs.push("TARGETS_ONLY".into());
+        }
+
+        if transparent_outlines {
+            shader_defs.push("TRANSPARENT_OUTLINES".into());
+        }
+
+        if xray_outlines {
+            shader_defs.push("XRAY_OUTLINES".into());
+        }
+
+        if mesh_params {
+            shader_defs.push("MESH_PARAMS".into());
+        }
+
+        if foliage_suppression {
+            shader_defs.push("FOLIAGE_SUPPRESSION".into());
+        }
+
+        if !background_edges {
+            shader_defs.push("NO_BACKGROUND_EDGES".into());
+        }
+
+        if overlay {
+            shader_defs.push("OVERLAY".into());
+        }
+
+        if scanlines {
+            shader_defs.push("SCANLINES".into());
+        }
+
+        if exclusion_rect {
+            shader_defs.push("EXCLUSION_RECT".into());
+        }
+
+        if screen_rect {
+            shader_defs.push("SCREEN_RECT".into());
+        }
+
+        if debug_split {
+            shader_defs.push("DEBUG_SPLIT".into());
+        }
+
+        if gradient_output {
+            shader_defs.push("GRADIENT_OUTPUT".into());
+        }
+
+        if distance_field {
+            shader_defs.push("DISTANCE_FIELD".into());
+        }
+
+        match output_alpha {
+            OutputAlpha::Opaque => (),
+            OutputAlpha::PreserveSource => shader_defs.push("PRESERVE_ALPHA".into()),
+            OutputAlpha::EdgeStrength => shader_defs.push("EDGE_STRENGTH_ALPHA".into()),
+        };
+
+        if hdr {
+            shader_defs.push("HDR".into());
+        }
+
+        if multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+
+        match projection {
+            ProjectionType::Perspective => shader_defs.push("VIEW_PROJECTION_PERSPECTIVE".into()),
+            ProjectionType::Orthographic => shader_defs.push("VIEW_PROJECTION_ORTHOGRAPHIC".into()),
+            _ => (),
+        };
+
+        shader_defs
+    }
+}
+
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+// Fields missing from a preset keep their default value, texture handles are never serialized.
+#[cfg_attr(feature = "serialize", serde(default))]
+#[require(EdgeDetectionStatus)]
+pub struct EdgeDetection {
+    /// Depth threshold, used to detect edges with significant depth changes.
+    /// Areas where the depth variation exceeds this threshold will be marked as edges.
+    ///
+    /// Range: (0.0, inf)
+    pub depth_threshold: f32,
+    /// The minimum step in view depth, in world units, that is detected as a depth edge, e.g.
+    /// `Some(0.1)` to only outline surfaces at least 10 cm in front of what's behind them. This
+    /// replaces `depth_threshold`, which is compared against the Sobel response of the depth and
+    /// so is four times the step it detects.
+    ///
+    /// `None` uses `depth_threshold`.
+    pub depth_threshold_world: Option<f32>,
+    /// Normal threshold, used to detect edges with significant normal direction changes.
+    /// Areas where the normal direction variation exceeds this threshold will be marked as edges.
+    ///
+    /// Range: (0.0, inf)
+    pub normal_threshold: f32,
+    /// Color threshold, used to detect edges with significant color changes.
+    /// Areas where the color variation exceeds this threshold will be marked as edges.
+    ///
+    /// Range: (0.0, inf)
+    pub color_threshold: f32,
+
+    /// Thickness of the edges detected based on depth variations.
+    /// This value controls the width of the edges drawn when depth-based edge detection is enabled.
+    /// Higher values result in thicker edges.
+    ///
+    /// Range: (0.0, inf)
+    pub depth_thickness: f32,
+    /// Thickness of the edges detected based on normal direction variations.
+    /// This value controls the width of the edges drawn when normal-based edge detection is enabled.
+    /// Higher values result in thicker edges.
+    ///
+    /// Range: (0.0, inf)
+    pub normal_thickness: f32,
+    /// Thickness of the edges detected based on color variations.
+    /// This value controls the width of the edges drawn when color-based edge detection is enabled.
+    /// Higher values result in thicker edges.
+    ///
+    /// Range: (0.0, inf)
+    pub color_thickness: f32,
+
+    /// How quickly the thicknesses taper off with the distance of the surface, for consistent
+    /// line widths in a technical-drawing look. Each thickness is divided by
+    /// `1.0 + thickness_falloff * distance`, with the view-space distance reconstructed from the
+    /// depth prepass, and is clamped to at least one texel, so near edges are thick and far edges
+    /// thin out to a single pixel (or grid cell, see [`EdgeDetection::pixelate_grid`]).
+    ///
+    /// `0.0` disables the falloff. Reads the depth prepass.
+    pub thickness_falloff: f32,
+
+    /// View distance in world units at which edges start fading out, reaching zero strength at
+    /// `distance_fade_end`, so distant geometry doesn't turn into a mess of lines. The distance
+    /// is reconstructed from the depth prepass for perspective and orthographic projections.
+    /// The fade applies to the detected edges, before dilation, so thickened lines fade as a
+    /// whole.
+    ///
+    /// Setting both to `0.0` disables the fade. Reads the depth prepass.
+    pub distance_fade_start: f32,
+    /// View distance in world units beyond which edges are hidden, see `distance_fade_start`.
+    pub distance_fade_end: f32,
+    /// View distance in world units below which edges are hidden, e.g. so a first-person camera
+    /// pressed against a wall doesn't fill the screen with the edges of surface detail. Edges
+    /// fade back in up to `near_fade_end`. Composes with `distance_fade_start`.
+    ///
+    /// Setting both to `0.0` disables the fade. Reads the depth prepass.
+    pub near_fade_start: f32,
+    /// View distance in world units from which edges are drawn at full strength, see
+    /// `near_fade_start`.
+    pub near_fade_end: f32,
+    /// View distance in world units below which surfaces are left out of edge detection
+    /// entirely, for first-person viewmodels rendered right in front of the camera. Unlike the
+    /// near fade, pixels within the edge thickness of such a surface have no edges either, so
+    /// the edges of the scene behind don't bleed onto the viewmodel and no silhouette is drawn
+    /// around it. Dilated and pooled edges don't grow onto it.
+    ///
+    /// `None` disables the cutoff. Reads the depth prepass.
+    pub viewmodel_depth_cutoff: Option<f32>,
+    /// The range of view distances in world units, `(min, max)`, the effect applies to. Outside
+    /// of it the scene is output unmodified, e.g. to leave the skybox and far terrain untouched
+    /// or to only outline what's within reach of an inspection tool. Edges outside the range are
+    /// dropped before dilation and pooling, so they don't leak into it either.
+    ///
+    /// `None` applies the effect at every distance. Reads the depth prepass.
+    pub effect_depth_range: Option<(f32, f32)>,
+    /// A world-space volume edges are limited to, e.g. a growing sphere around the player for a
+    /// scanner pulse. Animate it by changing the volume from a system, it's uploaded every frame.
+    /// The world position of a pixel is reconstructed from the depth prepass, and the
+    /// background is never inside.
+    ///
+    /// `None` draws edges everywhere. Reads the depth prepass.
+    pub volume: Option<EdgeVolume>,
+    /// Width in world units of the band inside the boundary of `volume` over which edges fade
+    /// in, `0.0` for a hard cut.
+    pub volume_falloff: f32,
+    /// The range of world heights (Y) in world units, `(min, max)`, edges are drawn within, e.g.
+    /// to hide edge clutter on ceilings and roofs in an architectural viewer. Outside of it the
+    /// scene is output unmodified. The height of a pixel is reconstructed from the depth prepass
+    /// like for `volume`, and the background is never inside.
+    ///
+    /// `None` draws edges at every height. Reads the depth prepass.
+    pub height_range: Option<(f32, f32)>,
+    /// Height in world units over which edges fade in at both bounds of `height_range`, `0.0`
+    /// for a hard cut.
+    pub height_falloff: f32,
+    /// Whether the [`DistanceFog`] of the camera is applied to the edge color, so edges fade into
+    /// the fog like the surfaces they outline instead of cutting through it. Has no effect on
+    /// cameras without fog. The fog is evaluated at the nearest surface around the edge, and the
+    /// directional light scattering of the fog is ignored.
+    ///
+    /// Reads the depth prepass, which is inserted on cameras with fog.
+    pub respect_fog: bool,
+
+    /// Luminance variance the 3x3 neighborhood of a pixel must reach for edges to be detected
+    /// there when its depth is constant. Flat fills, such as the clear color between objects,
+    /// then never get spurious edges, while silhouettes against them are kept since their depth
+    /// varies.
+    ///
+    /// `0.0` disables the gate. Reads the depth prepass.
+    pub min_neighborhood_variance: f32,
+    /// How strongly the thresholds rise where the normals are noisy, e.g. on alpha-masked
+    /// foliage, whose leaves would otherwise each get an outline and turn into noise. The noise
+    /// is how much more the normals vary along 5 taps across and along the pixel than between
+    /// the outer taps, which is 0.0 on flat and curved surfaces and at single creases, so the
+    /// thresholds only rise where the normals change direction back and forth. All thresholds
+    /// are multiplied by `1.0 + foliage_suppression * noise`.
+    ///
+    /// `0.0` disables the suppression. Reads the normal prepass.
+    pub foliage_suppression: f32,
+    /// Whether edges are drawn where geometry meets the background, i.e. the clear color or a
+    /// skybox. When `false`, edges whose kernels touch a pixel without geometry are dropped, so
+    /// only edges between objects remain. The background is told apart by its depth at the far
+    /// plane, which also holds for an infinite far plane.
+    ///
+    /// `false` reads the depth prepass.
+    pub background_edges: bool,
+    /// The prepass depth up to which a pixel counts as background, for `background_edges` and
+    /// the features that skip the background. Reversed-Z puts the far plane at a depth of 0, and
+    /// with a perspective projection the depth is `near / distance`, so the default of `1e-6`
+    /// treats surfaces beyond 100 000 units as background with Bevy's default near plane of
+    /// 0.1. Raise it if geometry at a finite far plane, e.g. a backdrop, should count as well;
+    /// `0.0` only counts the cleared depth.
+    pub background_depth_epsilon: f32,
+
+    /// Distance in pixels within which a normal edge is suppressed if a stronger one lies across it.
+    /// On high-poly meshes the normal detector fires on many nearby face boundaries; this keeps
+    /// only the dominant crease of a cluster by a local-maximum check along the gradient
+    /// direction. The cost grows linearly with the distance.
+    ///
+    /// `0` disables the suppression.
+    pub normal_edge_min_spacing: u32,
+    /// Thins the edges of all detectors down to a single pixel, for crisp line art. An edge pixel
+    /// is only kept where its response is a local maximum across the edge, the non-maximum
+    /// suppression step of the Canny edge detector. The detectors run on two more pixels for
+    /// this, which triples the cost of detection.
+    pub thin_edges: bool,
+    /// The space normals are compared in by the normal detector, see [`NormalSpace`].
+    pub normal_space: NormalSpace,
+
+    /// Resolution of a virtual low-resolution grid edges are detected on, for pixel-art looks
+    /// rendered at native resolution (e.g. `UVec2::new(320, 180)`).
+    /// Every pixel samples the prepass textures at the center of its grid cell and gradients are
+    /// taken between neighboring cells, so edges are chunky and aligned to the fake pixels.
+    ///
+    /// If `None`, edges are detected at the resolution of the view.
+    pub pixelate_grid: Option<UVec2>,
+
+    /// Number of discrete strength steps above zero edges are snapped to, for screen-printed line
+    /// weights. An edge gets one step per multiple of its threshold exceeded by the gradient, e.g.
+    /// with 3 steps a gradient of 1.5 times the threshold draws a line at 1/3 strength and 3 times
+    /// one at full strength.
+    ///
+    /// `0` draws every edge at full strength.
+    pub strength_steps: u32,
+    /// Exponent of the response curve applied to the edge strength before compositing, as
+    /// `pow(edge, edge_response_gamma)`. Values above 1.0 thin out partial edges into crisper
+    /// lines, values below 1.0 broaden them into softer ones. Only partial strengths are
+    /// affected, e.g. from `strength_steps`, `rim_modulation` or temporal stabilization.
+    /// It's applied after the response is thresholded, so on its own it's a no-op on the binary
+    /// edges of the default settings, which are either 0.0 or 1.0.
+    ///
+    /// Range: (0.0, inf)
+    pub edge_response_gamma: f32,
+
+    /// Exponent of a rim-style modulation of the edge strength, for painterly rim lines.
+    /// Edges are multiplied by `pow(1 - saturate(dot(n, v)), rim_modulation)`, so they intensify
+    /// where the surface turns away from the camera and soften on surfaces facing it.
+    /// Unlike [`EdgeDetection::steep_angle_multiplier`], this is an artistic control.
+    ///
+    /// `0.0` disables the modulation.
+    pub rim_modulation: f32,
+
+    /// Rate at which edges fade out with the distance from the screen center, for focus or
+    /// tunnel vision effects. The distance is corrected for the aspect ratio, so the fade is
+    /// circular, and is 1.0 in the corners. Edges are multiplied by
+    /// `1 - saturate(radial_falloff * distance)`, so at 1.0 they reach zero in the corners and
+    /// at 2.0 halfway there.
+    ///
+    /// `0.0` disables the falloff.
+    pub radial_falloff: f32,
+    /// Inverts `radial_falloff`, so edges are only drawn towards the periphery and fade out
+    /// towards the screen center.
+    pub radial_invert: bool,
+
+    /// Radius in pixels by which detected edges are grown before compositing.
+    /// When greater than 0, the edge mask is written by a first pass and dilated with a max filter
+    /// in a second pass, which gives uniformly thick, clean lines. Unlike the thickness
+    /// parameters, which widen the sampling footprint of the Sobel filter, this never misses or
+    /// doubles lines. The cost grows with the square of the radius.
+    ///
+    /// `0` disables dilation.
+    pub dilate_radius: u32,
+
+    /// Radius in pixels over which the edges bleed a soft darkening into the scene, like pigment
+    /// pooling near the edges of a watercolor. The edge mask is spread with a falloff by the
+    /// second pass of `dilate_radius`, at a fixed cost whatever the radius, and multiplied over
+    /// the scene independently of the lines. Set the alpha of `edge_color` to 0 to get the
+    /// pooling without the lines.
+    pub pooling_radius: f32,
+    /// How strongly the pooling darkens the scene towards `pooling_color`.
+    ///
+    /// `0.0` disables pooling.
+    pub pooling_strength: f32,
+    /// Color multiplied over the scene where pigment pools, see `pooling_radius`.
+    pub pooling_color: Color,
+
+    /// Radius in pixels over which the edges bleed a soft glow of their line color into the
+    /// scene. The edge mask is blurred with a falloff by the second pass of `dilate_radius` and
+    /// added over the output, so unlike [`EdgePlacement::BeforeBloom`] this works on LDR cameras
+    /// and without Bevy's `Bloom`. The halo takes the color of the line it surrounds, including
+    /// [`EdgeDetectionColor`] and `occluded_edge_color`.
+    ///
+    /// The blur reads `(2r+1)^2` texels per pixel, so the radius is clamped to
+    /// [`MAX_HALO_RADIUS`], 289 texels.
+    ///
+    /// `0` disables the halo.
+    pub halo_radius: u32,
+    /// How much of the line color the halo adds to the scene, see `halo_radius`. The blur spreads
+    /// thin lines out, so values above 1.0 are useful for a visible halo around them.
+    ///
+    /// `0.0` disables the halo.
+    pub halo_strength: f32,
+
+    /// Whether the depth threshold is adjusted at steep viewing angles, see
+    /// `steep_angle_threshold`. Turn it off for raw depth thresholding when the correction
+    /// over-suppresses legitimate gentle slopes, e.g. on flat terrain.
+    pub enable_steep_angle_correction: bool,
+
+    /// Steep angle threshold, used to adjust the depth threshold when viewing surfaces at steep angles.
+    /// When the angle between the view direction and the surface normal is very steep, the depth gradient
     /// can appear artificially large, causing non-edge regions to be mistakenly detected as edges.
     /// This threshold defines the angle at which the depth threshold adjustment begins to take effect.
     ///
@@ -394,17 +3306,339 @@ pub struct EdgeDetection {
     /// Frequency of UV distortion applied to the edge detection process.
     /// This controls how often the distortion effect repeats across the UV coordinates.
     /// Higher values result in more frequent distortion patterns.
+    ///
+    /// The noise is laid out in pixels scaled by the larger side of the view, so it's isotropic
+    /// whatever the aspect ratio.
     pub uv_distortion_frequency: Vec2,
 
     /// Strength of UV distortion applied to the edge detection process.
     /// This controls the intensity of the distortion effect.
     /// Higher values result in more pronounced distortion.
+    ///
+    /// A fraction of the width and height of the view, see `uv_distortion_in_pixels`.
     pub uv_distortion_strength: Vec2,
 
+    /// Frequency of a second distortion octave, summed with the first one for a richer wobble.
+    /// Usually a few times `uv_distortion_frequency`, with a smaller strength.
+    pub uv_distortion_frequency2: Vec2,
+
+    /// Strength of the second distortion octave, in the same unit as `uv_distortion_strength`.
+    /// Zero by default, which turns the octave off.
+    pub uv_distortion_strength2: Vec2,
+
+    /// Whether `uv_distortion_strength` is in pixels instead of a fraction of the view size.
+    /// As a fraction, the displacement is stretched along the longer side of wide views, e.g.
+    /// horizontally on a 21:9 monitor. In pixels, it's isotropic and the same on every
+    /// resolution. Off by default so scenes tuned on a given aspect ratio keep their look.
+    pub uv_distortion_in_pixels: bool,
+
+    /// The space the distortion noise is laid out in.
+    pub distortion_space: DistortionSpace,
+
+    /// Seed of the procedural noise, such as the UV distortion. The noise only depends on the
+    /// seed, so a static scene renders identically with the same seed, e.g. for golden-image
+    /// tests and reproducible captures. Different seeds shift the noise pattern.
+    pub noise_seed: u32,
+
     /// Edge color, used to draw the detected edges.
     /// Typically a high-contrast color (e.g., red or black) to visually highlight the edges.
+    ///
+    /// The color is converted to [`LinearRgba`] without clamping. When the view renders to an HDR
+    /// target, components greater than 1.0 (e.g. `LinearRgba::rgb(4.0, 1.0, 0.5).into()`) are
+    /// written as-is, so bright edges can be picked up by passes running after the edge pass.
+    /// On LDR targets the color is clamped to `[0.0, 1.0]`.
+    ///
+    /// The alpha channel is the opacity of the edges.
     pub edge_color: Color,
 
+    /// Whether the colors of the edges are written to the target in linear space.
+    /// Bevy's view targets are linear (HDR) or decode sRGB on write, so the colors are converted
+    /// to [`LinearRgba`]. When the edge pass renders into a target that stores values unconverted,
+    /// set this to `false` and the sRGB components of the colors are written as-is.
+    ///
+    /// Applies to every color of the effect, e.g. [`EdgeDetection::edge_color`] and
+    /// [`EdgeDetection::background_override`].
+    pub color_space_is_linear: bool,
+
+    /// Where the color of the edges comes from. [`EdgeDetection::edge_color_lit`] takes
+    /// precedence while it's enabled.
+    pub edge_color_source: EdgeColorSource,
+    /// Fraction by which the scene color is darkened when the edge color is taken from the
+    /// scene, see [`EdgeColorSource::NearerObject`].
+    ///
+    /// Range: [0.0, 1.0]
+    pub nearer_object_darkening: f32,
+
+    /// Colors of the edges by the view distance of their surface, for topographic or scanner
+    /// looks. Each entry is the distance in world units a band starts at and its color; a band
+    /// ends where the next one starts, so colors change in hard steps. Edges nearer than every
+    /// band keep `edge_color`, whose alpha remains the opacity of the edges.
+    ///
+    /// The entries may be in any order. Only the first [`MAX_DEPTH_BANDS`] are used.
+    /// Reads the depth prepass. [`EdgeDetection::edge_color_lit`] and
+    /// [`EdgeColorSource::NearerObject`] take precedence.
+    pub depth_bands: Vec<(f32, Color)>,
+
+    /// Ramp of the edge color across the screen, independent of the scene, e.g. for title
+    /// screens. The color goes from the first color to the second along the screen-space
+    /// direction, x right and y up, and the ramp spans the whole view along it.
+    /// [`EdgeDetection::depth_bands`] and the other edge color sources take precedence.
+    ///
+    /// If `None`, edges take `edge_color`, whose alpha remains the opacity of the edges either
+    /// way.
+    pub edge_color_gradient: Option<(Color, Color, Vec2)>,
+
+    /// Screen-space direction, x right and y up, of the light of the contour shading, e.g.
+    /// `Vec2::new(-1.0, 1.0)` for light from the top left. See `contour_strength`.
+    pub contour_light_dir: Vec2,
+    /// Strength of the contour shading, for a drawn toon contour. Edges on the side of objects
+    /// facing `contour_light_dir` are lightened towards white and those on the far side darkened
+    /// towards black, by the dot product of the direction with the depth gradient across the edge.
+    ///
+    /// `0.0` disables the shading. Reads the depth prepass.
+    pub contour_strength: f32,
+
+    /// Minimum luminance difference between the edge color and the scene behind it for edges to
+    /// be drawn at full strength. Below it edges fade out, so black outlines don't pile up on
+    /// dark surfaces and white ones on bright skies, and one set of settings holds across day and
+    /// night scenes.
+    ///
+    /// `0.0` disables the guard.
+    ///
+    /// Range: [0.0, 1.0]
+    pub contrast_guard: f32,
+    /// Color low-contrast edges switch to instead of fading out, see `contrast_guard`,
+    /// e.g. white for black outlines over dark surfaces.
+    ///
+    /// If `None`, low-contrast edges fade out.
+    pub contrast_guard_color: Option<Color>,
+
+    /// Multiplier of the edge color on HDR targets, making edges emissive.
+    /// Values above 1.0 push the edges above the LDR range so passes running after the edge pass,
+    /// such as bloom, pick them up as glowing lines for neon looks. Ignored on LDR targets, where
+    /// the edge color is clamped as usual.
+    ///
+    /// Range: [0.0, inf)
+    pub edge_emissive_strength: f32,
+    /// How the edge color follows the exposure of the scene, so lines keep a consistent
+    /// perceived contrast on HDR cameras with changing exposure, see [`ExposureCompensation`].
+    pub exposure_compensation: ExposureCompensation,
+
+    /// Offset in pixels of a duplicate of the edges drawn under them in `edge_shadow_color`, for
+    /// sticker and cartoon styles. Positive values move the shadow right and down.
+    /// The edges are detected a second time at the offset, which roughly doubles the cost of the
+    /// pass; with `dilate_radius` the dilated mask is reused instead.
+    ///
+    /// `Vec2::ZERO` disables the shadow.
+    pub edge_shadow_offset: Vec2,
+    /// Color of the edge drop shadow, see `edge_shadow_offset`. The alpha channel is its opacity.
+    pub edge_shadow_color: Color,
+
+    /// Direction, in world space, edges on surfaces facing it take `edge_color_lit`.
+    /// Edges on surfaces facing away take `edge_color_shadow`, and the color is blended in between.
+    pub edge_light_direction: Vec3,
+    /// Edge color on surfaces facing `edge_light_direction`, e.g. a warm color for top-facing
+    /// surfaces in a "lit ink" look. Replaces the color of `edge_color`, whose alpha remains the
+    /// opacity of the edges.
+    ///
+    /// The normal-based edge color is disabled while `edge_color_lit` and `edge_color_shadow` are
+    /// equal.
+    pub edge_color_lit: Color,
+    /// Edge color on surfaces facing away from `edge_light_direction`, see `edge_color_lit`.
+    pub edge_color_shadow: Color,
+
+    /// Strength of the desaturation applied to the scene color away from edges.
+    /// The scene color is pulled toward grayscale everywhere except on and near edges, so outlines
+    /// pop against a muted background. The effect fades out smoothly toward the edges.
+    ///
+    /// `0.0` keeps the scene color, `1.0` makes the non-edge image grayscale.
+    ///
+    /// Range: [0.0, 1.0]
+    pub non_edge_desaturation: f32,
+
+    /// Number of color levels the non-edge scene color is snapped to, for a cel-shaded look.
+    /// Each channel is quantized in an approximately perceptual space before the edges are
+    /// composited on top. HDR colors are quantized after a cheap tonemap approximation, which is
+    /// inverted afterwards. The brightest HDR level sits half a step below white in the tonemapped
+    /// space, so it stays finite.
+    ///
+    /// `0` disables posterization.
+    pub posterize_levels: u32,
+
+    /// Weight of the previous frame's edge mask when it's blended with the current one.
+    /// The previous mask is reprojected with the motion vectors of the [`MotionVectorPrepass`],
+    /// which is added to the camera automatically, and accumulating it stabilizes thin edges that would
+    /// otherwise crawl and flicker while the camera moves. Higher values are steadier but make
+    /// edges trail behind fast motion.
+    ///
+    /// `0.0` disables temporal stabilization.
+    ///
+    /// Range: [0.0, 1.0)
+    pub temporal_blend: f32,
+    /// Minimum on-screen speed, in pixels per frame, of the pixels edges are drawn at, for
+    /// speed lines or impact frames on fast movers. The speed is read from the motion vectors of
+    /// the [`MotionVectorPrepass`], which is added to the camera automatically, and the fastest of
+    /// the kernel taps counts, so the silhouette of a mover is outlined on both of its sides.
+    ///
+    /// Camera motion moves static geometry on screen as well, so it's outlined while the camera
+    /// moves.
+    ///
+    /// `0.0` draws edges regardless of motion.
+    pub motion_threshold: f32,
+
+    /// How the detected edges are composited with the scene, see [`EdgeBlendMode`].
+    pub blend_mode: EdgeBlendMode,
+
+    /// Color of the pixels brighter than `duotone_threshold` with [`EdgeBlendMode::Duotone`].
+    pub duotone_light_color: Color,
+    /// Color of the pixels darker than `duotone_threshold` with [`EdgeBlendMode::Duotone`].
+    pub duotone_shadow_color: Color,
+    /// Linear luminance splitting the shadow and light colors with [`EdgeBlendMode::Duotone`].
+    ///
+    /// Range: [0.0, 1.0]
+    pub duotone_threshold: f32,
+    /// Width of the luminance range around `duotone_threshold` the two colors are blended over.
+    /// `0.0` gives a hard split.
+    pub duotone_softness: f32,
+
+    /// Screen-space hatch lines drawn in `edge_color` over dark regions of the scene, for a
+    /// pen-and-ink look, see [`HatchingSettings`].
+    ///
+    /// If `None`, no hatching is drawn.
+    pub hatching: Option<HatchingSettings>,
+
+    /// Halftone dots replacing the scene color of non-edge regions, bigger in darker areas, for
+    /// a newspaper print look, see [`HalftoneSettings`].
+    ///
+    /// If `None`, no halftone is applied.
+    pub halftone: Option<HalftoneSettings>,
+
+    /// Draw the edges as scattered dots instead of solid strokes, for an engraving look, see
+    /// [`StippleSettings`]. Weak edges get sparse dots, strong edges become near-solid.
+    ///
+    /// If `None`, edges are solid strokes.
+    pub stipple: Option<StippleSettings>,
+
+    /// Whether to draw edges as a 4x4 ordered-dither (Bayer) pattern keyed to the screen position
+    /// instead of blending them, for retro 1-bit aesthetics.
+    /// The response of the detectors sets the density of the pattern, so gradients below the
+    /// thresholds fade out as sparser dots instead of disappearing.
+    pub dither: bool,
+
+    /// Flat color replacing the scene color of non-edge pixels.
+    /// When set, the output only shows this background and the edges drawn on top of it in
+    /// `edge_color`, producing a blueprint / schematic look. Partial edge coverage blends between
+    /// this color and `edge_color` rather than the original scene.
+    ///
+    /// If `None`, the scene color is kept.
+    pub background_override: Option<Color>,
+
+    /// "Paper" color the scene color of non-edge pixels is tinted towards, for sketch-on-paper
+    /// looks. The tint fades out around edges like `non_edge_desaturation`.
+    ///
+    /// If `None`, no tint is applied.
+    pub background_tint: Option<Color>,
+    /// How far non-edge pixels are tinted towards `background_tint`.
+    /// `0.0` keeps the scene color, `1.0` replaces it with the tint.
+    ///
+    /// Range: [0.0, 1.0]
+    pub background_tint_strength: f32,
+
+    /// Single-channel texture whose red channel multiplies the thresholds per pixel, for
+    /// spatially varying sensitivity, e.g. a focus area or sensitive foreground regions.
+    /// It is stretched over the view; black detects every gradient, white keeps the thresholds.
+    ///
+    /// If `None`, the thresholds are the same everywhere.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub threshold_map: Option<Handle<Image>>,
+    /// Texture whose red channel multiplies the strength of the edges per pixel, e.g. rendered
+    /// from the UI or painted, to composite edges only where wanted. It is stretched over the
+    /// view, sampled at the UV of the pixel like the scene; black hides the edges, white keeps
+    /// them. Applied after dilation, so thickened lines are masked as well.
+    ///
+    /// If `None`, edges are drawn at full strength everywhere.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub mask: Option<Handle<Image>>,
+    /// Whether only the meshes with [`EdgeDetectionTarget`] are outlined, e.g. for selection
+    /// outlines. Everything else is left untouched, and no edges are drawn while no target is
+    /// visible.
+    pub targets_only: bool,
+    /// Whether the silhouettes of transparent meshes are outlined, e.g. glass, which alpha
+    /// blending keeps out of the prepasses. The meshes whose [`StandardMaterial`] has a
+    /// blending [`AlphaMode`] are rendered into [`EdgeDetectionMarkerTextures`], and a
+    /// full-strength line is drawn where one of them is in front of other geometry or the
+    /// background. Other materials are ignored.
+    ///
+    /// The transparent meshes hide the [`NoEdgeDetection`] and [`EdgeDetectionTarget`] meshes
+    /// behind them from the marker pass. Reads the depth prepass.
+    pub transparent_outlines: bool,
+    /// Color of the silhouettes of the meshes with [`EdgeDetectionTarget`] where other geometry
+    /// hides them, e.g. enemies seen through walls. The depth of the targets is rendered a second
+    /// time on its own into [`EdgeDetectionMarkerTextures::xray_depth`], and a full-strength line
+    /// is drawn along the hidden part of their silhouettes. The visible parts of the targets are
+    /// outlined as before, in `edge_color`; only their silhouettes show through, not their
+    /// creases.
+    ///
+    /// If `None`, hidden targets aren't outlined. Reads the depth prepass.
+    pub occluded_edge_color: Option<Color>,
+    /// Whether meshes with [`EdgeDetectionColor`] or [`EdgeDetectionOverride`] use their own
+    /// edge color and thresholds in this view. Their parameters are rendered into two extra
+    /// textures of the size of the view, see [`EdgeDetectionMarkerTextures::color`], so they're
+    /// only allocated for the views enabling this. Reads the depth prepass.
+    pub mesh_params: bool,
+
+    /// Texture multiplied over the final composited output, e.g. a paper or canvas grain for
+    /// hand-drawn looks. It is tiled in screen space and sampled with repeat addressing.
+    ///
+    /// If `None`, no overlay is applied.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub overlay_texture: Option<Handle<Image>>,
+    /// Opacity of the overlay texture.
+    /// `0.0` leaves the output unchanged, `1.0` fully multiplies the overlay over it.
+    ///
+    /// Range: [0.0, 1.0]
+    pub overlay_opacity: f32,
+    /// Number of screen pixels covered by one texel of the overlay texture.
+    /// Higher values result in a coarser grain.
+    ///
+    /// Range: (0.0, inf)
+    pub overlay_scale: f32,
+
+    /// How much the output is darkened by horizontal scanlines, for CRT looks.
+    /// Scanlines are applied to the final output, after edges and the overlay.
+    ///
+    /// `0.0` disables scanlines, `1.0` makes the darkest part of each line black.
+    ///
+    /// Range: [0.0, 1.0]
+    pub scanline_strength: f32,
+    /// Number of scanlines per screen pixel, e.g. `0.5` draws a scanline every 2 pixels.
+    ///
+    /// Range: (0.0, 0.5]
+    pub scanline_frequency: f32,
+
+    /// A screen region where the scene is output unmodified, e.g. so edges don't clutter the
+    /// background of a minimap. `xy` is the minimum and `zw` the maximum corner of the rect in
+    /// UV coordinates of the view, from `(0, 0)` at the top-left to `(1, 1)` at the bottom-right
+    /// corner, so the rect keeps covering the same part of the screen when it's resized.
+    ///
+    /// `None` draws edges everywhere.
+    pub exclusion_rect: Option<Vec4>,
+    /// Limits the effect to a screen rect, e.g. for a scanner viewport in the UI, in the UV
+    /// coordinates of the view from `(0, 0)` at the top-left to `(1, 1)` at the bottom-right
+    /// corner of the camera viewport, or of the render target without one. Pixels outside of it
+    /// are passed through untouched at little cost, and the kernels repeat the border of the rect
+    /// instead of reading past it, so it isn't outlined itself.
+    ///
+    /// `None` applies the effect to the whole view. The effect never leaves the camera viewport,
+    /// so split-screen cameras sharing a render target don't detect each other's content.
+    pub screen_rect: Option<Rect>,
+    /// Splits the view into a before and after comparison for tuning the effect: left of this
+    /// horizontal position, in UV coordinates from `0.0` at the left to `1.0` at the right edge,
+    /// the scene is output unmodified.
+    ///
+    /// `None` applies the effect to the whole view.
+    pub debug_split: Option<f32>,
+
     /// Whether to enable depth-based edge detection.
     /// If `true`, edges will be detected based on depth variations.
     pub enable_depth: bool,
@@ -414,35 +3648,780 @@ pub struct EdgeDetection {
     /// Whether to enable color-based edge detection.
     /// If `true`, edges will be detected based on color variations.
     pub enable_color: bool,
+    /// Whether color-based edge detection only reacts to chroma (hue/saturation) changes.
+    /// If `true`, samples are converted to the a/b channels of Oklab before the Sobel filter, so
+    /// pure brightness changes (e.g. soft shadows or shading ramps) don't produce edges while
+    /// boundaries between differently colored materials still do.
+    pub chroma_only: bool,
+    /// How the channels of color differences are weighted by the color detector, see
+    /// [`ColorEdgeSpace`]. Ignored with `chroma_only`.
+    pub color_edge_space: ColorEdgeSpace,
+    /// Whether to enable object ID based edge detection.
+    /// If `true`, every visible mesh writes an ID that is stable for as long as its entity lives,
+    /// see [`EdgeDetectionObjectIds`], and edges are detected where neighboring IDs differ. Unlike
+    /// depth and normal edges, this outlines the silhouettes of touching or intersecting meshes
+    /// without the creases inside of them.
+    ///
+    /// Renders every visible mesh a second time with a draw call of its own, see
+    /// [`EdgeDetectionMarkerTextures`]. Lightmapped meshes aren't supported and count as
+    /// background.
+    pub enable_object_id: bool,
+    /// Whether to enable material based edge detection with Bevy's deferred renderer.
+    /// If `true`, edges are detected where the material in the G-buffer of the
+    /// [`DeferredPrepass`] changes, e.g. between tiles and grout on the same flat surface, without
+    /// the lighting and shading the color edges react to. Materials differ by their deferred
+    /// lighting pass, base color, perceptual roughness, metallic, reflectance and flags.
+    ///
+    /// Textured materials vary within themselves, so this suits flat-colored materials best.
+    /// Unlit materials are only told apart by their flags and lighting pass.
+    ///
+    /// Ignored by cameras without a [`DeferredPrepass`], which isn't added automatically.
+    pub enable_material_id: bool,
+    /// Texture holding the surface curvature in its red channel, e.g. an `R16Float` image the
+    /// application renders each frame with a curvature material on a second camera, for
+    /// anatomical-illustration looks. Edges are detected where the curvature changes, by a Sobel
+    /// kernel `normal_thickness` texels wide, and combined with the other detectors; they count
+    /// as geometry for [`EdgeLogic::DepthOrNormal`].
+    ///
+    /// The texture is stretched over the view and loaded at whole texels, so its resolution may
+    /// differ from the view. Curvature baked per object has to be rendered into such a texture
+    /// first, the effect doesn't read the materials of the meshes.
+    ///
+    /// If `None`, the detector is disabled.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub curvature_texture: Option<Handle<Image>>,
+    /// Curvature gradient above which an edge is drawn, in the units of `curvature_texture`.
+    ///
+    /// Range: (0.0, inf)
+    pub curvature_threshold: f32,
+
+    /// How the responses of the enabled detectors are combined where several of them fire,
+    /// see [`EdgeCombineMode`].
+    pub combine_mode: EdgeCombineMode,
+    /// Which of the enabled detectors must fire for a pixel to become an edge, see [`EdgeLogic`].
+    /// Applied before `combine_mode`.
+    pub edge_logic: EdgeLogic,
+    /// The kernel the depth detector derives the view depth with, see [`EdgeOperator`].
+    pub depth_operator: EdgeOperator,
+    /// The kernel the normal detector derives the normals with, see [`EdgeOperator`].
+    pub normal_operator: EdgeOperator,
+    /// The kernel the color detector derives the scene color with, see [`EdgeOperator`]. The
+    /// color is usually the noisiest input, where the cheaper `Roberts` loses little.
+    pub color_operator: EdgeOperator,
+
+    /// An additional depth texture whose silhouettes are combined with the depth prepass.
+    /// Useful for geometry rendered into its own depth buffer (e.g. foliage) that isn't merged into
+    /// the main prepass. The texture must use the same camera and depth convention as the depth
+    /// prepass (reversed-Z NDC depth in the red channel); its resolution may differ from the view.
+    ///
+    /// Only used when `enable_depth` is `true`; both sources are combined like separate
+    /// detectors, see `combine_mode`.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub secondary_depth: Option<Handle<Image>>,
+
+    /// What is written into the alpha channel of the output, see [`OutputAlpha`].
+    pub output_alpha: OutputAlpha,
+
+    /// Whether to also write the direction of the edge gradient of every pixel into
+    /// [`EdgeDetectionGradientTexture`], e.g. for a later pass drawing hatch strokes that follow
+    /// the surfaces. The texture lives on the view entity of the render world.
+    ///
+    /// Not supported by [`EdgeDetectionCompute`].
+    pub gradient_output: bool,
+
+    /// Whether to also write the distance in pixels from every pixel to the nearest edge into
+    /// [`EdgeDetectionDistanceTexture`], searched up to this radius. A later pass can compare it
+    /// against an animated value to reveal the edges progressively, e.g. wiping outlines in.
+    ///
+    /// The distance is measured from the detected edges minus `dilate_radius`, so it's signed:
+    /// negative inside the drawn lines, 0.0 on their border and
+    /// `distance_field_radius - dilate_radius` where no edge is in reach.
+    ///
+    /// The field is computed by the second pass of `dilate_radius` with a brute-force search:
+    /// every pixel loads up to `(2 * distance_field_radius + 1)^2` texels of the edge mask, e.g.
+    /// 1089 at a radius of 16, so the cost grows with the square of the radius.
+    ///
+    /// `0` disables the output. Not supported by [`EdgeDetectionCompute`].
+    pub distance_field_radius: u32,
+
+    /// How the scene color is filtered when sampled between texels, e.g.
+    /// because of UV distortion, see [`SamplerFilter`].
+    pub sampler_filter: SamplerFilter,
+}
+
+impl Default for EdgeDetection {
+    fn default() -> Self {
+        Self {
+            depth_threshold: 1.0,
+            depth_threshold_world: None,
+            normal_threshold: 0.8,
+            color_threshold: 0.1,
+
+            depth_thickness: 1.0,
+            normal_thickness: 1.0,
+            color_thickness: 1.0,
+
+            thickness_falloff: 0.0,
+            distance_fade_start: 0.0,
+            distance_fade_end: 0.0,
+            near_fade_start: 0.0,
+            near_fade_end: 0.0,
+            viewmodel_depth_cutoff: None,
+            effect_depth_range: None,
+            volume: None,
+            volume_falloff: 0.0,
+            height_range: None,
+            height_falloff: 0.0,
+            respect_fog: true,
+
+            min_neighborhood_variance: 0.0,
+            foliage_suppression: 0.0,
+            background_edges: true,
+            background_depth_epsilon: 1e-6,
+
+            normal_edge_min_spacing: 0,
+            thin_edges: false,
+            normal_space: NormalSpace::World,
+
+            pixelate_grid: None,
+
+            strength_steps: 0,
+            edge_response_gamma: 1.0,
+
+            rim_modulation: 0.0,
+            radial_falloff: 0.0,
+            radial_invert: false,
+
+            dilate_radius: 0,
+
+            pooling_radius: 8.0,
+            pooling_strength: 0.0,
+            pooling_color: Color::srgb(0.55, 0.45, 0.4),
+            halo_radius: 0,
+            halo_strength: 1.0,
+
+            enable_steep_angle_correction: true,
+            steep_angle_threshold: 0.00,
+            steep_angle_multiplier: 0.30,
+
+            uv_distortion_frequency: Vec2::splat(1.0),
+            uv_distortion_strength: Vec2::splat(0.004),
+            uv_distortion_frequency2: Vec2::splat(4.0),
+            uv_distortion_strength2: Vec2::ZERO,
+            uv_distortion_in_pixels: false,
+            distortion_space: DistortionSpace::Screen,
+            noise_seed: 0,
+
+            edge_color: Color::BLACK,
+
+            color_space_is_linear: true,
+
+            edge_color_source: EdgeColorSource::Fixed,
+            nearer_object_darkening: 0.5,
+            depth_bands: Vec::new(),
+            edge_color_gradient: None,
+
+            contour_light_dir: Vec2::new(-1.0, 1.0),
+            contour_strength: 0.0,
+            contrast_guard: 0.0,
+            contrast_guard_color: None,
+
+            edge_emissive_strength: 1.0,
+            exposure_compensation: ExposureCompensation::None,
+
+            edge_shadow_offset: Vec2::ZERO,
+            edge_shadow_color: Color::BLACK,
+
+            edge_light_direction: Vec3::Y,
+            edge_color_lit: Color::BLACK,
+            edge_color_shadow: Color::BLACK,
+
+            non_edge_desaturation: 0.0,
+            posterize_levels: 0,
+
+            temporal_blend: 0.0,
+            motion_threshold: 0.0,
+
+            blend_mode: EdgeBlendMode::Overlay,
+
+            duotone_light_color: Color::WHITE,
+            duotone_shadow_color: Color::BLACK,
+            duotone_threshold: 0.2,
+            duotone_softness: 0.0,
+
+            hatching: None,
+            halftone: None,
+            stipple: None,
+
+            dither: false,
+
+            background_override: None,
+
+            background_tint: None,
+            background_tint_strength: 0.5,
+
+            threshold_map: None,
+            mask: None,
+            targets_only: false,
+            transparent_outlines: false,
+            occluded_edge_color: None,
+            mesh_params: false,
+
+            overlay_texture: None,
+            overlay_opacity: 1.0,
+            overlay_scale: 1.0,
+
+            scanline_strength: 0.0,
+            scanline_frequency: 0.5,
+
+            exclusion_rect: None,
+            screen_rect: None,
+            debug_split: None,
+
+            enable_depth: true,
+            enable_normal: true,
+            enable_color: false,
+            chroma_only: false,
+            color_edge_space: ColorEdgeSpace::Rgb,
+            enable_object_id: false,
+            enable_material_id: false,
+            curvature_texture: None,
+            curvature_threshold: 0.5,
+            combine_mode: EdgeCombineMode::Add,
+            edge_logic: EdgeLogic::Any,
+            depth_operator: EdgeOperator::Sobel,
+            normal_operator: EdgeOperator::Sobel,
+            color_operator: EdgeOperator::Sobel,
+
+            secondary_depth: None,
+
+            output_alpha: OutputAlpha::Opaque,
+
+            gradient_output: false,
+            distance_field_radius: 0,
+
+            sampler_filter: SamplerFilter::Linear,
+        }
+    }
+}
+
+impl EdgeDetection {
+    /// A risograph poster look: the scene is mapped to `light` and `shadow` by luminance and
+    /// edges are drawn in `edge`, see [`EdgeBlendMode::Duotone`].
+    pub fn duotone(light: Color, shadow: Color, edge: Color) -> Self {
+        Self {
+            blend_mode: EdgeBlendMode::Duotone,
+            duotone_light_color: light,
+            duotone_shadow_color: shadow,
+            edge_color: edge,
+            ..default()
+        }
+    }
+
+    /// Whether the enabled features read the depth prepass.
+    pub fn needs_depth_prepass(&self) -> bool {
+        self.enable_depth
+            || self.distortion_space == DistortionSpace::World
+            || self.thickness_falloff > 0.0
+            || self.distance_fade()
+            || self.near_fade()
+            || self.viewmodel_depth_cutoff.is_some()
+            || self.effect_depth_range.is_some()
+            || self.volume.is_some()
+            || self.height_range.is_some()
+            || self.min_neighborhood_variance > 0.0
+            || !self.background_edges
+            || self.contour_strength != 0.0
+            || self.edge_color_source == EdgeColorSource::NearerObject
+            || !self.depth_bands.is_empty()
+            || self.targets_only
+            || self.transparent_outlines
+            || self.occluded_edge_color.is_some()
+            || self.enable_object_id
+    }
+
+    /// Whether the enabled features read the normal prepass.
+    /// Depth edges use the normals to correct the threshold at steep viewing angles.
+    pub fn needs_normal_prepass(&self) -> bool {
+        (self.enable_depth && self.enable_steep_angle_correction)
+            || self.enable_normal
+            || self.edge_lighting()
+            || self.rim_modulation > 0.0
+            || self.distortion_space == DistortionSpace::World
+            || self.foliage_suppression > 0.0
+    }
+
+    /// Whether the edge color follows the surface normal, see [`EdgeDetection::edge_color_lit`].
+    pub fn edge_lighting(&self) -> bool {
+        self.edge_color_lit != self.edge_color_shadow
+    }
+
+    /// Whether the enabled features read the motion vector prepass.
+    pub fn needs_motion_vector_prepass(&self) -> bool {
+        self.temporal_blend > 0.0 || self.motion_threshold > 0.0
+    }
+
+    /// Whether the enabled features read any prepass.
+    pub fn needs_prepass(&self) -> bool {
+        self.needs_depth_prepass()
+            || self.needs_normal_prepass()
+            || self.needs_motion_vector_prepass()
+    }
+
+    /// Whether edges fade out with the distance, see [`EdgeDetection::distance_fade_start`].
+    pub fn distance_fade(&self) -> bool {
+        self.distance_fade_start != 0.0 || self.distance_fade_end != 0.0
+    }
+
+    /// Whether edges near the camera fade out, see [`EdgeDetection::near_fade_start`].
+    pub fn near_fade(&self) -> bool {
+        self.near_fade_start != 0.0 || self.near_fade_end != 0.0
+    }
+
+    /// Whether edges pool into the scene, see [`EdgeDetection::pooling_radius`].
+    pub fn pooling(&self) -> bool {
+        self.pooling_strength > 0.0 && self.pooling_radius > 0.0
+    }
+
+    /// Whether the edges bleed a halo into the scene, see [`EdgeDetection::halo_radius`].
+    pub fn halo(&self) -> bool {
+        self.halo_strength > 0.0 && self.halo_radius > 0
+    }
+
+    /// Whether the edge mask is written by a first pass and read back by a second one, for
+    /// [`EdgeDetection::dilate_radius`], [`EdgeDetection::pooling_radius`],
+    /// [`EdgeDetection::halo_radius`] and [`EdgeDetection::distance_field_radius`].
+    pub fn uses_mask_pass(&self) -> bool {
+        self.dilate_radius > 0 || self.pooling() || self.halo() || self.distance_field_radius > 0
+    }
+}
+
+/// Tuned starting points for [`EdgeDetection`], e.g.
+/// `EdgeDetection { edge_color: Color::WHITE, ..EdgeDetectionPreset::CelShaded.into() }`.
+///
+/// Fields a preset doesn't mention keep their default value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum EdgeDetectionPreset {
+    /// Thick, steady black outlines on a posterized scene, for a cartoon look.
+    #[default]
+    CelShaded,
+    /// Thin single-pixel lines of every crease on a flat paper background, like a blueprint.
+    TechnicalDrawing,
+    /// Wobbly brown ink lines and hatched shadows on a washed out scene.
+    InkSketch,
+    /// Faint, thin silhouettes which keep the scene as it is, to outline without styling.
+    Subtle,
+}
+
+impl From<EdgeDetectionPreset> for EdgeDetection {
+    fn from(preset: EdgeDetectionPreset) -> Self {
+        match preset {
+            EdgeDetectionPreset::CelShaded => Self {
+                depth_threshold: 0.8,
+                normal_threshold: 0.6,
+                depth_thickness: 2.0,
+                normal_thickness: 1.5,
+                uv_distortion_strength: Vec2::ZERO,
+                posterize_levels: 4,
+                ..default()
+            },
+            EdgeDetectionPreset::TechnicalDrawing => Self {
+                normal_threshold: 0.3,
+                thin_edges: true,
+                uv_distortion_strength: Vec2::ZERO,
+                edge_color: Color::srgb(0.1, 0.15, 0.35),
+                background_override: Some(Color::srgb(0.96, 0.95, 0.9)),
+                ..default()
+            },
+            EdgeDetectionPreset::InkSketch => Self {
+                depth_threshold: 0.8,
+                normal_threshold: 0.6,
+                depth_thickness: 1.5,
+                uv_distortion_frequency: Vec2::splat(4.0),
+                uv_distortion_strength: Vec2::splat(0.002),
+                uv_distortion_strength2: Vec2::splat(0.0005),
+                uv_distortion_frequency2: Vec2::splat(16.0),
+                edge_color: Color::srgb(0.1, 0.08, 0.06),
+                non_edge_desaturation: 0.6,
+                hatching: Some(HatchingSettings::default()),
+                ..default()
+            },
+            EdgeDetectionPreset::Subtle => Self {
+                depth_threshold: 1.5,
+                normal_threshold: 1.2,
+                enable_steep_angle_correction: false,
+                uv_distortion_strength: Vec2::ZERO,
+                edge_color: Color::srgba(0.0, 0.0, 0.0, 0.35),
+                ..default()
+            },
+        }
+    }
+}
+
+/// [`EdgeDetection`] together with the camera components the default depth and normal edges
+/// rely on, so the prepass textures exist from the first frame the camera renders.
+///
+/// The prepasses can't follow the enable flags at the bundle level. Color-only setups can spawn
+/// [`EdgeDetection`] on its own instead: [`insert_edge_detection_prepasses`] adds whichever
+/// prepasses the enabled features need, one frame later.
+#[derive(Bundle, Clone)]
+pub struct EdgeDetectionBundle {
+    pub edge_detection: EdgeDetection,
+    pub depth_prepass: DepthPrepass,
+    pub normal_prepass: NormalPrepass,
+    /// The prepass textures of a view are only created with an [`Msaa`] component.
+    /// Defaults to `Msaa::Off`, the setting the examples use.
+    pub msaa: Msaa,
+}
+
+impl Default for EdgeDetectionBundle {
+    fn default() -> Self {
+        Self {
+            edge_detection: EdgeDetection::default(),
+            depth_prepass: DepthPrepass,
+            normal_prepass: NormalPrepass,
+            msaa: Msaa::Off,
+        }
+    }
+}
+
+/// How the detected edges are composited with the scene color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum EdgeBlendMode {
+    /// Draw `edge_color` where edges are detected and keep the scene color elsewhere.
+    #[default]
+    Overlay,
+    /// Keep the scene color where edges are detected and apply `edge_color` everywhere else.
+    /// The edge strength is simply inverted before compositing, e.g. to build a tinted
+    /// "frosted glass" look with clear engraved lines.
+    Invert,
+    /// Map non-edge pixels to `duotone_shadow_color` or `duotone_light_color` by their
+    /// luminance and draw `edge_color` on top, for a risograph poster look.
+    /// See [`EdgeDetection::duotone`].
+    Duotone,
+}
+
+/// Where the color of the edges comes from, see [`EdgeDetection::edge_color_source`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum EdgeColorSource {
+    /// Every edge takes `edge_color`.
+    #[default]
+    Fixed,
+    /// Edges take the scene color of the nearest surface around them, darkened by
+    /// `nearer_object_darkening`, so outlines between overlapping objects read as belonging to
+    /// the front one. Against the sky the object is always the nearer side.
+    ///
+    /// The alpha of `edge_color` remains the opacity of the edges. Reads the depth prepass.
+    NearerObject,
+}
+
+/// How the responses of the detectors are combined, see [`EdgeDetection::combine_mode`].
+///
+/// A response is the ratio of the gradient of a detector to its threshold, and pixels whose
+/// combined response exceeds 1.0 are edges. The edge strength is always clamped to [0, 1], so
+/// overlapping detectors never draw darker than a single `edge_color`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum EdgeCombineMode {
+    /// Take the strongest response, so each detector finds the same edges as on its own.
+    #[default]
+    Max,
+    /// Sum the responses, so gradients below the thresholds of several detectors add up to an
+    /// edge.
+    Add,
+    /// Average the responses of the enabled detectors, so only edges found by most of them
+    /// remain.
+    Average,
+}
+
+/// A world-space volume edges are limited to, see [`EdgeDetection::volume`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum EdgeVolume {
+    /// A sphere around `center`.
+    Sphere { center: Vec3, radius: f32 },
+    /// An axis-aligned box around `center`, extending `half_size` along each axis.
+    Box { center: Vec3, half_size: Vec3 },
+}
+
+impl EdgeVolume {
+    /// The shape of the volume, part of the specialization key.
+    pub fn shape(&self) -> EdgeVolumeShape {
+        match self {
+            Self::Sphere { .. } => EdgeVolumeShape::Sphere,
+            Self::Box { .. } => EdgeVolumeShape::Box,
+        }
+    }
+}
+
+/// The shape of an [`EdgeVolume`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeVolumeShape {
+    Sphere,
+    Box,
+}
+
+/// Which of the enabled detectors must fire for a pixel to become an edge, see
+/// [`EdgeDetection::edge_logic`].
+///
+/// A detector fires where its response exceeds 1.0. Pixels failing the logic get no edge at all,
+/// otherwise the responses are combined by [`EdgeCombineMode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum EdgeLogic {
+    /// Any detector firing is enough.
+    #[default]
+    Any,
+    /// Every enabled detector must fire, e.g. depth and normal edges must agree.
+    ///
+    /// [`EdgeDetection::secondary_depth`] counts as a detector of its own, so while it's set an
+    /// edge also has to show up in the secondary depth texture.
+    All,
+    /// The depth or the normal detector must fire, so the color detector alone never draws an
+    /// edge and texture noise is ignored, while it still strengthens geometric edges.
+    DepthOrNormal,
+}
+
+/// The kernel a detector derives its input with, see [`EdgeDetection::depth_operator`] and the
+/// other detectors.
+///
+/// The kernels are scaled to give the same response to a step, so the thresholds carry over
+/// between them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum EdgeOperator {
+    /// The 3x3 Sobel kernel, 12 samples.
+    #[default]
+    Sobel,
+    /// The 3x3 Scharr kernel, 12 samples. Weights the center row more than Sobel, so the
+    /// direction of the gradient is more accurate, e.g. for `thin_edges` and
+    /// `gradient_output`, at the same cost.
+    Scharr,
+    /// The 2x2 Roberts cross, 4 samples. The cheapest kernel, and the most sensitive to noise.
+    /// Its taps sit half a thickness around the pixel.
+    Roberts,
+}
+
+/// The space the normal detector compares normals in, see [`EdgeDetection::normal_space`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum NormalSpace {
+    /// Compare the world-space normals of the prepass as they are. The detector compares the
+    /// normal components separately, along fixed world axes here, so crease edges don't change
+    /// as the camera rotates.
+    #[default]
+    World,
+    /// Rotate the normals into view space first, so the components compared follow the camera
+    /// and crease edges shimmer as it rotates. Mostly useful for comparison.
+    View,
+}
+
+/// How the color detector weights the channels of color differences, see
+/// [`EdgeDetection::color_edge_space`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum ColorEdgeSpace {
+    /// Weight the red, green and blue differences equally.
+    #[default]
+    Rgb,
+    /// Weight the differences by the Rec. 709 luminance coefficients (0.2126, 0.7152, 0.0722),
+    /// so the gradient follows perceived brightness: noise in the blue channel barely registers
+    /// while red and green boundaries stay crisp. The weighted gradients are smaller, so
+    /// `color_threshold` usually needs lowering.
+    Luminance,
+}
+
+/// How the edge color follows the exposure of the scene, see
+/// [`EdgeDetection::exposure_compensation`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum ExposureCompensation {
+    /// The edge color is written as is.
+    #[default]
+    None,
+    /// The edge color is multiplied by the luminance of the scene color under it, so lines are
+    /// relative to the brightness of the surfaces they outline. White lines take the brightness
+    /// of the surface, black lines stay black.
+    ///
+    /// There's no floor: edges over black scene pixels, such as unlit surfaces or an empty
+    /// background, are black whatever the edge color.
+    SceneLuminance,
+    /// The edge color is scaled by the exposure of the camera's [`Exposure`] relative to the
+    /// default exposure, like the lit surfaces of the scene. Cameras without [`Exposure`] use the
+    /// default, leaving the edge color unchanged.
+    CameraExposure,
+}
+
+/// The space the UV distortion noise is laid out in, see [`EdgeDetection::distortion_space`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum DistortionSpace {
+    /// The noise is glued to the screen, so wobbly lines crawl along static geometry while the
+    /// camera moves. `uv_distortion_frequency` is per view size.
+    #[default]
+    Screen,
+    /// The noise is laid out on the world position of the surfaces, reconstructed from the depth
+    /// prepass, so the wobble sticks to objects during camera moves.
+    /// `uv_distortion_frequency` is per world unit.
+    World,
+}
+
+/// Settings of the hatching drawn over dark regions, see [`EdgeDetection::hatching`].
+///
+/// Mid-tones get a single direction of hatch lines, shadows get a second direction crossing it.
+/// Thresholds are compared with the linear luminance of the scene color.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub struct HatchingSettings {
+    /// Angle of the hatch lines in radians, counter-clockwise from the horizontal.
+    /// The cross-hatching of shadows is rotated by a further 90 degrees.
+    pub angle: f32,
+    /// Distance in pixels between two hatch lines.
+    ///
+    /// Range: (0.0, inf)
+    pub spacing: f32,
+    /// Width in pixels of the hatch lines.
+    pub line_width: f32,
+    /// Pixels darker than this luminance get hatched.
+    pub mid_threshold: f32,
+    /// Pixels darker than this luminance get cross-hatched.
+    /// Usually lower than `mid_threshold`.
+    pub dark_threshold: f32,
+}
+
+impl Default for HatchingSettings {
+    fn default() -> Self {
+        Self {
+            angle: std::f32::consts::FRAC_PI_4,
+            spacing: 6.0,
+            line_width: 1.0,
+            mid_threshold: 0.2,
+            dark_threshold: 0.05,
+        }
+    }
+}
+
+/// Settings of the halftone dots of non-edge regions, see [`EdgeDetection::halftone`].
+///
+/// The dots are printed in the scene color on white, on a grid in physical pixels. The share of
+/// its cell a dot covers grows as the linear luminance of the scene color drops.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub struct HalftoneSettings {
+    /// Distance in pixels between the centers of two dots.
+    ///
+    /// Range: (0.0, inf)
+    pub spacing: f32,
+    /// Angle of the dot grid in radians, counter-clockwise from the horizontal.
+    pub angle: f32,
+    /// Exponent of the curve from luminance to coverage, `(1 - luminance)^exponent`.
+    /// Values above 1.0 shrink the dots of mid-tones, values below grow them.
+    ///
+    /// Range: (0.0, inf)
+    pub exponent: f32,
 }
 
-impl Default for EdgeDetection {
+impl Default for HalftoneSettings {
     fn default() -> Self {
         Self {
-            depth_threshold: 1.0,
-            normal_threshold: 0.8,
-            color_threshold: 0.1,
-
-            depth_thickness: 1.0,
-            normal_thickness: 1.0,
-            color_thickness: 1.0,
-
-            steep_angle_threshold: 0.00,
-            steep_angle_multiplier: 0.30,
-
-            uv_distortion_frequency: Vec2::splat(1.0),
-            uv_distortion_strength: Vec2::splat(0.004),
+            spacing: 8.0,
+            angle: std::f32::consts::FRAC_PI_4,
+            exponent: 1.0,
+        }
+    }
+}
 
-            edge_color: Color::BLACK,
+/// Settings of the stipple dots edges are drawn with, see [`EdgeDetection::stipple`].
+///
+/// Dots sit on a screen-space grid, jittered within their cell. Each dot appears once the edge
+/// strength exceeds a random threshold of its cell and grows to fill the cell as the strength
+/// reaches 1.0.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub struct StippleSettings {
+    /// Distance in pixels between two cells of the grid.
+    ///
+    /// Range: (0.0, inf)
+    pub spacing: f32,
+    /// Diameter in pixels of the dots of weak edges.
+    ///
+    /// Range: (0.0, spacing]
+    pub size: f32,
+}
 
-            enable_depth: true,
-            enable_normal: true,
-            enable_color: false,
+impl Default for StippleSettings {
+    fn default() -> Self {
+        Self {
+            spacing: 4.0,
+            size: 2.0,
         }
     }
 }
 
-#[derive(Component, Clone, Copy, ShaderType, ExtractComponent)]
+/// What the edge detection pass writes into the alpha channel of its output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum OutputAlpha {
+    /// The output is fully opaque.
+    #[default]
+    Opaque,
+    /// The alpha of the source texture is written through unchanged, so transparent regions of
+    /// the render target stay transparent. Only the RGB channels are modified where edges appear.
+    PreserveSource,
+    /// The final edge strength in `[0.0, 1.0]` is written, after opacity and dithering.
+    /// Since the alpha of opaque 3D scenes is otherwise unused, later render graph nodes can read
+    /// it to cheaply restrict their work to the edges.
+    EdgeStrength,
+}
+
+/// The filter used when the edge detection pass samples the scene color.
+/// Prepass textures are always loaded at whole texels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", reflect(Serialize, Deserialize))]
+pub enum SamplerFilter {
+    /// Blend between neighboring texels.
+    #[default]
+    Linear,
+    /// Take the nearest texel, for crisp pixel-art output and to avoid colors bleeding across
+    /// the edges being detected.
+    Nearest,
+}
+
+#[derive(Component, Clone, Copy, Debug, ShaderType, ExtractComponent)]
 pub struct EdgeDetectionUniform {
     pub depth_threshold: f32,
     pub normal_threshold: f32,
@@ -458,12 +4437,156 @@ pub struct EdgeDetectionUniform {
     pub uv_distortion: Vec4,
 
     pub edge_color: LinearRgba,
+    pub edge_emissive_strength: f32,
+    /// The exposure of the camera relative to the default one, see
+    /// [`ExposureCompensation::CameraExposure`].
+    pub camera_exposure: f32,
+
+    pub background_color: LinearRgba,
+
+    pub non_edge_desaturation: f32,
+    pub posterize_levels: u32,
+
+    pub overlay_opacity: f32,
+    pub overlay_scale: f32,
+
+    pub scanline_strength: f32,
+    pub scanline_frequency: f32,
+
+    pub temporal_blend: f32,
+
+    pub hatch_angle: f32,
+    pub hatch_spacing: f32,
+    pub hatch_line_width: f32,
+    pub hatch_mid_threshold: f32,
+    pub hatch_dark_threshold: f32,
+
+    pub edge_light_direction: Vec3,
+    pub edge_color_lit: LinearRgba,
+    pub edge_color_shadow: LinearRgba,
+
+    pub dilate_radius: u32,
+
+    pub strength_steps: u32,
+    pub edge_response_gamma: f32,
+
+    pub edge_shadow_offset: Vec2,
+    pub edge_shadow_color: LinearRgba,
+
+    pub pixelate_grid: Vec2,
+
+    pub normal_edge_min_spacing: u32,
+
+    pub rim_modulation: f32,
+
+    pub nearer_object_darkening: f32,
+
+    pub thickness_falloff: f32,
+
+    pub contour_light_dir: Vec2,
+    pub contour_strength: f32,
+
+    pub contrast_guard: f32,
+    pub contrast_guard_color: LinearRgba,
+
+    pub background_tint: LinearRgba,
+    pub background_tint_strength: f32,
+
+    pub noise_seed: u32,
+
+    pub halftone_spacing: f32,
+    pub halftone_angle: f32,
+    pub halftone_exponent: f32,
+
+    pub stipple_spacing: f32,
+    pub stipple_size: f32,
+
+    pub duotone_light_color: LinearRgba,
+    pub duotone_shadow_color: LinearRgba,
+    pub duotone_threshold: f32,
+    pub duotone_softness: f32,
+
+    pub min_neighborhood_variance: f32,
+
+    pub pooling_radius: f32,
+    pub pooling_strength: f32,
+    pub pooling_color: LinearRgba,
+
+    /// The start distances of the depth bands in ascending order, four per vector.
+    pub depth_band_starts: [Vec4; MAX_DEPTH_BANDS / 4],
+    pub depth_band_colors: [LinearRgba; MAX_DEPTH_BANDS],
+    pub depth_band_count: u32,
+
+    pub edge_gradient_start: LinearRgba,
+    pub edge_gradient_end: LinearRgba,
+    pub edge_gradient_direction: Vec2,
+
+    pub distance_fade_start: f32,
+    pub distance_fade_end: f32,
+    pub near_fade_start: f32,
+    pub near_fade_end: f32,
+    pub effect_depth_min: f32,
+    pub effect_depth_max: f32,
+    pub volume_center: Vec3,
+    /// The half size of a box volume, or the radius of a sphere in every component.
+    pub volume_extents: Vec3,
+    pub volume_falloff: f32,
+    pub height_min: f32,
+    pub height_max: f32,
+    pub height_falloff: f32,
+
+    /// The falloff of the camera's fog, see [`EdgeDetectionUniform::FOG_MODE_LINEAR`] and the
+    /// other modes. Filled in from the camera during extraction.
+    pub fog_mode: u32,
+    pub fog_color: LinearRgba,
+    /// Start and end distance for linear fog, the density in `x` for exponential fog and the
+    /// extinction for atmospheric fog.
+    pub fog_params: Vec4,
+    /// The inscattering of atmospheric fog.
+    pub fog_inscattering: Vec4,
+
+    /// The min (`xy`) and max (`zw`) corners of the exclusion rect in UV coordinates.
+    pub exclusion_rect: Vec4,
+    /// The min (`xy`) and max (`zw`) corners of the screen rect in UV coordinates of the camera
+    /// viewport.
+    pub screen_rect: Vec4,
+    pub debug_split: f32,
+    pub radial_falloff: f32,
+    pub distance_field_radius: u32,
+    pub motion_threshold: f32,
+    pub background_depth_epsilon: f32,
+    pub viewmodel_depth_cutoff: f32,
+    pub foliage_suppression: f32,
+    pub uv_distortion2: Vec4,
+    pub occluded_edge_color: LinearRgba,
+    pub curvature_threshold: f32,
+    pub halo_radius: u32,
+    pub halo_strength: f32,
 }
 
 impl EdgeDetectionUniform {
+    pub const FOG_MODE_OFF: u32 = 0;
+    pub const FOG_MODE_LINEAR: u32 = 1;
+    pub const FOG_MODE_EXPONENTIAL: u32 = 2;
+    pub const FOG_MODE_EXPONENTIAL_SQUARED: u32 = 3;
+    pub const FOG_MODE_ATMOSPHERIC: u32 = 4;
+
+    /// Extracts the uniform of every camera with [`EdgeDetection`], every frame.
+    ///
+    /// The uniform carries no view size: the shader reads it from the dimensions of the main
+    /// texture and the viewport of the view, so pixel-based settings follow window resizes
+    /// and viewport changes on the frame they happen.
+    #[allow(clippy::type_complexity)]
     pub fn extract_edge_detection_settings(
         mut commands: Commands,
-        mut query: Extract<Query<(RenderEntity, &EdgeDetection)>>,
+        mut query: Extract<
+            Query<(
+                RenderEntity,
+                &EdgeDetection,
+                Option<&Exposure>,
+                Option<&DistanceFog>,
+            )>,
+        >,
     ) {
         if !DEPTH_TEXTURE_SAMPLING_SUPPORTED {
             info_once!(
@@ -472,57 +4595,661 @@ impl EdgeDetectionUniform {
             return;
         }
 
-        for (entity, edge_detection) in query.iter_mut() {
+        for (entity, edge_detection, exposure, fog) in query.iter_mut() {
             let mut entity_commands = commands
                 .get_entity(entity)
                 .expect("Edge Detection entity wasn't synced.");
 
-            entity_commands.insert((*edge_detection, EdgeDetectionUniform::from(edge_detection)));
-        }
+            let mut uniform = EdgeDetectionUniform::from(edge_detection);
+            uniform.camera_exposure =
+                exposure.copied().unwrap_or_default().exposure() / Exposure::default().exposure();
+
+            if let Some(fog) = fog.filter(|_| edge_detection.respect_fog) {
+                uniform.set_fog(fog);
+            }
+
+            entity_commands.insert((edge_detection.clone(), uniform));
+        }
+    }
+}
+
+impl EdgeDetectionUniform {
+    /// Copies the falloff of `fog`, which Bevy evaluates the same way for the scene.
+    fn set_fog(&mut self, fog: &DistanceFog) {
+        self.fog_color = fog.color.to_linear();
+        (self.fog_mode, self.fog_params, self.fog_inscattering) = match fog.falloff {
+            FogFalloff::Linear { start, end } => (
+                Self::FOG_MODE_LINEAR,
+                Vec4::new(start, end, 0.0, 0.0),
+                Vec4::ZERO,
+            ),
+            FogFalloff::Exponential { density } => (
+                Self::FOG_MODE_EXPONENTIAL,
+                Vec4::new(density, 0.0, 0.0, 0.0),
+                Vec4::ZERO,
+            ),
+            FogFalloff::ExponentialSquared { density } => (
+                Self::FOG_MODE_EXPONENTIAL_SQUARED,
+                Vec4::new(density, 0.0, 0.0, 0.0),
+                Vec4::ZERO,
+            ),
+            FogFalloff::Atmospheric {
+                extinction,
+                inscattering,
+            } => (
+                Self::FOG_MODE_ATMOSPHERIC,
+                extinction.extend(0.0),
+                inscattering.extend(0.0),
+            ),
+        };
+    }
+}
+
+impl From<&EdgeDetection> for EdgeDetectionUniform {
+    fn from(ed: &EdgeDetection) -> Self {
+        let hatching = ed.hatching.unwrap_or_default();
+        let halftone = ed.halftone.unwrap_or_default();
+        let stipple = ed.stipple.unwrap_or_default();
+        let (effect_depth_min, effect_depth_max) = ed.effect_depth_range.unwrap_or_default();
+        let (height_min, height_max) = ed.height_range.unwrap_or_default();
+        let (volume_center, volume_extents) = match ed.volume {
+            Some(EdgeVolume::Sphere { center, radius }) => (center, Vec3::splat(radius)),
+            Some(EdgeVolume::Box { center, half_size }) => (center, half_size),
+            None => (Vec3::ZERO, Vec3::ZERO),
+        };
+
+        let mut depth_bands = ed.depth_bands.clone();
+        depth_bands.truncate(MAX_DEPTH_BANDS);
+        depth_bands.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let (edge_gradient_start, edge_gradient_end, edge_gradient_direction) = ed
+            .edge_color_gradient
+            .unwrap_or((Color::NONE, Color::NONE, Vec2::ZERO));
+        let color = |color: Color| -> LinearRgba {
+            if ed.color_space_is_linear {
+                color.into()
+            } else {
+                let Srgba {
+                    red,
+                    green,
+                    blue,
+                    alpha,
+                } = color.to_srgba();
+                LinearRgba::new(red, green, blue, alpha)
+            }
+        };
+
+        Self {
+            // The Sobel kernel weights a depth step across all three of its rows by 1 + 2 + 1.
+            depth_threshold: ed
+                .depth_threshold_world
+                .map_or(ed.depth_threshold, |step| 4.0 * step),
+            normal_threshold: ed.normal_threshold,
+            color_threshold: ed.color_threshold,
+
+            depth_thickness: ed.depth_thickness,
+            normal_thickness: ed.normal_thickness,
+            color_thickness: ed.color_thickness,
+
+            steep_angle_threshold: ed.steep_angle_threshold,
+            steep_angle_multiplier: ed.steep_angle_multiplier,
+
+            uv_distortion: Vec4::new(
+                ed.uv_distortion_frequency.x,
+                ed.uv_distortion_frequency.y,
+                ed.uv_distortion_strength.x,
+                ed.uv_distortion_strength.y,
+            ),
+
+            edge_color: color(ed.edge_color),
+            edge_emissive_strength: ed.edge_emissive_strength,
+            // Filled in from the camera during extraction.
+            camera_exposure: 1.0,
+
+            background_color: color(ed.background_override.unwrap_or(Color::NONE)),
+
+            non_edge_desaturation: ed.non_edge_desaturation,
+            posterize_levels: ed.posterize_levels,
+
+            overlay_opacity: ed.overlay_opacity,
+            overlay_scale: ed.overlay_scale,
+
+            scanline_strength: ed.scanline_strength,
+            scanline_frequency: ed.scanline_frequency,
+
+            temporal_blend: ed.temporal_blend,
+
+            hatch_angle: hatching.angle,
+            hatch_spacing: hatching.spacing,
+            hatch_line_width: hatching.line_width,
+            hatch_mid_threshold: hatching.mid_threshold,
+            hatch_dark_threshold: hatching.dark_threshold,
+
+            edge_light_direction: ed.edge_light_direction.normalize_or_zero(),
+            edge_color_lit: color(ed.edge_color_lit),
+            edge_color_shadow: color(ed.edge_color_shadow),
+
+            dilate_radius: ed.dilate_radius,
+
+            strength_steps: ed.strength_steps,
+            edge_response_gamma: ed.edge_response_gamma,
+
+            edge_shadow_offset: ed.edge_shadow_offset,
+            edge_shadow_color: color(ed.edge_shadow_color),
+
+            pixelate_grid: ed
+                .pixelate_grid
+                .unwrap_or(UVec2::ONE)
+                .max(UVec2::ONE)
+                .as_vec2(),
+
+            normal_edge_min_spacing: ed.normal_edge_min_spacing,
+
+            rim_modulation: ed.rim_modulation,
+
+            nearer_object_darkening: ed.nearer_object_darkening,
+
+            thickness_falloff: ed.thickness_falloff,
+
+            contour_light_dir: ed.contour_light_dir.normalize_or_zero(),
+            contour_strength: ed.contour_strength,
+            contrast_guard: ed.contrast_guard,
+            contrast_guard_color: color(ed.contrast_guard_color.unwrap_or(Color::NONE)),
+
+            background_tint: color(ed.background_tint.unwrap_or(Color::NONE)),
+            background_tint_strength: ed.background_tint_strength,
+
+            noise_seed: ed.noise_seed,
+
+            halftone_spacing: halftone.spacing,
+            halftone_angle: halftone.angle,
+            halftone_exponent: halftone.exponent,
+
+            stipple_spacing: stipple.spacing,
+            stipple_size: stipple.size,
+
+            duotone_light_color: color(ed.duotone_light_color),
+            duotone_shadow_color: color(ed.duotone_shadow_color),
+            duotone_threshold: ed.duotone_threshold,
+            duotone_softness: ed.duotone_softness,
+
+            min_neighborhood_variance: ed.min_neighborhood_variance,
+
+            pooling_radius: ed.pooling_radius,
+            pooling_strength: ed.pooling_strength,
+            pooling_color: color(ed.pooling_color),
+
+            depth_band_starts: std::array::from_fn(|i| {
+                Vec4::from_array(std::array::from_fn(|j| {
+                    depth_bands
+                        .get(i * 4 + j)
+                        .map_or(f32::INFINITY, |band| band.0)
+                }))
+            }),
+            depth_band_colors: std::array::from_fn(|i| {
+                depth_bands
+                    .get(i)
+                    .map_or(LinearRgba::NONE, |band| color(band.1))
+            }),
+            depth_band_count: depth_bands.len() as u32,
+
+            edge_gradient_start: color(edge_gradient_start),
+            edge_gradient_end: color(edge_gradient_end),
+            edge_gradient_direction: edge_gradient_direction.normalize_or_zero(),
+
+            distance_fade_start: ed.distance_fade_start,
+            distance_fade_end: ed.distance_fade_end,
+            near_fade_start: ed.near_fade_start,
+            near_fade_end: ed.near_fade_end,
+            volume_center,
+            volume_extents,
+            volume_falloff: ed.volume_falloff,
+            height_min,
+            height_max,
+            height_falloff: ed.height_falloff,
+            effect_depth_min,
+            effect_depth_max,
+
+            fog_mode: Self::FOG_MODE_OFF,
+            fog_color: LinearRgba::NONE,
+            fog_params: Vec4::ZERO,
+            fog_inscattering: Vec4::ZERO,
+
+            exclusion_rect: ed.exclusion_rect.unwrap_or_default(),
+            // A camera viewport alone limits the effect to the whole viewport.
+            screen_rect: ed
+                .screen_rect
+                .map_or(Vec4::new(0.0, 0.0, 1.0, 1.0), |rect| {
+                    rect.min.extend(rect.max.x).extend(rect.max.y)
+                }),
+            debug_split: ed.debug_split.unwrap_or_default(),
+            radial_falloff: ed.radial_falloff,
+            distance_field_radius: ed.distance_field_radius,
+            motion_threshold: ed.motion_threshold,
+            background_depth_epsilon: ed.background_depth_epsilon,
+            viewmodel_depth_cutoff: ed.viewmodel_depth_cutoff.unwrap_or_default(),
+            foliage_suppression: ed.foliage_suppression,
+            uv_distortion2: Vec4::new(
+                ed.uv_distortion_frequency2.x,
+                ed.uv_distortion_frequency2.y,
+                ed.uv_distortion_strength2.x,
+                ed.uv_distortion_strength2.y,
+            ),
+            occluded_edge_color: color(ed.occluded_edge_color.unwrap_or(Color::NONE)),
+            curvature_threshold: ed.curvature_threshold,
+            halo_radius: ed.halo_radius.min(MAX_HALO_RADIUS),
+            halo_strength: ed.halo_strength,
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EdgeMarkerLabel;
+
+/// Renders the [`EdgeMarker3d`] phase of a view into its [`EdgeDetectionMarkerTextures`].
+///
+/// It runs after the prepasses and before [`EdgeDetectionNode`], wherever the latter is placed.
+#[derive(Default)]
+pub struct EdgeMarkerNode;
+
+impl ViewNode for EdgeMarkerNode {
+    type ViewQuery = (
+        &'static ExtractedCamera,
+        Option<&'static EdgeDetectionMarkerTextures>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (camera, marker_textures): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let view_entity = graph.view_entity();
+
+        let (Some(phase), Some(marker_textures)) = (
+            world
+                .resource::<ViewBinnedRenderPhases<EdgeMarker3d>>()
+                .get(&view_entity),
+            marker_textures,
+        ) else {
+            return Ok(());
+        };
+
+        // Rendered even without meshes, so the textures are cleared.
+        let mut color_attachments =
+            vec![Some(color_attachment(&marker_textures.marker.default_view))];
+        if let Some(object_id) = &marker_textures.object_id {
+            color_attachments.push(Some(color_attachment(&object_id.default_view)));
+        }
+        if let Some(color) = &marker_textures.color {
+            color_attachments.push(Some(color_attachment(&color.default_view)));
+        }
+        if let Some(thresholds) = &marker_textures.thresholds {
+            color_attachments.push(Some(color_attachment(&thresholds.default_view)));
+        }
+
+        let diagnostics = render_context.diagnostic_recorder();
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("edge_detection_marker_pass"),
+            color_attachments: &color_attachments,
+            // Reversed-Z: 0.0 is the far plane.
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &marker_textures.depth.default_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(0.0),
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        let pass_span = diagnostics.pass_span(&mut render_pass, "edge_detection_marker_pass");
+
+        if let Some(viewport) = camera.viewport.as_ref() {
+            render_pass.set_camera_viewport(viewport);
+        }
+
+        if let Err(err) = phase.render(&mut render_pass, world, view_entity) {
+            error!("Error encountered while rendering the edge marker phase {err:?}");
+        }
+
+        pass_span.end(&mut render_pass);
+        drop(render_pass);
+
+        let Some(xray_depth) = &marker_textures.xray_depth else {
+            return Ok(());
+        };
+
+        // The targets are rendered again on their own, so nothing in front of them hides their
+        // depth. The texture is cleared even when no target is visible.
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("edge_detection_xray_pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &xray_depth.default_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(0.0),
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        let pass_span = diagnostics.pass_span(&mut render_pass, "edge_detection_xray_pass");
+
+        if let Some(viewport) = camera.viewport.as_ref() {
+            render_pass.set_camera_viewport(viewport);
+        }
+
+        if let Some(xray_phase) = world
+            .resource::<ViewBinnedRenderPhases<EdgeXray3d>>()
+            .get(&view_entity)
+        {
+            if let Err(err) = xray_phase.render(&mut render_pass, world, view_entity) {
+                error!("Error encountered while rendering the edge x-ray phase {err:?}");
+            }
+        }
+
+        pass_span.end(&mut render_pass);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EdgeDetectionLabel;
+
+// The post process node used for the render graph
+#[derive(Default)]
+pub struct EdgeDetectionNode;
+
+impl ViewNode for EdgeDetectionNode {
+    type ViewQuery = (
+        &'static EdgeDetection,
+        Option<&'static Msaa>,
+        &'static ViewTarget,
+        Option<&'static ViewPrepassTextures>,
+        &'static ViewUniformOffset,
+        &'static DynamicUniformIndex<EdgeDetectionUniform>,
+        &'static EdgeDetectionPipelineId,
+        Option<&'static EdgeDetectionHistoryTextures>,
+        Option<&'static EdgeDetectionMaskPipelineId>,
+        Option<&'static EdgeDetectionMaskTexture>,
+        Option<&'static EdgeDetectionGradientTexture>,
+        Option<&'static EdgeDetectionDistanceTexture>,
+        Option<&'static EdgeDetectionMarkerTextures>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (
+            edge_detection,
+            msaa,
+            view_target,
+            prepass_textures,
+            view_uniform_index,
+            ed_uniform_index,
+            edge_detection_pipeline_id,
+            history_textures,
+            mask_pipeline_id,
+            mask_texture,
+            gradient_texture,
+            distance_texture,
+            marker_textures,
+        ): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let edge_detection_pipeline = world.resource::<EdgeDetectionPipeline>();
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(edge_detection_pipeline_id.0)
+        else {
+            return Ok(());
+        };
+
+        // Drawing into a target of another format fails wgpu validation with a cryptic error.
+        let target_format = view_target.main_texture_format();
+        if target_format != edge_detection_pipeline_id.1 {
+            error_once!(
+                "Skipping edge detection: the view target is {target_format:?} but the pipeline \
+                was specialized for {:?}. Check that `Camera::hdr` matches the view target.",
+                edge_detection_pipeline_id.1
+            );
+            return Ok(());
+        }
+
+        let history = match (edge_detection.temporal_blend > 0.0, history_textures) {
+            (true, Some(history_textures)) => Some(history_textures),
+            (true, None) => return Ok(()),
+            (false, _) => None,
+        };
+
+        // When dilating, the mask pass writes into the history texture if there is one, since the
+        // stabilized mask has to be stored there anyway.
+        let mask_pass = if edge_detection.uses_mask_pass() {
+            let Some(mask_pipeline) = mask_pipeline_id.and_then(|mask_pipeline_id| {
+                pipeline_cache.get_render_pipeline(mask_pipeline_id.0)
+            }) else {
+                return Ok(());
+            };
+
+            let target = match (history, mask_texture) {
+                (Some(history), _) => &history.write.default_view,
+                (None, Some(mask_texture)) => &mask_texture.0.default_view,
+                (None, None) => return Ok(()),
+            };
+
+            Some(EdgeMaskPass {
+                pipeline: mask_pipeline,
+                target,
+            })
+        } else {
+            None
+        };
+        let dilate = mask_pass.is_some();
+
+        let gradient_write = match (edge_detection.gradient_output, gradient_texture) {
+            (true, Some(gradient_texture)) => Some(&gradient_texture.0.default_view),
+            (true, None) => return Ok(()),
+            (false, _) => None,
+        };
+
+        let distance_write = match (edge_detection.distance_field_radius > 0, distance_texture) {
+            (true, Some(distance_texture)) => Some(&distance_texture.0.default_view),
+            (true, None) => return Ok(()),
+            (false, _) => None,
+        };
+
+        let Some(mut inputs) = edge_detection_pass_inputs(
+            world,
+            edge_detection,
+            view_target,
+            prepass_textures,
+            view_uniform_index,
+            ed_uniform_index,
+            msaa.is_some_and(|msaa| *msaa != Msaa::Off),
+        ) else {
+            return Ok(());
+        };
+
+        if let Some(history) = history {
+            inputs.history_read = &history.read.default_view;
+        }
+        inputs.history_write = history
+            .filter(|_| !dilate)
+            .map(|history| &history.write.default_view);
+        inputs.mask_pass = mask_pass;
+        inputs.gradient_write = gradient_write;
+        inputs.distance_write = distance_write;
+        inputs.marker_bind_group = edge_detection_pipeline_id.2;
+        if let Some(marker_textures) = marker_textures {
+            inputs.marker_depth = &marker_textures.depth.default_view;
+            inputs.marker = &marker_textures.marker.default_view;
+            if let Some(object_id) = &marker_textures.object_id {
+                inputs.object_id = &object_id.default_view;
+            }
+            if let Some(xray_depth) = &marker_textures.xray_depth {
+                inputs.xray_depth = &xray_depth.default_view;
+            }
+            if let Some(color) = &marker_textures.color {
+                inputs.marker_color = &color.default_view;
+            }
+            if let Some(thresholds) = &marker_textures.thresholds {
+                inputs.marker_thresholds = &thresholds.default_view;
+            }
+        }
+
+        run_edge_detection(render_context, edge_detection_pipeline, pipeline, inputs)
+    }
+}
+
+/// Gathers the inputs of the edge detection pass of a view and starts a post process write on
+/// its [`ViewTarget`]. The history, mask pass and gradient output are left unset.
+///
+/// Returns `None` without touching the view target while an input isn't available yet.
+fn edge_detection_pass_inputs<'a>(
+    world: &'a World,
+    edge_detection: &'a EdgeDetection,
+    view_target: &'a ViewTarget,
+    prepass_textures: Option<&'a ViewPrepassTextures>,
+    view_uniform_index: &ViewUniformOffset,
+    ed_uniform_index: &DynamicUniformIndex<EdgeDetectionUniform>,
+    multisampled: bool,
+) -> Option<EdgeDetectionPassInputs<'a>> {
+    let edge_detection_pipeline = world.resource::<EdgeDetectionPipeline>();
+
+    // Prepasses the enabled features don't read may be missing, so they're replaced by
+    // zeroed textures. The pass can't run without the ones it needs.
+    let depth_texture = prepass_textures.and_then(|textures| textures.depth_view());
+    let normal_texture = prepass_textures.and_then(|textures| textures.normal_view());
+
+    if (edge_detection.needs_depth_prepass() && depth_texture.is_none())
+        || (edge_detection.needs_normal_prepass() && normal_texture.is_none())
+    {
+        return None;
     }
-}
 
-impl From<&EdgeDetection> for EdgeDetectionUniform {
-    fn from(ed: &EdgeDetection) -> Self {
-        Self {
-            depth_threshold: ed.depth_threshold,
-            normal_threshold: ed.normal_threshold,
-            color_threshold: ed.color_threshold,
+    let depth_texture =
+        depth_texture.unwrap_or(edge_detection_pipeline.fallback_depth_texture(multisampled));
+    let normal_texture =
+        normal_texture.unwrap_or(edge_detection_pipeline.fallback_texture(multisampled));
 
-            depth_thickness: ed.depth_thickness,
-            normal_thickness: ed.normal_thickness,
-            color_thickness: ed.color_thickness,
+    // Without a motion vector prepass the history is read back without reprojection.
+    let motion_vectors = prepass_textures
+        .and_then(|textures| textures.motion_vectors_view())
+        .unwrap_or(edge_detection_pipeline.fallback_texture(multisampled));
 
-            steep_angle_threshold: ed.steep_angle_threshold,
-            steep_angle_multiplier: ed.steep_angle_multiplier,
+    // Cameras without a deferred prepass don't detect material IDs, see `EdgeDetectionKey::new`.
+    let deferred = prepass_textures
+        .and_then(|textures| textures.deferred_view())
+        .unwrap_or(&edge_detection_pipeline.fallback_uint_texture);
+    let deferred_lighting_pass_id = prepass_textures
+        .and_then(|textures| textures.deferred_lighting_pass_id.as_ref())
+        .map_or(
+            &edge_detection_pipeline.fallback_uint_texture,
+            |attachment| &attachment.texture.default_view,
+        );
 
-            uv_distortion: Vec4::new(
-                ed.uv_distortion_frequency.x,
-                ed.uv_distortion_frequency.y,
-                ed.uv_distortion_strength.x,
-                ed.uv_distortion_strength.y,
-            ),
+    let gpu_images = world.resource::<RenderAssets<GpuImage>>();
 
-            edge_color: ed.edge_color.into(),
-        }
-    }
+    let noise_texture = gpu_images.get(&edge_detection_pipeline.noise_texture)?;
+
+    // Optional textures fall back to a constant white texture while they are unset or still
+    // loading, so the bind group layout stays the same. White produces no secondary depth
+    // edges, leaves the output unchanged as an overlay, the thresholds as a threshold map and
+    // the edge strength as a mask.
+    let fallback_image = &world.resource::<FallbackImage>().d2;
+    let optional_texture = |handle: &Option<Handle<Image>>| {
+        handle
+            .as_ref()
+            .and_then(|handle| gpu_images.get(handle))
+            .unwrap_or(fallback_image)
+    };
+
+    let secondary_depth_texture = optional_texture(&edge_detection.secondary_depth);
+    let overlay_texture = optional_texture(&edge_detection.overlay_texture);
+    let threshold_map = optional_texture(&edge_detection.threshold_map);
+    let strength_mask = optional_texture(&edge_detection.mask);
+    let curvature = optional_texture(&edge_detection.curvature_texture);
+
+    let view_uniforms_binding = world.resource::<ViewUniforms>().uniforms.binding()?;
+
+    let ed_uniform_binding = world
+        .resource::<ComponentUniforms<EdgeDetectionUniform>>()
+        .uniforms()
+        .binding()?;
+
+    // This will start a new "post process write", obtaining two texture
+    // views from the view target - a `source` and a `destination`.
+    // `source` is the "current" main texture and you _must_ write into
+    // `destination` because calling `post_process_write()` on the
+    // [`ViewTarget`] will internally flip the [`ViewTarget`]'s main
+    // texture to the `destination` texture. Failing to do so will cause
+    // the current main texture information to be lost.
+    let post_process = view_target.post_process_write();
+
+    Some(EdgeDetectionPassInputs {
+        source: post_process.source,
+        destination: post_process.destination,
+        depth: depth_texture,
+        normal: normal_texture,
+        noise: &noise_texture.texture_view,
+        view_uniforms: view_uniforms_binding,
+        edge_detection_uniforms: ed_uniform_binding,
+        view_uniform_offset: view_uniform_index.offset,
+        edge_detection_uniform_offset: ed_uniform_index.index(),
+        secondary_depth: &secondary_depth_texture.texture_view,
+        overlay: &overlay_texture.texture_view,
+        threshold_map: &threshold_map.texture_view,
+        strength_mask: &strength_mask.texture_view,
+        curvature: &curvature.texture_view,
+        marker_depth: edge_detection_pipeline.fallback_depth_texture(false),
+        marker: edge_detection_pipeline.fallback_texture(false),
+        object_id: &edge_detection_pipeline.fallback_uint_texture,
+        xray_depth: edge_detection_pipeline.fallback_depth_texture(false),
+        marker_color: edge_detection_pipeline.fallback_texture(false),
+        marker_thresholds: edge_detection_pipeline.fallback_texture(false),
+        deferred,
+        deferred_lighting_pass_id,
+        motion_vectors,
+        history_read: &fallback_image.texture_view,
+        history_write: None,
+        mask_pass: None,
+        gradient_write: None,
+        distance_write: None,
+        sampler_filter: edge_detection.sampler_filter,
+        multisampled,
+        marker_bind_group: false,
+    })
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
-pub struct EdgeDetectionLabel;
+pub struct EdgeDetectionComputeLabel;
 
-// The post process node used for the render graph
+/// The compute alternative of [`EdgeDetectionNode`], which runs for views with
+/// [`EdgeDetectionCompute`].
+///
+/// It writes the result straight into the [`ViewTarget`] as a storage texture, dispatching 8x8
+/// workgroups over the view.
 #[derive(Default)]
-pub struct EdgeDetectionNode;
+pub struct EdgeDetectionComputeNode;
 
-impl ViewNode for EdgeDetectionNode {
+impl ViewNode for EdgeDetectionComputeNode {
     type ViewQuery = (
-        &'static Msaa,
+        &'static EdgeDetection,
+        Option<&'static Msaa>,
         &'static ViewTarget,
-        &'static ViewPrepassTextures,
+        Option<&'static ViewPrepassTextures>,
         &'static ViewUniformOffset,
         &'static DynamicUniformIndex<EdgeDetectionUniform>,
-        &'static EdgeDetectionPipelineId,
+        &'static EdgeDetectionComputePipelineId,
+        Option<&'static EdgeDetectionMarkerTextures>,
     );
 
     fn run(
@@ -530,12 +5257,14 @@ impl ViewNode for EdgeDetectionNode {
         _graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
         (
+            edge_detection,
             msaa,
             view_target,
             prepass_textures,
             view_uniform_index,
             ed_uniform_index,
             edge_detection_pipeline_id,
+            marker_textures,
         ): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
@@ -543,98 +5272,1299 @@ impl ViewNode for EdgeDetectionNode {
 
         let Some(pipeline) = world
             .resource::<PipelineCache>()
-            .get_render_pipeline(edge_detection_pipeline_id.0)
+            .get_compute_pipeline(edge_detection_pipeline_id.0)
         else {
             return Ok(());
         };
 
-        let (Some(depth_texture), Some(normal_texture)) =
-            (&prepass_textures.depth, &prepass_textures.normal)
-        else {
+        let Some(mut inputs) = edge_detection_pass_inputs(
+            world,
+            edge_detection,
+            view_target,
+            prepass_textures,
+            view_uniform_index,
+            ed_uniform_index,
+            msaa.is_some_and(|msaa| *msaa != Msaa::Off),
+        ) else {
             return Ok(());
         };
 
-        let Some(noise_texture) = world
-            .resource::<RenderAssets<GpuImage>>()
-            .get(&edge_detection_pipeline.noise_texture)
-        else {
-            return Ok(());
+        inputs.marker_bind_group = edge_detection_pipeline_id.1;
+        if let Some(marker_textures) = marker_textures {
+            inputs.marker_depth = &marker_textures.depth.default_view;
+            inputs.marker = &marker_textures.marker.default_view;
+            if let Some(object_id) = &marker_textures.object_id {
+                inputs.object_id = &object_id.default_view;
+            }
+            if let Some(xray_depth) = &marker_textures.xray_depth {
+                inputs.xray_depth = &xray_depth.default_view;
+            }
+            if let Some(color) = &marker_textures.color {
+                inputs.marker_color = &color.default_view;
+            }
+            if let Some(thresholds) = &marker_textures.thresholds {
+                inputs.marker_thresholds = &thresholds.default_view;
+            }
+        }
+
+        let size = view_target.main_texture().size();
+
+        run_edge_detection_compute(
+            render_context,
+            edge_detection_pipeline,
+            pipeline,
+            inputs,
+            UVec2::new(size.width, size.height),
+        )
+    }
+}
+
+/// The per-view inputs of the edge detection pass.
+///
+/// See [`run_edge_detection`].
+pub struct EdgeDetectionPassInputs<'a> {
+    /// The texture holding the current scene color.
+    pub source: &'a TextureView,
+    /// The texture the composited result is written into. Must differ from `source`.
+    pub destination: &'a TextureView,
+    /// The depth prepass texture of the view.
+    pub depth: &'a TextureView,
+    /// The normal prepass texture of the view.
+    pub normal: &'a TextureView,
+    /// The noise texture used for UV distortion, see [`EdgeDetectionPipeline::noise_texture`].
+    pub noise: &'a TextureView,
+    /// The binding of the [`ViewUniforms`] buffer.
+    pub view_uniforms: BindingResource<'a>,
+    /// The binding of the [`EdgeDetectionUniform`] buffer.
+    pub edge_detection_uniforms: BindingResource<'a>,
+    /// The dynamic offset of the view in the [`ViewUniforms`] buffer.
+    pub view_uniform_offset: u32,
+    /// The dynamic offset of the view in the [`EdgeDetectionUniform`] buffer.
+    pub edge_detection_uniform_offset: u32,
+    /// The secondary depth texture, see [`EdgeDetection::secondary_depth`].
+    /// Bind any unfilterable float texture when unused.
+    pub secondary_depth: &'a TextureView,
+    /// The overlay texture, see [`EdgeDetection::overlay_texture`].
+    /// Bind a white texture when unused.
+    pub overlay: &'a TextureView,
+    /// The threshold map, see [`EdgeDetection::threshold_map`].
+    /// Bind a white texture when unused.
+    pub threshold_map: &'a TextureView,
+    /// The strength mask, see [`EdgeDetection::mask`].
+    /// Bind a white texture when unused.
+    pub strength_mask: &'a TextureView,
+    /// The depth of the entities with [`NoEdgeDetection`] or [`EdgeDetectionTarget`], see
+    /// [`EdgeDetectionMarkerTextures::depth`]. Bind a zeroed depth texture when unused.
+    pub marker_depth: &'a TextureView,
+    /// Which of the entities are targets, see [`EdgeDetectionMarkerTextures::marker`].
+    /// Bind any filterable float texture when unused.
+    pub marker: &'a TextureView,
+    /// The object IDs of the meshes, see [`EdgeDetectionMarkerTextures::object_id`].
+    /// Bind any unsigned integer texture when unused.
+    pub object_id: &'a TextureView,
+    /// The depth of the targets alone, see [`EdgeDetectionMarkerTextures::xray_depth`].
+    /// Bind a zeroed depth texture when unused.
+    pub xray_depth: &'a TextureView,
+    /// The curvature texture, see [`EdgeDetection::curvature_texture`].
+    /// Bind any filterable float texture when unused.
+    pub curvature: &'a TextureView,
+    /// The colors of the meshes, see [`EdgeDetectionMarkerTextures::color`].
+    /// Bind any filterable float texture when unused.
+    pub marker_color: &'a TextureView,
+    /// The thresholds of the meshes, see [`EdgeDetectionMarkerTextures::thresholds`].
+    /// Bind any filterable float texture when unused.
+    pub marker_thresholds: &'a TextureView,
+    /// The G-buffer of the [`DeferredPrepass`], see [`EdgeDetection::enable_material_id`].
+    /// Bind any unsigned integer texture when unused.
+    pub deferred: &'a TextureView,
+    /// The deferred lighting pass IDs of the [`DeferredPrepass`].
+    /// Bind any unsigned integer texture when unused.
+    pub deferred_lighting_pass_id: &'a TextureView,
+    /// The motion vector prepass texture of the view.
+    /// Bind a zeroed texture when unused or missing.
+    pub motion_vectors: &'a TextureView,
+    /// The edge mask of the previous frame, see [`EdgeDetectionHistoryTextures::read`].
+    /// Bind any filterable float texture when unused.
+    pub history_read: &'a TextureView,
+    /// The texture the stabilized edge mask is written into, see
+    /// [`EdgeDetectionHistoryTextures::write`]. Must be `Some` exactly when temporal
+    /// stabilization is enabled without dilation.
+    pub history_write: Option<&'a TextureView>,
+    /// The mask pass, which must be `Some` exactly when edges are dilated.
+    pub mask_pass: Option<EdgeMaskPass<'a>>,
+    /// The texture the gradient is written into, see [`EdgeDetectionGradientTexture`]. Must be
+    /// `Some` exactly when [`EdgeDetection::gradient_output`] is enabled.
+    pub gradient_write: Option<&'a TextureView>,
+    /// The texture the distance to the nearest edge is written into, see
+    /// [`EdgeDetectionDistanceTexture`]. Must be `Some` exactly when
+    /// [`EdgeDetection::distance_field_radius`] is enabled.
+    pub distance_write: Option<&'a TextureView>,
+    /// The filter of the sampler used for the scene color.
+    pub sampler_filter: SamplerFilter,
+    /// Whether the prepass textures are multisampled.
+    pub multisampled: bool,
+    /// Whether the pipeline has the second bind group, see
+    /// [`EdgeDetectionKey::uses_marker_bind_group`]. The marker, x-ray, curvature and mesh param
+    /// textures are only bound when it is set.
+    pub marker_bind_group: bool,
+}
+
+/// The first pass of views with [`EdgeDetection::dilate_radius`], which writes the edge mask.
+pub struct EdgeMaskPass<'a> {
+    /// The pipeline of the mask pass, see [`EdgeDetectionMaskPipelineId`].
+    pub pipeline: &'a RenderPipeline,
+    /// The texture the edge mask is written into, read back dilated by the second pass.
+    /// See [`EdgeDetectionMaskTexture`], or [`EdgeDetectionHistoryTextures::write`] when temporal
+    /// stabilization is enabled.
+    pub target: &'a TextureView,
+}
+
+/// Runs the edge detection pass, reading from `inputs.source` and writing into `inputs.destination`.
+///
+/// This is what [`EdgeDetectionNode`] executes. Custom render graph nodes can call it directly to
+/// control the ordering of the effect relative to other post processing passes themselves.
+/// `pipeline` must be specialized from `edge_detection_pipeline` with a key matching the view.
+///
+/// When `inputs.mask_pass` is set, the edge mask is written by the mask pass first and
+/// `pipeline` dilates and composites it.
+pub fn run_edge_detection(
+    render_context: &mut RenderContext,
+    edge_detection_pipeline: &EdgeDetectionPipeline,
+    pipeline: &RenderPipeline,
+    inputs: EdgeDetectionPassInputs,
+) -> Result<(), NodeRunError> {
+    // The bind_group gets created each frame.
+    //
+    // Normally, you would create a bind_group in the Queue set,
+    // but this doesn't work with the post_process_write().
+    // The reason it doesn't work is because each post_process_write will alternate the source/destination.
+    // The only way to have the correct source/destination for the bind_group
+    // is to make sure you get it during the node execution.
+    let render_device = render_context.render_device().clone();
+    let unused_edge_mask = edge_detection_pipeline.fallback_texture(false);
+    let marker_bind_group =
+        create_edge_marker_bind_group(&render_device, edge_detection_pipeline, &inputs);
+
+    let edge_mask = if let Some(mask_pass) = &inputs.mask_pass {
+        let bind_group = create_edge_detection_bind_group(
+            &render_device,
+            edge_detection_pipeline,
+            &inputs,
+            unused_edge_mask,
+            None,
+        );
+
+        let mut color_attachments = vec![Some(color_attachment(mask_pass.target))];
+
+        if let Some(gradient_write) = inputs.gradient_write {
+            color_attachments.extend([None, Some(color_attachment(gradient_write))]);
+        }
+
+        draw_edge_detection_pass(
+            render_context,
+            "edge_detection_mask_pass",
+            mask_pass.pipeline,
+            &bind_group,
+            marker_bind_group.as_ref(),
+            &color_attachments,
+            &inputs,
+        );
+
+        mask_pass.target
+    } else {
+        unused_edge_mask
+    };
+
+    let bind_group = create_edge_detection_bind_group(
+        &render_device,
+        edge_detection_pipeline,
+        &inputs,
+        edge_mask,
+        None,
+    );
+
+    let mut color_attachments = vec![Some(color_attachment(inputs.destination))];
+
+    if let Some(history_write) = inputs.history_write {
+        color_attachments.push(Some(color_attachment(history_write)));
+    }
+
+    // Without a mask pass, this pass writes the gradient into the third target.
+    if let (None, Some(gradient_write)) = (&inputs.mask_pass, inputs.gradient_write) {
+        color_attachments.resize(2, None);
+        color_attachments.push(Some(color_attachment(gradient_write)));
+    }
+
+    // The distance field is computed from the mask, so only ever by the second pass.
+    if let (Some(_), Some(distance_write)) = (&inputs.mask_pass, inputs.distance_write) {
+        color_attachments.resize(3, None);
+        color_attachments.push(Some(color_attachment(distance_write)));
+    }
+
+    draw_edge_detection_pass(
+        render_context,
+        "edge_detection_pass",
+        pipeline,
+        &bind_group,
+        marker_bind_group.as_ref(),
+        &color_attachments,
+        &inputs,
+    );
+
+    Ok(())
+}
+
+/// Runs the edge detection as a compute shader, reading from `inputs.source` and writing into
+/// `inputs.destination` as a storage texture of `size` pixels.
+///
+/// This is what [`EdgeDetectionComputeNode`] executes. `pipeline` must be specialized from
+/// `edge_detection_pipeline` with a key matching the view, and the destination must be an
+/// [`ViewTarget::TEXTURE_FORMAT_HDR`] texture with [`TextureUsages::STORAGE_BINDING`].
+/// `inputs.history_write`, `inputs.mask_pass`, `inputs.gradient_write` and
+/// `inputs.distance_write` are ignored.
+pub fn run_edge_detection_compute(
+    render_context: &mut RenderContext,
+    edge_detection_pipeline: &EdgeDetectionPipeline,
+    pipeline: &ComputePipeline,
+    inputs: EdgeDetectionPassInputs,
+    size: UVec2,
+) -> Result<(), NodeRunError> {
+    let bind_group = create_edge_detection_bind_group(
+        render_context.render_device(),
+        edge_detection_pipeline,
+        &inputs,
+        edge_detection_pipeline.fallback_texture(false),
+        Some(inputs.destination),
+    );
+    let marker_bind_group = create_edge_marker_bind_group(
+        render_context.render_device(),
+        edge_detection_pipeline,
+        &inputs,
+    );
+
+    let diagnostics = render_context.diagnostic_recorder();
+    let mut compute_pass =
+        render_context
+            .command_encoder()
+            .begin_compute_pass(&ComputePassDescriptor {
+                label: Some("edge_detection_compute_pass"),
+                timestamp_writes: None,
+            });
+    let pass_span = diagnostics.pass_span(&mut compute_pass, "edge_detection_compute_pass");
+
+    compute_pass.set_pipeline(pipeline);
+    compute_pass.set_bind_group(
+        0,
+        &bind_group,
+        &[
+            inputs.view_uniform_offset,
+            inputs.edge_detection_uniform_offset,
+        ],
+    );
+    if let Some(marker_bind_group) = &marker_bind_group {
+        compute_pass.set_bind_group(1, marker_bind_group, &[]);
+    }
+    compute_pass.dispatch_workgroups(size.x.div_ceil(8), size.y.div_ceil(8), 1);
+
+    pass_span.end(&mut compute_pass);
+
+    Ok(())
+}
+
+/// Creates the bind group of a pass. The compute pass additionally binds the storage texture it
+/// writes into as `output`.
+fn create_edge_detection_bind_group(
+    render_device: &RenderDevice,
+    edge_detection_pipeline: &EdgeDetectionPipeline,
+    inputs: &EdgeDetectionPassInputs,
+    edge_mask: &TextureView,
+    output: Option<&TextureView>,
+) -> BindGroup {
+    let layout = match output {
+        Some(_) => edge_detection_pipeline.compute_bind_group_layout(inputs.multisampled),
+        None => edge_detection_pipeline.bind_group_layout(inputs.multisampled),
+    };
+
+    // It's important for this to match the BindGroupLayout defined in the PostProcessPipeline
+    let entries = DynamicBindGroupEntries::sequential((
+        // Make sure to use the source view
+        inputs.source,
+        // Use depth prepass
+        inputs.depth,
+        // Use normal prepass
+        inputs.normal,
+        // Use simple texture sampler
+        edge_detection_pipeline.sampler(inputs.sampler_filter),
+        // Use noise texture
+        inputs.noise,
+        // Use noise texture sampler
+        &edge_detection_pipeline.noise_sampler,
+        // view uniform binding
+        inputs.view_uniforms.clone(),
+        // Set the uniform binding
+        inputs.edge_detection_uniforms.clone(),
+        // Use secondary depth
+        inputs.secondary_depth,
+        // Use overlay texture
+        inputs.overlay,
+        // Use motion vector prepass
+        inputs.motion_vectors,
+        // Use previous edge mask
+        inputs.history_read,
+        // Use edge mask of the mask pass
+        edge_mask,
+        // Use threshold map
+        inputs.threshold_map,
+        // Use strength mask
+        inputs.strength_mask,
+        // Use object IDs
+        inputs.object_id,
+        // Use deferred G-buffer
+        inputs.deferred,
+        // Use deferred lighting pass IDs
+        inputs.deferred_lighting_pass_id,
+    ));
+
+    let entries = match output {
+        Some(output) => entries.extend_sequential((output,)),
+        None => entries,
+    };
+
+    render_device.create_bind_group("edge_detection_bind_group", layout, &entries)
+}
+
+/// Creates the second bind group of a pass, if its pipeline has one.
+fn create_edge_marker_bind_group(
+    render_device: &RenderDevice,
+    edge_detection_pipeline: &EdgeDetectionPipeline,
+    inputs: &EdgeDetectionPassInputs,
+) -> Option<BindGroup> {
+    if !inputs.marker_bind_group {
+        return None;
+    }
+
+    // It's important for this to match `marker_bind_group_layout_entries`
+    let entries = BindGroupEntries::sequential((
+        // Use depth of the marked entities
+        inputs.marker_depth,
+        // Use markers of the marked entities
+        inputs.marker,
+        // Use depth of the targets alone
+        inputs.xray_depth,
+        // Use curvature
+        inputs.curvature,
+        // Use colors of the marked entities
+        inputs.marker_color,
+        // Use thresholds of the marked entities
+        inputs.marker_thresholds,
+    ));
+
+    Some(render_device.create_bind_group(
+        "edge_detection_marker_bind_group",
+        &edge_detection_pipeline.marker_layout,
+        &entries,
+    ))
+}
+
+fn color_attachment(view: &TextureView) -> RenderPassColorAttachment<'_> {
+    RenderPassColorAttachment {
+        view,
+        resolve_target: None,
+        ops: Operations::default(),
+    }
+}
+
+fn draw_edge_detection_pass(
+    render_context: &mut RenderContext,
+    label: &'static str,
+    pipeline: &RenderPipeline,
+    bind_group: &BindGroup,
+    marker_bind_group: Option<&BindGroup>,
+    color_attachments: &[Option<RenderPassColorAttachment>],
+    inputs: &EdgeDetectionPassInputs,
+) {
+    let diagnostics = render_context.diagnostic_recorder();
+    let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+        label: Some(label),
+        color_attachments,
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    let pass_span = diagnostics.pass_span(&mut render_pass, label);
+
+    render_pass.set_render_pipeline(pipeline);
+    render_pass.set_bind_group(
+        0,
+        bind_group,
+        &[
+            inputs.view_uniform_offset,
+            inputs.edge_detection_uniform_offset,
+        ],
+    );
+    if let Some(marker_bind_group) = marker_bind_group {
+        render_pass.set_bind_group(1, marker_bind_group, &[]);
+    }
+    // Covers the whole target instead of only the camera viewport: cameras sharing a target share
+    // its main textures, so the other viewports are copied through to the written one. The shader
+    // limits the effect to the viewport, see `EdgeDetection::screen_rect`.
+    render_pass.draw(0..3, 0..1);
+
+    pass_span.end(&mut render_pass);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        render::{render_graph::RenderSubGraph, MainWorld},
+    };
+    use core::time::Duration;
+
+    fn key(edge_detection: &EdgeDetection) -> EdgeDetectionKey {
+        EdgeDetectionKey::new(
+            edge_detection,
+            false,
+            false,
+            None,
+            false,
+            false,
+            false,
+            false,
+        )
+    }
+
+    #[test]
+    fn missing_msaa_is_inserted_as_off() {
+        let mut world = World::new();
+        let camera = world.spawn(EdgeDetection::default()).id();
+        let multisampled = world.spawn((EdgeDetection::default(), Msaa::Sample4)).id();
+
+        world
+            .run_system_once(insert_edge_detection_prepasses)
+            .unwrap();
+
+        assert_eq!(world.get::<Msaa>(camera), Some(&Msaa::Off));
+        assert_eq!(world.get::<Msaa>(multisampled), Some(&Msaa::Sample4));
+        assert!(world.get::<DepthPrepass>(camera).is_some());
+    }
+
+    #[test]
+    fn marker_pass_follows_the_marked_entities() {
+        let mut world = World::new();
+        let camera = world.spawn(EdgeDetection::default()).id();
+        let object_id = world
+            .spawn(EdgeDetection {
+                enable_object_id: true,
+                ..default()
+            })
+            .id();
+
+        world
+            .run_system_once(insert_edge_detection_prepasses)
+            .unwrap();
+        assert!(world.get::<EdgeDetectionMarkerPass>(camera).is_none());
+        assert!(world.get::<EdgeDetectionMarkerPass>(object_id).is_some());
+
+        let excluded = world.spawn(NoEdgeDetection).id();
+        world
+            .run_system_once(insert_edge_detection_prepasses)
+            .unwrap();
+        assert!(world.get::<EdgeDetectionMarkerPass>(camera).is_some());
+
+        world.despawn(excluded);
+        world
+            .run_system_once(insert_edge_detection_prepasses)
+            .unwrap();
+        assert!(world.get::<EdgeDetectionMarkerPass>(camera).is_none());
+        assert!(world.get::<EdgeDetectionMarkerPass>(object_id).is_some());
+    }
+
+    #[test]
+    fn every_key_field_changes_the_pipeline() {
+        use core::hash::BuildHasher;
+        use std::hash::RandomState;
+
+        let hasher = RandomState::new();
+        // Color edges are on, so the fields refining them take effect.
+        let base = key(&EdgeDetection {
+            enable_color: true,
+            ..default()
+        });
+        type Toggle = (&'static str, fn(&mut EdgeDetectionKey));
+        let toggles: [Toggle; 72] = [
+            ("enable_depth", |key| key.enable_depth ^= true),
+            ("enable_normal", |key| key.enable_normal ^= true),
+            ("normal_edge_spacing", |key| key.normal_edge_spacing ^= true),
+            ("thin_edges", |key| key.thin_edges ^= true),
+            ("normal_space", |key| key.normal_space = NormalSpace::View),
+            ("enable_color", |key| key.enable_color ^= true),
+            ("chroma_only", |key| key.chroma_only ^= true),
+            ("color_edge_space", |key| {
+                key.color_edge_space = ColorEdgeSpace::Luminance;
+            }),
+            ("enable_object_id", |key| key.enable_object_id ^= true),
+            ("enable_material_id", |key| key.enable_material_id ^= true),
+            ("curvature_edges", |key| key.curvature_edges ^= true),
+            ("secondary_depth", |key| key.secondary_depth ^= true),
+            ("combine_mode", |key| {
+                key.combine_mode = EdgeCombineMode::Average;
+            }),
+            ("edge_logic", |key| key.edge_logic = EdgeLogic::All),
+            ("depth_operator", |key| {
+                key.depth_operator = EdgeOperator::Scharr
+            }),
+            ("normal_operator", |key| {
+                key.normal_operator = EdgeOperator::Roberts;
+            }),
+            ("color_operator", |key| {
+                key.color_operator = EdgeOperator::Scharr
+            }),
+            ("pixelate", |key| key.pixelate ^= true),
+            ("temporal", |key| key.temporal ^= true),
+            ("motion_gate", |key| key.motion_gate ^= true),
+            ("dilate", |key| key.dilate ^= true),
+            ("pooling", |key| key.pooling ^= true),
+            ("halo", |key| key.halo ^= true),
+            ("mask", |key| key.mask ^= true),
+            ("steep_angle_correction", |key| {
+                key.steep_angle_correction ^= true;
+            }),
+            ("blend_mode", |key| key.blend_mode = EdgeBlendMode::Invert),
+            ("edge_lighting", |key| key.edge_lighting ^= true),
+            ("edge_color_source", |key| {
+                key.edge_color_source = EdgeColorSource::NearerObject;
+            }),
+            ("exposure_compensation", |key| {
+                key.exposure_compensation = ExposureCompensation::SceneLuminance;
+            }),
+            ("uv_distortion_in_pixels", |key| {
+                key.uv_distortion_in_pixels ^= true;
+            }),
+            ("distortion_space", |key| {
+                key.distortion_space = DistortionSpace::World;
+            }),
+            ("contour", |key| key.contour ^= true),
+            ("distance_fade", |key| key.distance_fade ^= true),
+            ("near_fade", |key| key.near_fade ^= true),
+            ("viewmodel_cutoff", |key| key.viewmodel_cutoff ^= true),
+            ("radial_falloff", |key| key.radial_falloff ^= true),
+            ("radial_invert", |key| key.radial_invert ^= true),
+            ("effect_depth_range", |key| key.effect_depth_range ^= true),
+            ("volume", |key| key.volume = Some(EdgeVolumeShape::Box)),
+            ("height_range", |key| key.height_range ^= true),
+            ("fog", |key| key.fog ^= true),
+            ("depth_bands", |key| key.depth_bands ^= true),
+            ("edge_color_gradient", |key| key.edge_color_gradient ^= true),
+            ("edge_shadow", |key| key.edge_shadow ^= true),
+            ("hatching", |key| key.hatching ^= true),
+            ("halftone", |key| key.halftone ^= true),
+            ("stipple", |key| key.stipple ^= true),
+            ("contrast_guard", |key| key.contrast_guard ^= true),
+            ("contrast_guard_flip", |key| key.contrast_guard_flip ^= true),
+            ("dither", |key| key.dither ^= true),
+            ("background_override", |key| key.background_override ^= true),
+            ("background_tint", |key| key.background_tint ^= true),
+            ("background_edges", |key| key.background_edges ^= true),
+            ("threshold_map", |key| key.threshold_map ^= true),
+            ("strength_mask", |key| key.strength_mask ^= true),
+            ("marked_entities", |key| key.marked_entities ^= true),
+            ("targets_only", |key| key.targets_only ^= true),
+            ("transparent_outlines", |key| {
+                key.transparent_outlines ^= true
+            }),
+            ("xray_outlines", |key| key.xray_outlines ^= true),
+            ("mesh_params", |key| key.mesh_params ^= true),
+            ("foliage_suppression", |key| key.foliage_suppression ^= true),
+            ("overlay", |key| key.overlay ^= true),
+            ("scanlines", |key| key.scanlines ^= true),
+            ("exclusion_rect", |key| key.exclusion_rect ^= true),
+            ("screen_rect", |key| key.screen_rect ^= true),
+            ("debug_split", |key| key.debug_split ^= true),
+            ("output_alpha", |key| {
+                key.output_alpha = OutputAlpha::EdgeStrength;
+            }),
+            ("gradient_output", |key| key.gradient_output ^= true),
+            ("distance_field", |key| key.distance_field ^= true),
+            ("hdr", |key| key.hdr ^= true),
+            ("multisampled", |key| key.multisampled ^= true),
+            ("projection", |key| {
+                key.projection = ProjectionType::Orthographic;
+            }),
+        ];
+
+        for (field, toggle) in toggles {
+            let mut toggled = base;
+            toggle(&mut toggled);
+            assert!(toggled != base, "{field}");
+            assert_ne!(hasher.hash_one(toggled), hasher.hash_one(base), "{field}");
+            // The passes select the entry point and targets instead, see `specialize`.
+            if !matches!(field, "dilate" | "mask") {
+                assert_ne!(toggled.shader_defs(), base.shader_defs(), "{field}");
+            }
+        }
+    }
+
+    /// A render world extracting from `main_world`, for the tests of the extraction systems.
+    fn render_world(main_world: World) -> World {
+        let mut main = MainWorld::default();
+        *main = main_world;
+        let mut render_world = World::new();
+        render_world.insert_resource(main);
+        render_world
+    }
+
+    #[test]
+    fn object_ids_are_stable() {
+        let mut main_world = World::new();
+        main_world.spawn(EdgeDetection {
+            enable_object_id: true,
+            ..default()
+        });
+        let first = main_world.spawn(Mesh3d::default()).id();
+        let second = main_world.spawn(Mesh3d::default()).id();
+        let lightmapped = main_world
+            .spawn((Mesh3d::default(), Lightmap::default()))
+            .id();
+
+        let mut world = render_world(main_world);
+        world.init_resource::<EdgeDetectionObjectIds>();
+        world
+            .run_system_once(extract_edge_detection_object_ids)
+            .unwrap();
+
+        let object_ids = &world.resource::<EdgeDetectionObjectIds>().0;
+        let second_id = object_ids[&MainEntity::from(second)];
+        assert_ne!(object_ids[&MainEntity::from(first)], 0);
+        assert_ne!(object_ids[&MainEntity::from(first)], second_id);
+        assert!(!object_ids.contains_key(&MainEntity::from(lightmapped)));
+
+        // Removing a mesh doesn't renumber the others.
+        world.resource_mut::<MainWorld>().despawn(first);
+        world
+            .run_system_once(extract_edge_detection_object_ids)
+            .unwrap();
+        let object_ids = &world.resource::<EdgeDetectionObjectIds>().0;
+        assert_eq!(object_ids.len(), 1);
+        assert_eq!(object_ids[&MainEntity::from(second)], second_id);
+    }
+
+    /// A device for the tests creating textures, or `None` on machines without an adapter.
+    fn test_render_device() -> Option<RenderDevice> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = bevy::tasks::block_on(instance.request_adapter(&default()))?;
+        let descriptor = wgpu::DeviceDescriptor {
+            required_limits: adapter.limits(),
+            ..default()
         };
+        let (device, _) = bevy::tasks::block_on(adapter.request_device(&descriptor, None)).ok()?;
+        Some(RenderDevice::from(device))
+    }
 
-        let Some(view_uniforms_binding) = world.resource::<ViewUniforms>().uniforms.binding()
-        else {
-            return Ok(());
+    #[test]
+    fn view_textures_follow_the_resolution() {
+        let Some(render_device) = test_render_device() else {
+            return;
         };
+        let mut world = World::new();
+        world.insert_resource(render_device);
+        world.init_resource::<TextureCache>();
+        let view = world
+            .spawn((
+                ExtractedCamera {
+                    target: None,
+                    physical_viewport_size: None,
+                    physical_target_size: None,
+                    viewport: None,
+                    render_graph: Core3d.intern(),
+                    order: 0,
+                    output_mode: default(),
+                    msaa_writeback: false,
+                    clear_color: default(),
+                    sorted_camera_index_for_target: 0,
+                    exposure: 0.0,
+                    hdr: false,
+                },
+                EdgeDetection {
+                    distance_field_radius: 4,
+                    ..default()
+                },
+                DepthPrepass,
+                EdgeDetectionMarkerPass,
+            ))
+            .id();
 
-        let Some(ed_uniform_binding) = world
-            .resource::<ComponentUniforms<EdgeDetectionUniform>>()
-            .uniforms()
-            .binding()
-        else {
-            return Ok(());
+        for size in [
+            UVec2::new(640, 360),
+            UVec2::new(1280, 720),
+            UVec2::new(300, 200),
+        ] {
+            world
+                .get_mut::<ExtractedCamera>(view)
+                .unwrap()
+                .physical_target_size = Some(size);
+            world
+                .run_system_once(prepare_edge_detection_marker_textures)
+                .unwrap();
+            world
+                .run_system_once(prepare_edge_detection_distance_textures)
+                .unwrap();
+
+            let extent = Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            };
+            let marker = world.get::<EdgeDetectionMarkerTextures>(view).unwrap();
+            assert_eq!(marker.depth.texture.size(), extent);
+            assert_eq!(marker.marker.texture.size(), extent);
+            let distance = world.get::<EdgeDetectionDistanceTexture>(view).unwrap();
+            assert_eq!(distance.0.texture.size(), extent);
+        }
+    }
+
+    #[test]
+    fn marker_textures_follow_the_camera_config() {
+        let marker_key = |edge_detection: &EdgeDetection, depth_prepass: bool| {
+            EdgeDetectionKey::new(
+                edge_detection,
+                false,
+                false,
+                None,
+                false,
+                depth_prepass,
+                false,
+                false,
+            )
+        };
+        let mesh_params = EdgeDetection {
+            mesh_params: true,
+            ..default()
         };
 
-        // This will start a new "post process write", obtaining two texture
-        // views from the view target - a `source` and a `destination`.
-        // `source` is the "current" main texture and you _must_ write into
-        // `destination` because calling `post_process_write()` on the
-        // [`ViewTarget`] will internally flip the [`ViewTarget`]'s main
-        // texture to the `destination` texture. Failing to do so will cause
-        // the current main texture information to be lost.
-        let post_process = view_target.post_process_write();
-
-        // The bind_group gets created each frame.
-        //
-        // Normally, you would create a bind_group in the Queue set,
-        // but this doesn't work with the post_process_write().
-        // The reason it doesn't work is because each post_process_write will alternate the source/destination.
-        // The only way to have the correct source/destination for the bind_group
-        // is to make sure you get it during the node execution.
-        let multisampled = *msaa != Msaa::Off;
-        let bind_group = render_context.render_device().create_bind_group(
-            "edge_detection_bind_group",
-            edge_detection_pipeline.bind_group_layout(multisampled),
-            // It's important for this to match the BindGroupLayout defined in the PostProcessPipeline
-            &BindGroupEntries::sequential((
-                // Make sure to use the source view
-                post_process.source,
-                // Use depth prepass
-                &depth_texture.texture.default_view,
-                // Use normal prepass
-                &normal_texture.texture.default_view,
-                // Use simple texture sampler
-                &edge_detection_pipeline.linear_sampler,
-                // Use noise texture
-                &noise_texture.texture_view,
-                // Use noise texture sampler
-                &edge_detection_pipeline.noise_sampler,
-                // view uniform binding
-                view_uniforms_binding,
-                // Set the uniform binding
-                ed_uniform_binding,
-            )),
+        assert!(marker_key(&EdgeDetection::default(), true).marked_entities);
+        assert!(!marker_key(&EdgeDetection::default(), true).mesh_params);
+        assert!(marker_key(&mesh_params, true).mesh_params);
+        assert!(!marker_key(&mesh_params, false).marked_entities);
+        assert!(!marker_key(&mesh_params, false).mesh_params);
+    }
+
+    #[test]
+    fn color_space_is_linear_converts_colors_on_the_cpu() {
+        let gray = Color::srgb(0.5, 0.5, 0.5);
+        let linear = EdgeDetection {
+            edge_color: gray,
+            background_override: Some(gray),
+            ..default()
+        };
+        let unconverted = EdgeDetection {
+            color_space_is_linear: false,
+            ..linear.clone()
+        };
+
+        let uniform = EdgeDetectionUniform::from(&linear);
+        assert_eq!(uniform.edge_color, gray.to_linear());
+        assert_eq!(uniform.background_color, gray.to_linear());
+
+        let uniform = EdgeDetectionUniform::from(&unconverted);
+        assert_eq!(uniform.edge_color, LinearRgba::new(0.5, 0.5, 0.5, 1.0));
+        assert_eq!(
+            uniform.background_color,
+            LinearRgba::new(0.5, 0.5, 0.5, 1.0)
         );
 
-        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
-            label: Some("edge_detection_pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: post_process.destination,
-                resolve_target: None,
-                ops: Operations::default(),
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
+        // Only the uniform changes, the pipeline is shared.
+        assert!(key(&linear) == key(&unconverted));
+    }
+
+    #[test]
+    fn black_edges_stay_black_in_both_color_spaces() {
+        for color_space_is_linear in [true, false] {
+            let edge_detection = EdgeDetection {
+                color_space_is_linear,
+                ..default()
+            };
+            let uniform = EdgeDetectionUniform::from(&edge_detection);
+            assert_eq!(uniform.edge_color, LinearRgba::BLACK);
+        }
+    }
+
+    #[test]
+    fn pulse_animates_and_restores_the_edge_color() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, animate_edge_detection_pulses);
+
+        let origin = Color::srgb(1.0, 0.0, 0.0);
+        let camera = app
+            .world_mut()
+            .spawn((
+                EdgeDetection {
+                    edge_color: origin,
+                    ..default()
+                },
+                EdgeDetectionPulse {
+                    period: 2.0,
+                    min_intensity: 0.0,
+                    max_intensity: 1.0,
+                    color_a: Color::BLACK,
+                    color_b: Color::WHITE,
+                },
+            ))
+            .id();
+        let edge_color = |app: &App| app.world().get::<EdgeDetection>(camera).unwrap().edge_color;
+
+        // The start of a cycle is `color_a` at `min_intensity`.
+        app.update();
+        assert_eq!(edge_color(&app), Color::BLACK.with_alpha(0.0));
+        assert_eq!(
+            app.world()
+                .get::<EdgeDetectionPulseOrigin>(camera)
+                .unwrap()
+                .0,
+            origin
+        );
+
+        // Halfway through it's `color_b` at `max_intensity`.
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(1));
+        app.update();
+        let color = edge_color(&app).to_linear();
+        assert!((color.red - 1.0).abs() < 1e-5 && (color.alpha - 1.0).abs() < 1e-5);
+
+        app.world_mut()
+            .entity_mut(camera)
+            .remove::<EdgeDetectionPulse>();
+        app.update();
+        assert_eq!(edge_color(&app), origin);
+        assert!(app
+            .world()
+            .get::<EdgeDetectionPulseOrigin>(camera)
+            .is_none());
+    }
+
+    #[test]
+    fn first_bind_group_fits_the_webgpu_texture_limit() {
+        let sampled_textures = |entries: &[BindGroupLayoutEntry]| {
+            entries
+                .iter()
+                .filter(|entry| matches!(entry.ty, BindingType::Texture { .. }))
+                .count()
+        };
+
+        for multisampled in [false, true] {
+            for visibility in [ShaderStages::FRAGMENT, ShaderStages::COMPUTE] {
+                let entries = bind_group_layout_entries(visibility, multisampled);
+                assert!(sampled_textures(&entries) <= 16);
+            }
+        }
+        assert!(sampled_textures(&marker_bind_group_layout_entries()) <= 16);
+
+        // Views without markers or curvature leave the second bind group out.
+        let base = key(&EdgeDetection::default());
+        assert!(!base.uses_marker_bind_group());
+        for flag in [
+            EdgeDetectionKey {
+                marked_entities: true,
+                ..base
+            },
+            EdgeDetectionKey {
+                curvature_edges: true,
+                ..base
+            },
+        ] {
+            assert!(flag.uses_marker_bind_group());
+        }
+    }
+
+    #[test]
+    fn presets_stay_in_the_documented_ranges() {
+        for preset in [
+            EdgeDetectionPreset::CelShaded,
+            EdgeDetectionPreset::TechnicalDrawing,
+            EdgeDetectionPreset::InkSketch,
+            EdgeDetectionPreset::Subtle,
+        ] {
+            let ed = EdgeDetection::from(preset);
+            // Range: (0.0, inf)
+            let open = [
+                ("depth_threshold", ed.depth_threshold),
+                ("normal_threshold", ed.normal_threshold),
+                ("color_threshold", ed.color_threshold),
+                ("curvature_threshold", ed.curvature_threshold),
+                ("depth_thickness", ed.depth_thickness),
+                ("normal_thickness", ed.normal_thickness),
+                ("color_thickness", ed.color_thickness),
+                ("edge_response_gamma", ed.edge_response_gamma),
+                ("overlay_scale", ed.overlay_scale),
+            ];
+            for (field, value) in open {
+                assert!(value.is_finite() && value > 0.0, "{preset:?} {field}");
+            }
+
+            // Range: [0.0, inf)
+            let non_negative = [
+                ("steep_angle_multiplier", ed.steep_angle_multiplier),
+                ("edge_emissive_strength", ed.edge_emissive_strength),
+                ("halo_strength", ed.halo_strength),
+                ("uv_distortion_strength.x", ed.uv_distortion_strength.x),
+                ("uv_distortion_strength.y", ed.uv_distortion_strength.y),
+                ("uv_distortion_strength2.x", ed.uv_distortion_strength2.x),
+                ("uv_distortion_strength2.y", ed.uv_distortion_strength2.y),
+            ];
+            for (field, value) in non_negative {
+                assert!(value.is_finite() && value >= 0.0, "{preset:?} {field}");
+            }
+
+            // Range: [0.0, 1.0]
+            let fractions = [
+                ("steep_angle_threshold", ed.steep_angle_threshold),
+                ("nearer_object_darkening", ed.nearer_object_darkening),
+                ("contrast_guard", ed.contrast_guard),
+                ("non_edge_desaturation", ed.non_edge_desaturation),
+                ("duotone_threshold", ed.duotone_threshold),
+                ("background_tint_strength", ed.background_tint_strength),
+                ("overlay_opacity", ed.overlay_opacity),
+                ("scanline_strength", ed.scanline_strength),
+            ];
+            for (field, value) in fractions {
+                assert!((0.0..=1.0).contains(&value), "{preset:?} {field}");
+            }
+            assert!((0.0..1.0).contains(&ed.temporal_blend), "{preset:?}");
+            assert!(
+                ed.scanline_frequency > 0.0 && ed.scanline_frequency <= 0.5,
+                "{preset:?}"
+            );
+            assert!(ed.contour_strength.is_finite(), "{preset:?}");
+
+            let uniform = EdgeDetectionUniform::from(&ed);
+            assert!(!format!("{uniform:?}").contains("NaN"), "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn halo_radius_is_capped() {
+        let wide = EdgeDetection {
+            halo_radius: 64,
+            halo_strength: 1.0,
+            ..default()
+        };
+        assert!(wide.halo());
+        assert_eq!(
+            EdgeDetectionUniform::from(&wide).halo_radius,
+            MAX_HALO_RADIUS
+        );
+    }
+
+    #[test]
+    fn mesh_params_are_merged_per_mesh() {
+        let mut main_world = World::new();
+        let overridden = main_world
+            .spawn((
+                Mesh3d::default(),
+                EdgeDetectionOverride {
+                    normal_threshold: Some(0.5),
+                    ..default()
+                },
+            ))
+            .id();
+        let both = main_world
+            .spawn((
+                Mesh3d::default(),
+                EdgeDetectionColor(Color::WHITE),
+                EdgeDetectionOverride {
+                    depth_threshold: Some(2.0),
+                    ..default()
+                },
+            ))
+            .id();
+        let thresholds = EdgeDetectionOverride {
+            depth_threshold: Some(1.0),
+            normal_threshold: Some(1.0),
+            color_threshold: Some(1.0),
+        };
+        main_world.spawn((Mesh3d::default(), thresholds, NoEdgeDetection));
+        main_world.spawn((Mesh3d::default(), thresholds, Lightmap::default()));
+
+        let mut world = render_world(main_world);
+        world.init_resource::<EdgeDetectionMeshParams>();
+        world
+            .run_system_once(extract_edge_detection_mesh_params)
+            .unwrap();
+
+        // `None` keeps the threshold of the camera, and the excluded and lightmapped meshes are
+        // left out.
+        let mesh_params = &world.resource::<EdgeDetectionMeshParams>().0;
+        assert_eq!(mesh_params.len(), 2);
+        let overridden = mesh_params[&MainEntity::from(overridden)];
+        assert_eq!(overridden.thresholds, Vec4::new(0.0, 0.5, 0.0, 0.0));
+        assert_eq!(overridden.color, LinearRgba::NONE);
+        let both = mesh_params[&MainEntity::from(both)];
+        assert_eq!(both.thresholds, Vec4::new(2.0, 0.0, 0.0, 0.0));
+        assert_eq!(both.color, LinearRgba::WHITE);
+    }
+
+    #[test]
+    fn mesh_colors_are_linear_and_transparent_ones_fall_back() {
+        let orange = Color::srgb(1.0, 0.5, 0.0);
+        let mut main_world = World::new();
+        let colored = main_world
+            .spawn((Mesh3d::default(), EdgeDetectionColor(orange)))
+            .id();
+        let transparent = main_world
+            .spawn((Mesh3d::default(), EdgeDetectionColor(Color::NONE)))
+            .id();
+
+        let mut world = render_world(main_world);
+        world.init_resource::<EdgeDetectionMeshParams>();
+        world
+            .run_system_once(extract_edge_detection_mesh_params)
+            .unwrap();
+
+        let mesh_params = &world.resource::<EdgeDetectionMeshParams>().0;
+        assert_eq!(
+            mesh_params[&MainEntity::from(colored)].color,
+            orange.to_linear()
+        );
+        // The shader keeps `EdgeDetection::edge_color` where the color is fully transparent, like
+        // for the meshes without one.
+        assert_eq!(mesh_params[&MainEntity::from(transparent)].color.alpha, 0.0);
+    }
+
+    #[test]
+    fn bundle_adds_the_prepasses_and_msaa() {
+        let mut world = World::new();
+        let camera = world.spawn(EdgeDetectionBundle::default()).id();
+
+        assert!(world.get::<EdgeDetection>(camera).is_some());
+        assert!(world.get::<DepthPrepass>(camera).is_some());
+        assert!(world.get::<NormalPrepass>(camera).is_some());
+        assert_eq!(world.get::<Msaa>(camera), Some(&Msaa::Off));
+    }
+
+    /// Fails when the WGSL a mirror below was transcribed from changes, so the mirror is updated
+    /// with the shader instead of testing stale code.
+    ///
+    /// `lines` are compared without their indentation and have to be consecutive in the shader.
+    fn assert_mirrors(lines: &[&str]) {
+        let shader: Vec<&str> = include_str!("edge_detection.wgsl")
+            .lines()
+            .map(str::trim)
+            .collect();
+        assert!(
+            shader.windows(lines.len()).any(|window| window == lines),
+            "`edge_detection.wgsl` no longer contains the mirrored code:\n{}",
+            lines.join("\n")
+        );
+    }
+
+    /// Mirror of `pcg_hash` in `edge_detection.wgsl`.
+    fn pcg_hash(input: u32) -> u32 {
+        let state = input.wrapping_mul(747796405).wrapping_add(2891336453);
+        let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+        (word >> 22) ^ word
+    }
+
+    /// Mirror of `noise_seed_offset` in `edge_detection.wgsl`.
+    fn noise_seed_offset(seed: u32) -> Vec2 {
+        if seed == 0 {
+            return Vec2::ZERO;
+        }
+        let hash = UVec2::new(pcg_hash(seed), pcg_hash(seed ^ 0x9e3779b9));
+        (hash & UVec2::splat(0xffff)).as_vec2() / 65536.0
+    }
+
+    #[test]
+    fn noise_seed_is_deterministic() {
+        assert_mirrors(&[
+            "fn pcg_hash(input: u32) -> u32 {",
+            "let state = input * 747796405u + 2891336453u;",
+            "let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;",
+            "return (word >> 22u) ^ word;",
+            "}",
+        ]);
+        assert_mirrors(&[
+            "fn noise_seed_offset() -> vec2f {",
+            "let seed = ed_uniform.noise_seed;",
+            "if seed == 0u {",
+            "return vec2f(0.0);",
+            "}",
+            "",
+            "// The noise texture repeats, so a fraction of it is enough.",
+            "let hash = vec2u(pcg_hash(seed), pcg_hash(seed ^ 0x9e3779b9u));",
+            "return vec2f(hash & vec2u(0xffffu)) / 65536.0;",
+            "}",
+        ]);
+
+        let seeded = EdgeDetection {
+            noise_seed: 42,
+            ..default()
+        };
+        assert_eq!(EdgeDetectionUniform::from(&seeded).noise_seed, 42);
+        assert!(key(&seeded) == key(&EdgeDetection::default()));
+
+        assert_eq!(noise_seed_offset(0), Vec2::ZERO);
+        let offsets: Vec<Vec2> = (1..256).map(noise_seed_offset).collect();
+        for (i, offset) in offsets.iter().enumerate() {
+            // Same seed, bit-identical offset.
+            assert_eq!(
+                noise_seed_offset(i as u32 + 1).to_array(),
+                offset.to_array()
+            );
+            assert!(offset.cmpge(Vec2::ZERO).all() && offset.cmplt(Vec2::ONE).all());
+            // Different seeds, different patterns.
+            assert!(offsets[i + 1..].iter().all(|other| other != offset));
+        }
+    }
+
+    /// Mirror of `pixel_coord` in `edge_detection.wgsl`, for one axis and without `SCREEN_RECT`.
+    /// Checked against the shader by `kernel_taps_load_whole_texels`.
+    fn pixel_coord(uv: f32, texture_size: f32) -> i32 {
+        ((uv * texture_size).floor() as i32).clamp(0, texture_size as i32 - 1)
+    }
+
+    #[test]
+    fn kernel_taps_load_whole_texels() {
+        assert_mirrors(&[
+            "fn pixel_coord(uv: vec2f) -> vec2i {",
+            "let coord = vec2i(floor(clamp_to_screen_rect(uv) * texture_size));",
+            "return clamp(coord, vec2i(0), vec2i(texture_size) - 1);",
+            "}",
+        ]);
+
+        for size in [1u32, 7, 320, 1080, 1920, 2160, 4096] {
+            let (texture_size, texel_size) = (size as f32, 1.0 / size as f32);
+            for x in 0..size as i32 {
+                // The fullscreen triangle interpolates the uv at the pixel center.
+                let uv = (x as f32 + 0.5) / texture_size;
+                assert_eq!(pixel_coord(uv, texture_size), x, "size {size}");
+                for thickness in 1..=3 {
+                    let offset = texel_size * thickness as f32;
+                    let clamped = |coord: i32| coord.clamp(0, size as i32 - 1);
+                    assert_eq!(
+                        pixel_coord(uv - offset, texture_size),
+                        clamped(x - thickness)
+                    );
+                    assert_eq!(
+                        pixel_coord(uv + offset, texture_size),
+                        clamped(x + thickness)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn silhouette_edges_straddle_the_depth_step() {
+        // A row of the depth prepass with a silhouette between texels `silhouette - 1` and
+        // `silhouette`.
+        let (size, silhouette) = (1920, 1234);
+        let depth = |coord: i32| if coord < silhouette { 1.0 } else { 0.0 };
+        let texture_size = size as f32;
+
+        let edges: Vec<i32> = (0..size)
+            .filter(|&x| {
+                let uv = (x as f32 + 0.5) / texture_size;
+                let left = depth(pixel_coord(uv - 1.0 / texture_size, texture_size));
+                let right = depth(pixel_coord(uv + 1.0 / texture_size, texture_size));
+                f32::abs(right - left) > 0.5
+            })
+            .collect();
+        assert_eq!(edges, [silhouette - 1, silhouette]);
+    }
+
+    #[cfg(feature = "serialize")]
+    fn round_trip(edge_detection: &EdgeDetection) {
+        let serialized = ron::to_string(edge_detection).unwrap();
+        let deserialized: EdgeDetection = ron::from_str(&serialized).unwrap();
+        assert_eq!(ron::to_string(&deserialized).unwrap(), serialized);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn presets_round_trip_through_ron() {
+        round_trip(&EdgeDetection::default());
+        round_trip(&EdgeDetection {
+            depth_threshold: 0.5,
+            enable_color: true,
+            edge_color: Color::srgba(0.1, 0.2, 0.3, 0.4),
+            background_override: Some(Color::WHITE),
+            pixelate_grid: Some(UVec2::new(320, 180)),
+            combine_mode: EdgeCombineMode::Add,
+            hatching: Some(HatchingSettings::default()),
+            noise_seed: 7,
+            ..default()
         });
+    }
 
-        render_pass.set_render_pipeline(pipeline);
-        render_pass.set_bind_group(
-            0,
-            &bind_group,
-            &[view_uniform_index.offset, ed_uniform_index.index()],
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn partial_presets_keep_defaults() {
+        let edge_detection: EdgeDetection =
+            ron::from_str("(depth_threshold: 2.5, enable_color: true)").unwrap();
+        let expected = EdgeDetection {
+            depth_threshold: 2.5,
+            enable_color: true,
+            ..default()
+        };
+        assert_eq!(
+            ron::to_string(&edge_detection).unwrap(),
+            ron::to_string(&expected).unwrap()
         );
-        render_pass.draw(0..3, 0..1);
+    }
 
-        Ok(())
+    /// Mirror of `posterize` in `edge_detection.wgsl` with `HDR` defined, for one channel.
+    fn posterize_hdr(color: f32, levels: u32) -> f32 {
+        let mapped = color / (1.0 + color.max(0.0));
+        let steps = (levels.max(2) - 1) as f32;
+        let quantized = ((mapped.max(0.0).sqrt() * steps).round() / steps).min(1.0 - 0.5 / steps);
+        let linear = quantized * quantized;
+        linear / (1.0 - linear).max(1e-4)
+    }
+
+    #[test]
+    fn hdr_posterize_is_finite_and_monotonic() {
+        assert_mirrors(&[
+            "fn posterize(color: vec3f, levels: u32) -> vec3f {",
+            "#ifdef HDR",
+            "// A cheap Reinhard tonemap brings HDR values into [0, 1) before quantizing; it's inverted afterwards.",
+            "let mapped = color / (1.0 + max(color, vec3f(0.0)));",
+            "#else",
+            "let mapped = saturate(color);",
+            "#endif",
+            "",
+            "// Quantize in an approximately perceptual (gamma 2) space so dark steps aren't crushed.",
+            "let steps = f32(max(levels, 2u) - 1u);",
+            "var quantized = round(sqrt(max(mapped, vec3f(0.0))) * steps) / steps;",
+            "",
+            "#ifdef HDR",
+            "// The top level would invert to 1 / 1e-4, keep it half a step below 1.0 instead.",
+            "quantized = min(quantized, vec3f(1.0 - 0.5 / steps));",
+            "#endif",
+            "",
+            "let linear = quantized * quantized;",
+            "",
+            "#ifdef HDR",
+            "return linear / max(1.0 - linear, vec3f(1e-4));",
+            "#else",
+            "return linear;",
+            "#endif",
+            "}",
+        ]);
+
+        for levels in 2..=16 {
+            let mut previous = 0.0;
+            for i in 0..=10_000 {
+                let color = (i as f32 * 0.01).powi(2);
+                let posterized = posterize_hdr(color, levels);
+                assert!(posterized.is_finite(), "levels {levels}, color {color}");
+                assert!(posterized >= previous, "levels {levels}, color {color}");
+                // The top level stays close to the others instead of jumping to 1e4.
+                assert!(posterized < 1e3, "levels {levels}, color {color}");
+                previous = posterized;
+            }
+        }
     }
 }
@@ -0,0 +1,231 @@
+//! Hover and selection outlines driven by `bevy_picking`, see [`EdgeDetectionPickingPlugin`].
+
+use bevy::{
+    picking::events::{Click, Out, Over, Pointer},
+    prelude::*,
+};
+
+#[cfg(doc)]
+use crate::EdgeDetection;
+use crate::{EdgeDetectionColor, EdgeDetectionTarget};
+
+/// Outlines the [`EdgeDetectionPickable`] meshes under the pointer, and the ones clicked on.
+///
+/// Hovered and selected meshes get [`EdgeDetectionTarget`], so they are outlined by the cameras
+/// with [`EdgeDetection::targets_only`]. Their [`EdgeDetectionColor`] follows
+/// [`EdgeDetectionPickingSettings`] and fades in and out with the outlines in the cameras with
+/// [`EdgeDetection::mesh_params`].
+///
+/// A picking backend must be added separately, e.g. `MeshPickingPlugin`.
+pub struct EdgeDetectionPickingPlugin;
+
+impl Plugin for EdgeDetectionPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EdgeDetectionPickingSettings>()
+            .register_type::<EdgeDetectionPickingSettings>()
+            .register_type::<EdgeDetectionPickable>()
+            .register_type::<EdgeDetectionPickingState>()
+            .add_observer(hover_pickable)
+            .add_observer(unhover_pickable)
+            .add_observer(select_pickable)
+            .add_systems(
+                PostUpdate,
+                (fade_picking_outlines, update_picking_edge_colors).chain(),
+            );
+    }
+}
+
+/// The colors and fade durations of the outlines of [`EdgeDetectionPickingPlugin`].
+#[derive(Resource, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct EdgeDetectionPickingSettings {
+    /// Edge color of the hovered meshes.
+    pub hover_color: Color,
+    /// Edge color of the selected meshes.
+    pub selection_color: Color,
+    /// Duration in seconds an outline takes to fully appear, 0.0 to appear at once.
+    pub fade_in: f32,
+    /// Duration in seconds an outline takes to disappear, 0.0 to disappear at once.
+    pub fade_out: f32,
+    /// Whether clicking a mesh keeps the other ones selected. Otherwise they are deselected, and
+    /// only one mesh is selected at a time.
+    pub multi_select: bool,
+}
+
+impl Default for EdgeDetectionPickingSettings {
+    fn default() -> Self {
+        Self {
+            hover_color: Color::srgb(1.0, 0.9, 0.4),
+            selection_color: Color::srgb(1.0, 0.5, 0.0),
+            fade_in: 0.1,
+            fade_out: 0.25,
+            multi_select: false,
+        }
+    }
+}
+
+/// Marks a mesh as outlined by [`EdgeDetectionPickingPlugin`] while hovered or selected.
+///
+/// The plugin inserts and removes [`EdgeDetectionTarget`] and [`EdgeDetectionColor`] on these
+/// meshes, so they shouldn't be added to them by hand. Pointer events bubble up to the parents,
+/// but only the entity with this component is outlined, so it belongs on the entity with
+/// [`Mesh3d`].
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+#[require(EdgeDetectionPickingState)]
+pub struct EdgeDetectionPickable;
+
+/// The hover and selection state of an [`EdgeDetectionPickable`] mesh.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionPickingState {
+    /// Whether a pointer is over the mesh.
+    pub hovered: bool,
+    /// Whether the mesh is selected. Clicking it toggles the selection, and may be set by hand.
+    pub selected: bool,
+    /// Opacity of the hover outline, fading towards 1.0 while hovered and 0.0 otherwise.
+    pub hover_weight: f32,
+    /// Opacity of the selection outline, fading towards 1.0 while selected and 0.0 otherwise.
+    pub selection_weight: f32,
+}
+
+impl EdgeDetectionPickingState {
+    /// Opacity of the outline of the mesh.
+    pub fn weight(&self) -> f32 {
+        self.hover_weight.max(self.selection_weight)
+    }
+}
+
+/// Marks an [`EdgeDetectionPickable`] mesh as hovered when a pointer enters it.
+pub fn hover_pickable(
+    trigger: Trigger<Pointer<Over>>,
+    mut pickables: Query<&mut EdgeDetectionPickingState, With<EdgeDetectionPickable>>,
+) {
+    if let Ok(mut state) = pickables.get_mut(trigger.entity()) {
+        state.hovered = true;
+    }
+}
+
+/// Marks an [`EdgeDetectionPickable`] mesh as no longer hovered when the pointer leaves it.
+pub fn unhover_pickable(
+    trigger: Trigger<Pointer<Out>>,
+    mut pickables: Query<&mut EdgeDetectionPickingState, With<EdgeDetectionPickable>>,
+) {
+    if let Ok(mut state) = pickables.get_mut(trigger.entity()) {
+        state.hovered = false;
+    }
+}
+
+/// Toggles the selection of a clicked [`EdgeDetectionPickable`] mesh, see
+/// [`EdgeDetectionPickingSettings::multi_select`].
+pub fn select_pickable(
+    trigger: Trigger<Pointer<Click>>,
+    settings: Res<EdgeDetectionPickingSettings>,
+    mut pickables: Query<(Entity, &mut EdgeDetectionPickingState), With<EdgeDetectionPickable>>,
+) {
+    let clicked = trigger.entity();
+    if !pickables.contains(clicked) {
+        return;
+    }
+
+    for (entity, mut state) in &mut pickables {
+        if entity == clicked {
+            state.selected = !state.selected;
+        } else if !settings.multi_select && state.selected {
+            state.selected = false;
+        }
+    }
+}
+
+/// Fades the outlines of [`EdgeDetectionPickable`] meshes, and adds [`EdgeDetectionTarget`] to the
+/// ones with a visible outline.
+pub fn fade_picking_outlines(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<EdgeDetectionPickingSettings>,
+    mut pickables: Query<
+        (
+            Entity,
+            &mut EdgeDetectionPickingState,
+            Has<EdgeDetectionTarget>,
+        ),
+        With<EdgeDetectionPickable>,
+    >,
+) {
+    let fade = |weight: f32, visible: bool| {
+        let (target, duration) = if visible {
+            (1.0, settings.fade_in)
+        } else {
+            (0.0, settings.fade_out)
+        };
+        if duration <= 0.0 {
+            return target;
+        }
+
+        let delta = time.delta_secs() / duration;
+        if visible {
+            (weight + delta).min(target)
+        } else {
+            (weight - delta).max(target)
+        }
+    };
+
+    for (entity, mut state, outlined) in &mut pickables {
+        let hover_weight = fade(state.hover_weight, state.hovered);
+        let selection_weight = fade(state.selection_weight, state.selected);
+        // Only touch the state when it changes, so change detection stays meaningful.
+        if hover_weight != state.hover_weight || selection_weight != state.selection_weight {
+            state.hover_weight = hover_weight;
+            state.selection_weight = selection_weight;
+        }
+
+        let visible = state.weight() > 0.0;
+        if visible && !outlined {
+            commands.entity(entity).insert(EdgeDetectionTarget);
+        } else if !visible && outlined {
+            commands.entity(entity).remove::<EdgeDetectionTarget>();
+        }
+    }
+}
+
+/// Sets the [`EdgeDetectionColor`] of the outlined [`EdgeDetectionPickable`] meshes from their
+/// hover and selection, and removes it from the others.
+///
+/// A hovered mesh turns to the selection color as it gets selected, and the opacity follows its
+/// most visible outline.
+#[allow(clippy::type_complexity)]
+pub fn update_picking_edge_colors(
+    mut commands: Commands,
+    settings: Res<EdgeDetectionPickingSettings>,
+    mut pickables: Query<
+        (
+            Entity,
+            &EdgeDetectionPickingState,
+            Option<&mut EdgeDetectionColor>,
+        ),
+        With<EdgeDetectionPickable>,
+    >,
+) {
+    for (entity, state, edge_color) in &mut pickables {
+        let weight = state.weight();
+        if weight <= 0.0 {
+            if edge_color.is_some() {
+                commands.entity(entity).remove::<EdgeDetectionColor>();
+            }
+            continue;
+        }
+
+        let color = settings
+            .hover_color
+            .mix(&settings.selection_color, state.selection_weight);
+        let color = color.with_alpha(color.alpha() * weight);
+
+        match edge_color {
+            Some(mut edge_color) if edge_color.0 != color => edge_color.0 = color,
+            Some(_) => {}
+            None => {
+                commands.entity(entity).insert(EdgeDetectionColor(color));
+            }
+        }
+    }
+}